@@ -0,0 +1,1063 @@
+// Headless runner, see `mehrmux-cli --help`. It runs the same engine as the app without opening
+// a window, so simulations can be scripted.
+
+use mehrmux_engine::compare::Comparison;
+use mehrmux_engine::decision;
+use mehrmux_engine::error::SimError;
+use mehrmux_engine::events;
+use mehrmux_engine::exercise::{self, Difficulty, Exercise, ExerciseSpec, Property};
+use mehrmux_engine::grading::{self, Grade, Segment, Verdict};
+use mehrmux_engine::plugin::PluginPolicy;
+use mehrmux_engine::policy::ScriptPolicy;
+use mehrmux_engine::queue_engine::{MetricValue, QueueDiscipline, Queues};
+use mehrmux_engine::report;
+use mehrmux_engine::sampling::{self, DEFAULT_SAMPLE_PERIOD};
+use mehrmux_engine::scenario::{ProcessSource, Scenario};
+use mehrmux_engine::sched_trace::{KernelRun, Replay};
+use mehrmux_engine::simulator::{RunOptions, RunResult};
+use mehrmux_engine::swf::SwfOptions;
+use mehrmux_engine::sweep::{self, Estimator, SweepAxis, SweepParameter, SweepRange, SweepSpec, WarmUp};
+use mehrmux_engine::workload::{self, TimeUnit, WorkloadEntry, WorkloadFormat};
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+// Utils -------------------------------------------------------------------------------------------
+
+// the disciplines a level of MLQ / MLFQ can use, see Simulator::generate_queue
+const LEVEL_DISCIPLINES: [&str; 8] =
+    ["RR", "SJF", "SRTF", "FCFS", "SPN", "HRRN", "SCRIPT", "PLUGIN"];
+
+// Exit codes, so scripts can tell a bad command line from a bad workload, a failed write or a
+// run that failed halfway.
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_INVALID_INPUT: i32 = 3;
+pub const EXIT_IO: i32 = 4;
+pub const EXIT_FAILED: i32 = 5;
+
+struct CliError {
+    code: i32,
+    message: String,
+}
+
+impl CliError {
+    fn usage(message: String) -> Self {
+        CliError { code: EXIT_USAGE, message }
+    }
+
+    fn invalid(message: String) -> Self {
+        CliError { code: EXIT_INVALID_INPUT, message }
+    }
+
+    fn failed(error: SimError) -> Self {
+        match error {
+            // the run never started, something it was given is wrong
+            SimError::InvalidConfig { .. } | SimError::InvalidRate { .. } => {
+                CliError::usage(error.to_string())
+            }
+            _ => CliError {
+                code: EXIT_FAILED,
+                message: format!("simulation failed: {}", error),
+            },
+        }
+    }
+
+    fn io(path: &Path, e: std::io::Error) -> Self {
+        CliError {
+            code: EXIT_IO,
+            message: format!("{}: {}", path.display(), e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+// Arguments ---------------------------------------------------------------------------------------
+
+/// Runs a scheduling simulation without the UI and prints its metrics.
+#[derive(Parser)]
+#[command(name = "mehrmux-cli", version)]
+struct Args {
+    /// JSON scenario file with any of the options below, flags given as well override it
+    #[arg(long)]
+    scenario: Option<PathBuf>,
+    /// FIFO, FCFS, SPN, SJF, HRRN, RR, SRTF, MLQ, MLFQ, SCRIPT or PLUGIN
+    #[arg(short, long)]
+    algorithm: Option<String>,
+    /// the four level disciplines of MLQ / MLFQ, comma separated (e.g. RR,RR,FCFS,FCFS)
+    #[arg(short, long, value_delimiter = ',')]
+    disciplines: Option<Vec<String>>,
+    /// time quantum in milliseconds
+    #[arg(short, long)]
+    quantum: Option<u64>,
+    /// context switch time in milliseconds
+    #[arg(short, long)]
+    context_switch: Option<u64>,
+    /// MLFQ priority boost period in milliseconds, no boost when not given
+    #[arg(long)]
+    boost_period: Option<u64>,
+    /// Rhai scheduling script for the SCRIPT algorithm and levels
+    #[arg(long)]
+    script: Option<PathBuf>,
+    /// WebAssembly policy (.wasm or .wat) for the PLUGIN algorithm and levels
+    #[arg(long)]
+    plugin: Option<PathBuf>,
+    /// seed for the generated processes, the same seed gives the same run
+    #[arg(short, long)]
+    seed: Option<u64>,
+    /// number of processes to generate when no workload is given
+    #[arg(short = 'n', long)]
+    processes: Option<usize>,
+    /// arrival rate λ of the generated processes, per second
+    #[arg(long)]
+    arrival_lambda: Option<f64>,
+    /// rate λ of the generated CPU burst times, per second
+    #[arg(long)]
+    burst_lambda: Option<f64>,
+    /// workload file (csv, json, swf or a sched trace) to run instead of generated processes
+    #[arg(short, long)]
+    workload: Option<PathBuf>,
+    /// format of the workload file, guessed from its contents when not given
+    #[arg(long)]
+    workload_format: Option<String>,
+    /// unit of the workload's numbers without a unit, ms or s
+    #[arg(long)]
+    unit: Option<String>,
+    /// multiplies every submit and run time of an SWF trace, 0.001 replays a trace second as a
+    /// millisecond (1 by default)
+    #[arg(long)]
+    swf_time_scale: Option<f64>,
+    /// imports only the first N jobs of an SWF trace that can be replayed
+    #[arg(long)]
+    swf_limit: Option<usize>,
+    /// multiplies the run time of an SWF job by its processors, since there's a single CPU
+    #[arg(long)]
+    swf_scale_by_processors: bool,
+    /// how the metrics are printed
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+    /// writes the event log of the run as JSON Lines
+    #[arg(long)]
+    log: Option<PathBuf>,
+    /// prints why every process was dispatched, preempted, demoted or promoted, with the table
+    /// format. The log written by --log has the same decisions for the others
+    #[arg(long)]
+    explain: bool,
+    /// writes a Gantt chart of the run as SVG
+    #[arg(long)]
+    svg: Option<PathBuf>,
+    /// writes an HTML report of the run
+    #[arg(long)]
+    report: Option<PathBuf>,
+    /// how often the queues are sampled, in simulated milliseconds (100 by default, 0 for never)
+    #[arg(long)]
+    sample_period: Option<u64>,
+    /// writes the queue samples of the run as CSV
+    #[arg(long)]
+    samples: Option<PathBuf>,
+    /// flags a process as starving once it has waited this many milliseconds
+    #[arg(long)]
+    starvation_threshold: Option<u64>,
+    /// runs the same processes through each of these algorithms instead of --algorithm,
+    /// comma separated (e.g. FCFS,SJF,RR)
+    #[arg(long, value_delimiter = ',')]
+    compare: Option<Vec<String>>,
+    /// the metric --compare ranks the algorithms by
+    #[arg(long)]
+    rank_by: Option<String>,
+    /// sweeps a parameter over a range (time_quantum=5:50:5) or a list (context_switch=0,1,5),
+    /// can be repeated. Parameters: time_quantum, context_switch, arrival_lambda,
+    /// burst_lambda, mlfq_boost_period, and cpus, which the single CPU simulator only takes as 1
+    #[arg(long)]
+    sweep: Vec<String>,
+    /// independent runs per sweep point, each with its own seed derived from --seed
+    #[arg(long)]
+    replications: Option<usize>,
+    /// confidence level of the intervals a sweep reports
+    #[arg(long)]
+    confidence: Option<f64>,
+    /// the metrics a sweep reports, comma separated
+    #[arg(long, value_delimiter = ',')]
+    metrics: Option<Vec<String>>,
+    /// leaves the first N processes to finish out of every replication's metrics
+    #[arg(long, conflicts_with = "welch")]
+    warm_up: Option<usize>,
+    /// finds the warm-up with Welch's method, using a moving average of this many points
+    /// on each side
+    #[arg(long)]
+    welch: Option<usize>,
+    /// how close to its steady state level Welch's curve has to be, relative
+    #[arg(long, default_value_t = 0.05)]
+    welch_tolerance: f64,
+    /// estimates each metric from batch means of one long run instead of one sample per
+    /// replication
+    #[arg(long)]
+    batch_means: Option<usize>,
+    /// grades a homework JSON file, a student's schedule and metrics for a set of processes,
+    /// instead of running anything else
+    #[arg(long)]
+    grade: Option<PathBuf>,
+    /// writes a scheduling exercise with its answer keys instead of running anything else,
+    /// easy, medium or hard. Takes --seed, --compare for the algorithms, --quantum and
+    /// --context-switch; the table format prints it as Markdown, csv prints its processes
+    #[arg(long)]
+    exercise: Option<String>,
+    /// what the exercise's workload has to show, comma separated: tie, preemption, idle
+    #[arg(long, value_delimiter = ',', requires = "exercise")]
+    properties: Option<Vec<String>>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ScenarioFile {
+    algorithm: Option<String>,
+    disciplines: Option<Vec<String>>,
+    quantum: Option<u64>,
+    context_switch: Option<u64>,
+    boost_period: Option<u64>,
+    script: Option<PathBuf>, // relative to the scenario file
+    plugin: Option<PathBuf>, // relative to the scenario file
+    sample_period: Option<u64>,
+    starvation_threshold: Option<u64>,
+    seed: Option<u64>,
+    processes: Option<usize>,
+    arrival_lambda: Option<f64>,
+    burst_lambda: Option<f64>,
+    workload: Option<PathBuf>, // relative to the scenario file
+    workload_format: Option<String>,
+    unit: Option<String>,
+    swf: Option<SwfOptions>,
+    compare: Option<Vec<String>>,
+    rank_by: Option<String>,
+    sweep: Option<Vec<SweepAxis>>,
+    replications: Option<usize>,
+    confidence: Option<f64>,
+    metrics: Option<Vec<String>>,
+    warm_up: Option<WarmUp>,
+    batch_means: Option<usize>,
+}
+
+fn read_file(path: &Path) -> Result<String, CliError> {
+    std::fs::read_to_string(path).map_err(|e| CliError::io(path, e))
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), CliError> {
+    std::fs::write(path, contents).map_err(|e| CliError::io(path, e))
+}
+
+fn load_scenario(path: &Path) -> Result<ScenarioFile, CliError> {
+    let contents = read_file(path)?;
+    let mut scenario: ScenarioFile = serde_json::from_str(&contents)
+        .map_err(|e| CliError::invalid(format!("{}: {}", path.display(), e)))?;
+    if let Some(dir) = path.parent() {
+        scenario.workload = scenario.workload.map(|workload| dir.join(workload));
+        scenario.script = scenario.script.map(|script| dir.join(script));
+        scenario.plugin = scenario.plugin.map(|plugin| dir.join(plugin));
+    }
+    Ok(scenario)
+}
+
+fn parse_disciplines(disciplines: Vec<String>) -> Result<[QueueDiscipline; 4], CliError> {
+    let disciplines: Vec<String> = disciplines.iter().map(|d| d.trim().to_uppercase()).collect();
+    if let Some(unknown) = disciplines
+        .iter()
+        .find(|d| !LEVEL_DISCIPLINES.contains(&d.as_str()))
+    {
+        return Err(CliError::usage(format!(
+            "unknown discipline \"{}\", expected one of {}",
+            unknown,
+            LEVEL_DISCIPLINES.join(", ")
+        )));
+    }
+    let count = disciplines.len();
+    disciplines
+        .iter()
+        .filter_map(|d| QueueDiscipline::from_name(d))
+        .collect::<Vec<QueueDiscipline>>()
+        .try_into()
+        .map_err(|_| CliError::usage(format!("expected 4 disciplines, one per level, found {}", count)))
+}
+
+// Output ------------------------------------------------------------------------------------------
+
+fn format_metric(value: &MetricValue) -> String {
+    match value {
+        MetricValue::DurationValue(d) => format!("{:.3} ms", d.as_secs_f64() * 1000.0),
+        MetricValue::PercentageValue(p) => format!("{:.2}%", p),
+        MetricValue::IntegerValue(i) => i.to_string(),
+        MetricValue::StringValue(s) => s.clone(),
+        MetricValue::FloatValue(f) => format!("{:.4}", f),
+    }
+}
+
+/// ` (+1.5%)` after a predicted value, nothing when there was nothing to divide by.
+fn percent(relative_error: Option<f64>) -> String {
+    relative_error.map_or(String::new(), |e| format!(" ({:+.1}%)", e * 100.0))
+}
+
+fn csv_value(value: Option<f64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default() // an empty cell
+}
+
+fn metric_rows(metrics: &HashMap<String, MetricValue>) -> Vec<(&String, String)> {
+    let mut rows: Vec<(&String, String)> = metrics
+        .iter()
+        .map(|(name, value)| (name, format_metric(value)))
+        .collect();
+    rows.sort();
+    rows
+}
+
+fn scopes(result: &RunResult) -> Vec<(String, &HashMap<String, MetricValue>)> {
+    // the whole run first, then every level of a multilevel queue
+    let mut scopes = vec![(String::from("overall"), &result.metrics)];
+    for (index, metrics) in result.level_metrics.iter().enumerate() {
+        scopes.push((format!("queue_{}", index + 1), metrics));
+    }
+    scopes
+}
+
+fn print_table(result: &RunResult) {
+    println!("💻 {} — {} processes", result.queue, result.processes.len());
+    for (scope, metrics) in scopes(result) {
+        println!("--- {} ---", scope);
+        let rows = metric_rows(metrics);
+        let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        for (name, value) in rows {
+            println!("{:<width$}  {}", name, value, width = width);
+        }
+    }
+    if let Some(analytic) = &result.analytic {
+        let prediction = &analytic.prediction;
+        println!("--- analytic: {} ---", prediction.model);
+        println!("utilization (ρ)  {:.3}", prediction.utilization);
+        if !prediction.stable {
+            println!("unstable, the queue grows without bound");
+        }
+        let width = analytic.errors.iter().map(|e| e.metric.len()).max().unwrap_or(0);
+        for error in &analytic.errors {
+            println!(
+                "{:<width$}  predicted {:.2}ms, measured {:.2}ms{}",
+                error.metric,
+                error.predicted,
+                error.measured,
+                percent(error.relative_error),
+                width = width
+            );
+        }
+    }
+    if let Some(littles_law) = &result.littles_law {
+        println!("--- Little's law ---");
+        println!(
+            "L sampled {:.3}, λW {:.3} (λ {:.3}/s, W {:.2}ms){}",
+            littles_law.average_in_system,
+            littles_law.expected_in_system,
+            littles_law.arrival_rate,
+            littles_law.average_turnaround_time,
+            percent(littles_law.relative_error)
+        );
+    }
+    if result.starvation.len() > 1 || result.starvation.iter().any(|l| l.starved_processes > 0) {
+        println!("--- starvation ---");
+        for level in &result.starvation {
+            println!(
+                "queue_{}  {} starved, longest unserved {:.2}ms, max waiting {:.2}ms",
+                level.queue,
+                level.starved_processes,
+                level.longest_unserved.as_secs_f64() * 1000.0,
+                level.max_waiting_time.as_secs_f64() * 1000.0
+            );
+        }
+    }
+}
+
+/// The metrics of the replayed run next to the kernel's, for the ones the kernel has.
+fn print_kernel_table(result: &RunResult, kernel: &KernelRun) {
+    println!("--- next to the kernel, which had {} CPUs ---", kernel.cpus);
+    let mut names: Vec<&String> =
+        kernel.metrics.keys().filter(|name| *name != "queue_discipline").collect();
+    names.sort();
+    let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+    println!("{:<width$}  {:>14}  {:>14}", "", result.queue, "kernel", width = width);
+    for name in names {
+        let simulated = result.metrics.get(name).map(format_metric).unwrap_or_default();
+        let kernel = format_metric(&kernel.metrics[name]);
+        println!("{:<width$}  {:>14}  {:>14}", name, simulated, kernel, width = width);
+    }
+}
+
+fn print_csv(result: &RunResult, kernel: Option<&KernelRun>) {
+    println!("scope,metric,value");
+    let mut scopes = scopes(result);
+    if let Some(kernel) = kernel {
+        scopes.push((String::from("kernel"), &kernel.metrics));
+    }
+    for (scope, metrics) in scopes {
+        for (name, value) in metric_rows(metrics) {
+            // durations go out as plain milliseconds so spreadsheets can use them as numbers
+            let value = metrics[name].as_f64().map(|v| v.to_string()).unwrap_or(value);
+            println!("{},{},{}", scope, name, value);
+        }
+    }
+    if let Some(analytic) = &result.analytic {
+        println!("analytic,utilization,{}", analytic.prediction.utilization);
+        for error in &analytic.errors {
+            println!("analytic,{},{}", error.metric, error.predicted);
+            println!("analytic_error,{},{}", error.metric, csv_value(error.relative_error));
+        }
+    }
+    if let Some(littles_law) = &result.littles_law {
+        println!("littles_law,average_in_system,{}", littles_law.average_in_system);
+        println!("littles_law,expected_in_system,{}", littles_law.expected_in_system);
+        println!("littles_law,relative_error,{}", csv_value(littles_law.relative_error));
+    }
+    for level in &result.starvation {
+        let scope = format!("starvation_queue_{}", level.queue);
+        println!("{},starved_processes,{}", scope, level.starved_processes);
+        println!("{},longest_unserved,{}", scope, level.longest_unserved.as_secs_f64() * 1000.0);
+        println!("{},max_waiting_time,{}", scope, level.max_waiting_time.as_secs_f64() * 1000.0);
+    }
+}
+
+fn print_comparison_table(comparison: &Comparison) {
+    let mut names: Vec<&String> = comparison.runs[0].metrics.keys().collect();
+    names.sort();
+    let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+    // a sched trace's own run goes last, it's left out of the ranking
+    let mut columns: Vec<(&str, &HashMap<String, MetricValue>)> =
+        comparison.runs.iter().map(|run| (run.queue.as_str(), &run.metrics)).collect();
+    if let Some(kernel) = &comparison.kernel {
+        columns.push(("kernel", &kernel.metrics));
+    }
+    print!("{:<width$}", "", width = width);
+    for (queue, _) in &columns {
+        print!("  {:>14}", queue);
+    }
+    println!();
+    for name in names {
+        print!("{:<width$}", name, width = width);
+        for (_, metrics) in &columns {
+            let value = metrics.get(name).map(format_metric).unwrap_or_default();
+            print!("  {:>14}", value);
+        }
+        println!();
+    }
+    if let Some(kernel) = &comparison.kernel {
+        println!("the kernel ran the trace on {} CPUs, the simulator has one", kernel.cpus);
+    }
+    println!("--- ranked by {} ---", comparison.rank_by);
+    for (place, ranked) in comparison.ranking.iter().enumerate() {
+        println!("{}. {:<6} {:.3}", place + 1, ranked.queue, ranked.value);
+    }
+}
+
+fn segment_cell(segment: &Option<Segment>) -> String {
+    segment.as_ref().map_or(String::from("-"), |s| format!("{} {}-{}", s.process, s.start, s.end))
+}
+
+fn verdict_name(verdict: Verdict) -> &'static str {
+    match verdict {
+        Verdict::Correct => "correct",
+        Verdict::WrongProcess => "wrong process",
+        Verdict::WrongTimes => "wrong times",
+        Verdict::Missing => "missing",
+        Verdict::Extra => "extra",
+    }
+}
+
+fn print_decisions(result: &RunResult) {
+    println!("--- decisions ---");
+    for (time, decision) in decision::from_log(&result.log) {
+        println!("{:>10.3} ms  {}", time.as_secs_f64() * 1000.0, decision.text());
+    }
+}
+
+fn print_grade_table(grade: &Grade) {
+    println!("{:>3}  {:<18} {:<18} verdict", "#", "expected", "submitted");
+    for diff in &grade.segments {
+        println!(
+            "{:>3}  {:<18} {:<18} {}",
+            diff.index + 1,
+            segment_cell(&diff.expected),
+            segment_cell(&diff.submitted),
+            verdict_name(diff.verdict)
+        );
+    }
+    if let Some(divergence) = &grade.divergence {
+        println!("--- first mistake, segment {} at {} ms ---", divergence.index + 1, divergence.time);
+        println!("{}", divergence.explanation);
+    }
+    if !grade.metrics.is_empty() {
+        println!("--- metrics ---");
+        let width = grade.metrics.iter().map(|m| m.metric.len()).max().unwrap_or(0);
+        for metric in &grade.metrics {
+            println!(
+                "{:<width$}  expected {:.3}  submitted {:.3}  error {:+.3}  {}",
+                metric.metric,
+                metric.expected,
+                metric.submitted,
+                metric.error,
+                if metric.correct { "correct" } else { "wrong" },
+                width = width
+            );
+        }
+    }
+    println!("--- {} ---", if grade.correct { "correct" } else { "not correct" });
+}
+
+fn print_grade_csv(grade: &Grade) {
+    println!("segment,expected_process,expected_start,expected_end,submitted_process,submitted_start,submitted_end,verdict");
+    let cells = |segment: &Option<Segment>| match segment {
+        Some(s) => format!("{},{},{}", s.process, s.start, s.end),
+        None => String::from(",,"),
+    };
+    for diff in &grade.segments {
+        println!(
+            "{},{},{},{}",
+            diff.index + 1,
+            cells(&diff.expected),
+            cells(&diff.submitted),
+            verdict_name(diff.verdict).replace(' ', "_")
+        );
+    }
+}
+
+fn grade_homework(path: &Path, format: OutputFormat) -> Result<(), CliError> {
+    let homework = serde_json::from_str(&read_file(path)?)
+        .map_err(|e| CliError::invalid(format!("{}: {}", path.display(), e)))?;
+    let grade = grading::grade(&homework).map_err(|e| match e {
+        SimError::InvalidConfig { .. } => CliError::invalid(format!("{}: {}", path.display(), e)),
+        e => CliError::failed(e),
+    })?;
+    match format {
+        OutputFormat::Table => print_grade_table(&grade),
+        OutputFormat::Json => print_json(&grade),
+        OutputFormat::Csv => print_grade_csv(&grade),
+    }
+    Ok(())
+}
+
+fn print_exercise_csv(exercise: &Exercise) {
+    println!("name,arrival,burst");
+    for p in &exercise.processes {
+        println!("{},{},{}", p.name, p.arrival, p.burst);
+    }
+}
+
+fn write_exercise(args: &Args, difficulty: &str, format: OutputFormat) -> Result<(), CliError> {
+    let mut spec = ExerciseSpec {
+        difficulty: Difficulty::from_name(difficulty).ok_or(CliError::usage(format!(
+            "unknown difficulty \"{}\", expected easy, medium or hard",
+            difficulty
+        )))?,
+        ..ExerciseSpec::default()
+    };
+    if let Some(seed) = args.seed {
+        spec.seed = seed;
+    }
+    if let Some(algorithms) = &args.compare {
+        spec.algorithms = algorithms.clone();
+    }
+    if let Some(quantum) = args.quantum {
+        spec.time_quantum = quantum;
+    }
+    if let Some(context_switch) = args.context_switch {
+        spec.context_switch = context_switch;
+    }
+    for name in args.properties.iter().flatten() {
+        let property = Property::from_name(name).ok_or(CliError::usage(format!(
+            "unknown property \"{}\", expected tie, preemption or idle",
+            name
+        )))?;
+        spec.properties.push(property);
+    }
+    let exercise = exercise::generate(&spec).map_err(CliError::usage)?;
+    match format {
+        OutputFormat::Table => print!("{}", exercise.to_markdown()),
+        OutputFormat::Json => print_json(&exercise),
+        OutputFormat::Csv => print_exercise_csv(&exercise),
+    }
+    Ok(())
+}
+
+fn print_comparison_csv(comparison: &Comparison) {
+    println!("queue,metric,value,rank");
+    for run in &comparison.runs {
+        let rank = comparison
+            .ranking
+            .iter()
+            .position(|r| r.queue == run.queue)
+            .map(|place| place + 1)
+            .unwrap_or(0);
+        for (name, value) in metric_rows(&run.metrics) {
+            let value = run.metrics[name].as_f64().map(|v| v.to_string()).unwrap_or(value);
+            println!("{},{},{},{}", run.queue, name, value, rank);
+        }
+    }
+    if let Some(kernel) = &comparison.kernel {
+        for (name, value) in metric_rows(&kernel.metrics) {
+            let value = kernel.metrics[name].as_f64().map(|v| v.to_string()).unwrap_or(value);
+            println!("kernel,{},{},", name, value); // unranked
+        }
+    }
+}
+
+// cli ---------------------------------------------------------------------------------------------
+
+/// The flags and the scenario file merged together, flags win.
+struct Settings {
+    queue: Queues,
+    disciplines: Option<[QueueDiscipline; 4]>,
+    time_quantum: Duration,
+    context_switch: Duration,
+    boost_period: Option<Duration>,
+    script: Option<String>, // the source, for sweeps
+    policy: Option<Arc<ScriptPolicy>>,
+    plugin_path: Option<PathBuf>, // for sweeps, they load it themselves
+    plugin: Option<Arc<PluginPolicy>>,
+    sample_period: Option<Duration>,
+    starvation_threshold: Option<Duration>,
+    seed: Option<u64>,
+    source: ProcessSource,
+    kernel: Option<KernelRun>, // when the workload is a sched trace
+    compare: Option<Vec<Queues>>,
+    rank_by: String,
+    sweep: Vec<SweepAxis>,
+    replications: Option<usize>,
+    confidence: Option<f64>,
+    metrics: Option<Vec<String>>,
+    warm_up: WarmUp,
+    estimator: Estimator,
+}
+
+impl Settings {
+    fn is_batch(&self) -> bool {
+        // a sweep with no axes is just replications of one scenario
+        !self.sweep.is_empty()
+            || self.replications.is_some()
+            || !matches!(self.warm_up, WarmUp::None)
+            || matches!(self.estimator, Estimator::BatchMeans { .. })
+    }
+}
+
+fn parse_queue(name: &str) -> Result<Queues, CliError> {
+    Queues::from_name(name).ok_or(CliError::usage(format!("unknown algorithm \"{}\"", name)))
+}
+
+fn parse_axis(text: &str) -> Result<SweepAxis, CliError> {
+    let invalid = || CliError::usage(format!("can't read --sweep {}, expected name=start:end:step or name=a,b,c", text));
+    let (name, values) = text.split_once('=').ok_or_else(invalid)?;
+    let parameter = SweepParameter::from_name(name)
+        .ok_or(CliError::usage(format!("\"{}\" is not a parameter that can be swept", name)))?;
+    let numbers = |separator: char| {
+        values
+            .split(separator)
+            .map(|v| v.trim().parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|_| invalid())
+    };
+    if values.contains(':') {
+        let range = numbers(':')?;
+        if range.len() != 3 {
+            return Err(invalid());
+        }
+        Ok(SweepAxis {
+            parameter,
+            values: Vec::new(),
+            range: Some(SweepRange {
+                start: range[0],
+                end: range[1],
+                step: range[2],
+            }),
+        })
+    } else {
+        Ok(SweepAxis {
+            parameter,
+            values: numbers(',')?,
+            range: None,
+        })
+    }
+}
+
+fn load_script(path: &Path) -> Result<(String, ScriptPolicy), CliError> {
+    let source = read_file(path)?;
+    let policy = ScriptPolicy::compile(path.display().to_string(), &source)
+        .map_err(|e| CliError::invalid(format!("{}: {}", path.display(), e)))?;
+    Ok((source, policy))
+}
+
+fn load_plugin(path: &Path) -> Result<PluginPolicy, CliError> {
+    std::fs::metadata(path).map_err(|e| CliError::io(path, e))?; // a missing file isn't invalid
+    PluginPolicy::load(path).map_err(|e| CliError::invalid(format!("{}: {}", path.display(), e)))
+}
+
+fn load_workload(
+    path: &Path,
+    format: Option<String>,
+    unit: Option<String>,
+    swf: &SwfOptions,
+) -> Result<(Vec<WorkloadEntry>, Option<KernelRun>), CliError> {
+    let contents = read_file(path)?;
+    let format = WorkloadFormat::detect(format.as_deref(), &contents).map_err(CliError::usage)?;
+    let unit = match unit {
+        Some(unit) => TimeUnit::from_name(&unit)
+            .ok_or(CliError::usage(format!("unknown time unit \"{}\", expected ms or s", unit)))?,
+        None => TimeUnit::Milliseconds,
+    };
+    let report = workload::import_workload(&contents, format, unit, swf);
+    for warning in &report.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    if !report.valid {
+        let errors: Vec<String> = report.errors.iter().map(|e| e.to_string()).collect();
+        return Err(CliError::invalid(format!(
+            "{} is not a valid workload:\n{}",
+            path.display(),
+            errors.join("\n")
+        )));
+    }
+    Ok((report.processes, report.kernel))
+}
+
+fn resolve(args: &Args) -> Result<Settings, CliError> {
+    let scenario = match &args.scenario {
+        Some(path) => load_scenario(path)?,
+        None => ScenarioFile::default(),
+    };
+
+    let algorithm = args.algorithm.clone().or(scenario.algorithm).unwrap_or(String::from("FCFS"));
+    let queue = parse_queue(&algorithm)?;
+    let compare = match args.compare.clone().or(scenario.compare) {
+        Some(names) => Some(names.iter().map(|name| parse_queue(name)).collect::<Result<Vec<_>, _>>()?),
+        None => None,
+    };
+    let needs_levels = match &compare {
+        Some(queues) => queues.iter().find(|q| q.is_multilevel()).copied(),
+        None => Some(queue).filter(|q| q.is_multilevel()),
+    };
+    let disciplines = match args.disciplines.clone().or(scenario.disciplines) {
+        Some(disciplines) => Some(parse_disciplines(disciplines)?),
+        None => match needs_levels {
+            Some(queue) => {
+                return Err(CliError::usage(format!("{} needs --disciplines for its 4 levels", queue)))
+            }
+            None => None,
+        },
+    };
+    let uses = |discipline| match &compare {
+        Some(queues) => queues.iter().any(|q| q.uses(disciplines, discipline)),
+        None => queue.uses(disciplines, discipline),
+    };
+    let (script, policy) = match args.script.clone().or(scenario.script) {
+        Some(path) => {
+            let (source, policy) = load_script(&path)?;
+            (Some(source), Some(Arc::new(policy)))
+        }
+        None if uses(QueueDiscipline::SCRIPT) => {
+            return Err(CliError::usage(String::from("SCRIPT needs a --script")))
+        }
+        None => (None, None),
+    };
+    let plugin_path = args.plugin.clone().or(scenario.plugin);
+    let plugin = match &plugin_path {
+        Some(path) => Some(Arc::new(load_plugin(path)?)),
+        None if uses(QueueDiscipline::PLUGIN) => {
+            return Err(CliError::usage(String::from("PLUGIN needs a --plugin")))
+        }
+        None => None,
+    };
+    let time_quantum = Duration::from_millis(args.quantum.or(scenario.quantum).unwrap_or(10));
+    let context_switch =
+        Duration::from_millis(args.context_switch.or(scenario.context_switch).unwrap_or(1));
+    if time_quantum.is_zero() {
+        return Err(CliError::usage(String::from("the time quantum must be at least 1 ms")));
+    }
+
+    let mut swf = scenario.swf.unwrap_or_default();
+    if let Some(time_scale) = args.swf_time_scale {
+        swf.time_scale = time_scale;
+    }
+    swf.limit = args.swf_limit.or(swf.limit);
+    swf.scale_by_processors |= args.swf_scale_by_processors;
+    let (source, kernel) = match args.workload.clone().or(scenario.workload) {
+        Some(path) => {
+            let (entries, kernel) = load_workload(
+                &path,
+                args.workload_format.clone().or(scenario.workload_format),
+                args.unit.clone().or(scenario.unit),
+                &swf,
+            )?;
+            (ProcessSource::Workload(entries), kernel)
+        }
+        None => {
+            let source = ProcessSource::Generated {
+                arrival_lambda: args.arrival_lambda.or(scenario.arrival_lambda).unwrap_or(1.0),
+                burst_lambda: args.burst_lambda.or(scenario.burst_lambda).unwrap_or(2.0),
+                count: args.processes.or(scenario.processes).unwrap_or(10),
+            };
+            (source, None)
+        }
+    };
+
+    Ok(Settings {
+        queue,
+        disciplines,
+        time_quantum,
+        context_switch,
+        boost_period: args.boost_period.or(scenario.boost_period).map(Duration::from_millis),
+        script,
+        policy,
+        plugin_path,
+        plugin,
+        sample_period: match args.sample_period.or(scenario.sample_period) {
+            Some(0) => None,
+            Some(period) => Some(Duration::from_millis(period)),
+            None => Some(DEFAULT_SAMPLE_PERIOD),
+        },
+        starvation_threshold: args
+            .starvation_threshold
+            .or(scenario.starvation_threshold)
+            .map(Duration::from_millis),
+        seed: args.seed.or(scenario.seed),
+        source,
+        kernel,
+        compare,
+        rank_by: args
+            .rank_by
+            .clone()
+            .or(scenario.rank_by)
+            .unwrap_or(String::from("average_waiting_time")),
+        sweep: if args.sweep.is_empty() {
+            scenario.sweep.unwrap_or_default()
+        } else {
+            args.sweep.iter().map(|axis| parse_axis(axis)).collect::<Result<_, _>>()?
+        },
+        replications: args.replications.or(scenario.replications),
+        confidence: args.confidence.or(scenario.confidence),
+        metrics: args.metrics.clone().or(scenario.metrics),
+        warm_up: match (args.warm_up, args.welch) {
+            (Some(count), _) => WarmUp::Fixed { count },
+            (None, Some(window)) => WarmUp::Welch {
+                window,
+                tolerance: args.welch_tolerance,
+            },
+            (None, None) => scenario.warm_up.unwrap_or(WarmUp::None),
+        },
+        estimator: match args.batch_means.or(scenario.batch_means) {
+            Some(batches) => Estimator::BatchMeans { batches },
+            None => Estimator::Replications,
+        },
+    })
+}
+
+fn print_json<T: Serialize>(value: &T) {
+    // going through a Value sorts the metrics by name instead of the HashMap's order
+    let value = serde_json::to_value(value).unwrap();
+    println!("{}", serde_json::to_string_pretty(&value).unwrap())
+}
+
+fn run_comparison(
+    scenario: &Scenario,
+    queues: &[Queues],
+    rank_by: &str,
+    kernel: Option<KernelRun>,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    let mut comparison =
+        scenario.compare(queues, rank_by).map_err(|e| CliError::usage(e.into()))?;
+    comparison.kernel = kernel;
+    match format {
+        OutputFormat::Table => print_comparison_table(&comparison),
+        OutputFormat::Json => print_json(&comparison),
+        OutputFormat::Csv => print_comparison_csv(&comparison),
+    }
+    Ok(())
+}
+
+fn run_parameter_sweep(settings: Settings, format: OutputFormat) -> Result<(), CliError> {
+    let queues = settings.compare.clone().unwrap_or(vec![settings.queue]);
+    let mut spec = SweepSpec {
+        algorithms: queues,
+        disciplines: settings.disciplines,
+        time_quantum: settings.time_quantum.as_secs_f64() * 1000.0,
+        context_switch: settings.context_switch.as_secs_f64() * 1000.0,
+        mlfq_boost_period: settings.boost_period.map(|b| b.as_secs_f64() * 1000.0),
+        script: settings.script,
+        plugin: settings.plugin_path,
+        axes: settings.sweep,
+        seed: settings.seed.unwrap_or_else(rand::random),
+        ..SweepSpec::default()
+    };
+    spec.warm_up = settings.warm_up;
+    spec.estimator = settings.estimator;
+    let per_process = !matches!((spec.warm_up, spec.estimator), (WarmUp::None, Estimator::Replications));
+    if per_process {
+        // cpu utilization isn't an average over processes, see sweep::summarize
+        spec.metrics.retain(|m| m != "cpu_utilization");
+    }
+    if let Estimator::BatchMeans { .. } = spec.estimator {
+        spec.replications = 1; // batch means come from one long run unless asked otherwise
+    }
+    if let Some(replications) = settings.replications {
+        spec.replications = replications;
+    }
+    if let Some(confidence) = settings.confidence {
+        spec.confidence = confidence;
+    }
+    if let Some(metrics) = settings.metrics {
+        spec.metrics = metrics;
+    }
+    match settings.source {
+        ProcessSource::Generated {
+            arrival_lambda,
+            burst_lambda,
+            count,
+        } => {
+            spec.arrival_lambda = arrival_lambda;
+            spec.burst_lambda = burst_lambda;
+            spec.processes = count;
+        }
+        ProcessSource::Workload(entries) => spec.workload = Some(entries),
+    }
+
+    let result = sweep::run_sweep(&spec).map_err(CliError::usage)?;
+    match format {
+        OutputFormat::Table => {
+            for row in &result.rows {
+                let point: Vec<String> =
+                    row.parameters.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+                let half_width = row.interval.half_width.map(|h| format!(" ± {:.3}", h)).unwrap_or_default();
+                println!(
+                    "{:<6} {:<32} {:<24} {:.3}{} (n={}, warm-up={})",
+                    row.algorithm,
+                    point.join(" "),
+                    row.metric,
+                    row.interval.mean,
+                    half_width,
+                    row.interval.n,
+                    row.warm_up_deleted
+                );
+            }
+        }
+        OutputFormat::Json => print_json(&result),
+        OutputFormat::Csv => print!("{}", result.to_csv()),
+    }
+    Ok(())
+}
+
+/// The flags that write out or explain the one run, there's none to write with many.
+fn single_run_outputs(args: &Args) -> Vec<&'static str> {
+    let flags = [
+        ("--log", args.log.is_some()),
+        ("--svg", args.svg.is_some()),
+        ("--report", args.report.is_some()),
+        ("--samples", args.samples.is_some()),
+        ("--explain", args.explain),
+    ];
+    flags.into_iter().filter(|(_, given)| *given).map(|(flag, _)| flag).collect()
+}
+
+fn simulate(args: Args) -> Result<(), CliError> {
+    if let Some(path) = &args.grade {
+        return grade_homework(path, args.format);
+    }
+    if let Some(difficulty) = &args.exercise {
+        return write_exercise(&args, difficulty, args.format);
+    }
+    let settings = resolve(&args)?;
+    let outputs = single_run_outputs(&args);
+    if !outputs.is_empty() && (settings.compare.is_some() || settings.is_batch()) {
+        let runs = if settings.is_batch() { "a sweep" } else { "--compare" };
+        return Err(CliError::usage(format!(
+            "{} can't be used with {}, there's no single run to write out",
+            outputs.join(", "),
+            runs
+        )));
+    }
+    if settings.is_batch() {
+        return run_parameter_sweep(settings, args.format);
+    }
+
+    let scenario = Scenario {
+        queue: settings.queue,
+        disciplines: settings.disciplines,
+        time_quantum: settings.time_quantum,
+        context_switch: settings.context_switch,
+        options: RunOptions {
+            mlfq_boost_period: settings.boost_period,
+            sample_period: settings.sample_period,
+            starvation_threshold: settings.starvation_threshold,
+            policy: settings.policy,
+            plugin: settings.plugin,
+        },
+        source: settings.source,
+        seed: settings.seed,
+    };
+    if let Some(queues) = &settings.compare {
+        // a seeded comparison also starts at a fixed time, so its output is the same every time
+        return run_comparison(&scenario, queues, &settings.rank_by, settings.kernel, args.format);
+    }
+    let result = scenario.run().map_err(CliError::failed)?;
+
+    match (args.format, &settings.kernel) {
+        (OutputFormat::Table, kernel) => {
+            print_table(&result);
+            if let Some(kernel) = kernel {
+                print_kernel_table(&result, kernel);
+            }
+        }
+        (OutputFormat::Json, Some(kernel)) => print_json(&Replay { result: &result, kernel }),
+        (OutputFormat::Json, None) => print_json(&result),
+        (OutputFormat::Csv, kernel) => print_csv(&result, kernel.as_ref()),
+    }
+    if args.explain && matches!(args.format, OutputFormat::Table) {
+        print_decisions(&result);
+    }
+    if let Some(path) = &args.log {
+        events::export_json_lines(&result.log, &path.to_string_lossy()).map_err(|e| CliError {
+            code: EXIT_IO,
+            message: format!("{}: {}", path.display(), e),
+        })?;
+    }
+    if let Some(path) = &args.svg {
+        write_file(path, &report::render_svg(&report::bars_from_log(&result.log)))?;
+    }
+    if let Some(path) = &args.report {
+        write_file(path, &report::render_html("Mehrmux Simulation Report", &result.log))?;
+    }
+    if let Some(path) = &args.samples {
+        write_file(path, &sampling::to_csv(&result.samples))?;
+    }
+    Ok(())
+}
+
+/// Returns the process exit code.
+fn run() -> i32 {
+    let args = match Args::try_parse() {
+        Ok(args) => args,
+        Err(e) => {
+            // --help and --version end up here too, clap knows which ones are errors
+            let _ = e.print();
+            return if e.use_stderr() { EXIT_USAGE } else { EXIT_OK };
+        }
+    };
+    match simulate(args) {
+        Ok(()) => EXIT_OK,
+        Err(e) => {
+            eprintln!("error: {}", e.message);
+            e.code
+        }
+    }
+}
+
+fn main() {
+    std::process::exit(run())
+}
@@ -0,0 +1,49 @@
+// The exit codes scripts rely on, see EXIT_USAGE and the others in main.rs.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+fn exit_code(args: &[&str]) -> i32 {
+    let output = Command::new(env!("CARGO_BIN_EXE_mehrmux-cli")).args(args).output().unwrap();
+    output.status.code().unwrap()
+}
+
+/// `contents` in a file of its own under the temp directory.
+fn file(name: &str, contents: &str) -> String {
+    let name = format!("mehrmux-{}-{}", std::process::id(), name);
+    let path: PathBuf = std::env::temp_dir().join(name);
+    std::fs::write(&path, contents).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn a_run_that_finishes_exits_with_0() {
+    assert_eq!(exit_code(&["--algorithm", "RR", "--seed", "1", "-n", "5"]), 0);
+}
+
+#[test]
+fn a_bad_command_line_exits_with_2() {
+    assert_eq!(exit_code(&["--algorithm", "LOTTERY"]), 2);
+    assert_eq!(exit_code(&["--no-such-flag"]), 2);
+    // the outputs of a single run have nothing to write with many runs
+    assert_eq!(exit_code(&["--compare", "FCFS,RR", "--log", "run.jsonl"]), 2);
+    assert_eq!(exit_code(&["--sweep", "quantum=5,10", "--explain"]), 2);
+}
+
+#[test]
+fn an_invalid_workload_exits_with_3() {
+    let workload = file("invalid.csv", "arrival_time,cpu_burst_time\n0,abc\n");
+    assert_eq!(exit_code(&["--workload", &workload]), 3);
+}
+
+#[test]
+fn a_missing_file_exits_with_4() {
+    assert_eq!(exit_code(&["--workload", "/no/such/workload.csv"]), 4);
+}
+
+#[test]
+fn a_script_failing_halfway_exits_with_5() {
+    let source = "fn pick_next(ready, now) { if now > 3.0 { throw \"boom\"; } 0 }";
+    let script = file("throws.rhai", source);
+    assert_eq!(exit_code(&["--algorithm", "SCRIPT", "--script", &script, "--seed", "1"]), 5);
+}
@@ -0,0 +1,424 @@
+use crate::process_gen::{Process, ProcessType};
+use crate::queue_engine::{MetricValue, Queues};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+// Utils -------------------------------------------------------------------------------------------
+
+// the exponential distribution is evaluated on this many of its quantiles when a formula needs
+// the whole distribution and not only its moments
+const QUANTILES: usize = 4000;
+
+// MLQ's levels from the highest priority down, see MLQ::enqueue
+const LEVELS: [ProcessType; 4] = [
+    ProcessType::SystemProcess,
+    ProcessType::InteractiveProcess,
+    ProcessType::BatchProcess,
+    ProcessType::StudentProcess,
+];
+
+/// What queueing theory needs to know about a workload: the arrival rate, taken as Poisson,
+/// and the service times (seconds, sorted) of every class of process.
+#[derive(Debug, Clone)]
+pub struct Workload {
+    arrival_rate: f64,
+    services: Vec<f64>,
+    class_services: [Vec<f64>; 4],
+}
+
+impl Workload {
+    /// The workload of a run: arrivals at the rate they were observed, and the run's own CPU
+    /// bursts as the service time distribution (I/O isn't part of the models).
+    pub fn from_processes(processes: &[Process]) -> Option<Self> {
+        let arrivals: Vec<SystemTime> = processes.iter().map(|p| p.arrival_time).collect();
+        let arrival_rate = arrival_rate(&arrivals)?;
+        let mut class_services: [Vec<f64>; 4] = Default::default();
+        for process in processes {
+            let level = LEVELS.iter().position(|t| *t == process.process_type).unwrap_or(0);
+            class_services[level].push(process.cpu_burst_time.as_secs_f64());
+        }
+        Some(Workload::new(arrival_rate, class_services))
+    }
+
+    /// Poisson arrivals and exponential service, the processes the simulator generates. Every
+    /// process type is equally likely, like in Process::new.
+    pub fn exponential(arrival_rate: f64, service_rate: f64) -> Self {
+        let quantiles: Vec<f64> = (0..QUANTILES)
+            .map(|i| -(1.0 - (i as f64 + 0.5) / QUANTILES as f64).ln())
+            .collect();
+        // the quantiles miss a little of the tail, scaled the mean is 1/μ and ρ = λ/μ exactly
+        let scale = moment(&quantiles, 1) * service_rate;
+        let grid: Vec<f64> = quantiles.iter().map(|q| q / scale).collect();
+        Workload::new(arrival_rate, std::array::from_fn(|_| grid.clone()))
+    }
+
+    fn new(arrival_rate: f64, mut class_services: [Vec<f64>; 4]) -> Self {
+        for services in class_services.iter_mut() {
+            services.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        }
+        let mut services: Vec<f64> = class_services.iter().flatten().copied().collect();
+        services.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        Workload {
+            arrival_rate,
+            services,
+            class_services,
+        }
+    }
+
+    fn mean_service(&self) -> f64 {
+        moment(&self.services, 1)
+    }
+
+    fn load(&self) -> f64 {
+        self.arrival_rate * self.mean_service()
+    }
+
+    fn residual_work(&self) -> f64 {
+        // W0 = λE[S²]/2, the mean work left of the job in service as seen by an arrival
+        self.arrival_rate * moment(&self.services, 2) / 2.0
+    }
+}
+
+fn moment(samples: &[f64], power: i32) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples.iter().map(|s| s.powi(power)).sum::<f64>() / samples.len() as f64
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Prediction {
+    /// Expected values of a queueing model. `metrics` uses the names of calculate_metrics, in
+    /// milliseconds, plus `number_in_system` and `number_in_queue` (Little's law). A metric the
+    /// model has no formula for is left out, and an unstable model (ρ >= 1) has no metrics.
+    pub model: String,
+    pub arrival_rate: f64,
+    pub utilization: f64,
+    pub stable: bool,
+    pub metrics: HashMap<String, f64>,
+}
+
+impl Prediction {
+    fn new(model: &str, arrival_rate: f64, utilization: f64) -> Self {
+        Prediction {
+            model: model.to_string(),
+            arrival_rate,
+            utilization,
+            stable: utilization < 1.0,
+            metrics: HashMap::new(),
+        }
+    }
+
+    /// Fills in the metrics from the mean time in system and mean time waiting (seconds).
+    fn with_times(mut self, turnaround: f64, waiting: f64, response: Option<f64>) -> Self {
+        if !self.stable {
+            return self;
+        }
+        self.metrics.insert(String::from("average_turnaround_time"), turnaround * 1000.0);
+        self.metrics.insert(String::from("average_waiting_time"), waiting * 1000.0);
+        if let Some(response) = response {
+            self.metrics.insert(String::from("average_response_time"), response * 1000.0);
+        }
+        self.metrics.insert(String::from("number_in_system"), self.arrival_rate * turnaround);
+        self.metrics.insert(String::from("number_in_queue"), self.arrival_rate * waiting);
+        self
+    }
+}
+
+// Models ------------------------------------------------------------------------------------------
+
+pub fn mm1(arrival_rate: f64, service_rate: f64) -> Prediction {
+    let rho = arrival_rate / service_rate;
+    let prediction = Prediction::new("M/M/1 FCFS", arrival_rate, rho);
+    let waiting = rho / (service_rate - arrival_rate);
+    prediction.with_times(1.0 / (service_rate - arrival_rate), waiting, Some(waiting))
+}
+
+/// Erlang C, `servers` identical CPUs sharing one FCFS queue.
+pub fn mmc(arrival_rate: f64, service_rate: f64, servers: u32) -> Prediction {
+    let c = servers.max(1) as f64;
+    let offered = arrival_rate / service_rate;
+    let rho = offered / c;
+    let prediction = Prediction::new(&format!("M/M/{} FCFS", servers), arrival_rate, rho);
+    if rho >= 1.0 {
+        return prediction;
+    }
+    let mut term = 1.0; // a^k / k!
+    let mut sum = 1.0;
+    for k in 1..servers {
+        term *= offered / k as f64;
+        sum += term;
+    }
+    let last = term * offered / c / (1.0 - rho); // a^c / (c! (1 - ρ))
+    let wait_probability = last / (sum + last);
+    let waiting = wait_probability / (c * service_rate - arrival_rate);
+    prediction.with_times(waiting + 1.0 / service_rate, waiting, Some(waiting))
+}
+
+/// Pollaczek-Khinchine, FCFS with any service time distribution.
+pub fn mg1_fcfs(workload: &Workload) -> Prediction {
+    let rho = workload.load();
+    let prediction = Prediction::new("M/G/1 FCFS (Pollaczek-Khinchine)", workload.arrival_rate, rho);
+    let waiting = workload.residual_work() / (1.0 - rho);
+    prediction.with_times(waiting + workload.mean_service(), waiting, Some(waiting))
+}
+
+/// Processor sharing, the limit of round robin as the quantum goes to zero. Every process is
+/// served from the moment it arrives, so there is no response time to predict.
+pub fn mg1_ps(workload: &Workload) -> Prediction {
+    let rho = workload.load();
+    let prediction = Prediction::new("M/G/1-PS (round robin limit)", workload.arrival_rate, rho);
+    let turnaround = workload.mean_service() / (1.0 - rho);
+    prediction.with_times(turnaround, turnaround - workload.mean_service(), None)
+}
+
+/// The size based policies all look at the load of the processes smaller than a process, so
+/// they share this walk over the sorted service times. `time_in_system` gets the size x, the
+/// load of processes smaller than x, the load of processes up to x, the second moment of
+/// processes up to x, the share of processes larger than x and the SRPT residence integral.
+fn size_based(
+    workload: &Workload,
+    model: &str,
+    time_in_system: impl Fn(f64, f64, f64, f64, f64, f64) -> f64,
+    response_is_waiting: bool,
+) -> Prediction {
+    let rho = workload.load();
+    let prediction = Prediction::new(model, workload.arrival_rate, rho);
+    if rho >= 1.0 || workload.services.is_empty() {
+        return prediction;
+    }
+    let n = workload.services.len() as f64;
+    let lambda = workload.arrival_rate;
+    let (mut load_below, mut moment_below) = (0.0, 0.0);
+    let (mut residence, mut previous) = (0.0, 0.0);
+    let mut total = 0.0;
+    let mut index = 0;
+    while index < workload.services.len() {
+        let x = workload.services[index];
+        // ρ(t) is the load below the last size passed for t between two sizes
+        residence += (x - previous) / (1.0 - load_below);
+        previous = x;
+        let ties = workload.services[index..].iter().take_while(|s| **s == x).count();
+        let load_before = load_below;
+        load_below += lambda * x * ties as f64 / n;
+        moment_below += x * x * ties as f64 / n;
+        let larger = (workload.services.len() - index - ties) as f64 / n;
+        let t = time_in_system(x, load_before, load_below, moment_below, larger, residence);
+        total += t * ties as f64;
+        index += ties;
+    }
+    let turnaround = total / n;
+    let waiting = turnaround - workload.mean_service();
+    prediction.with_times(turnaround, waiting, Some(waiting).filter(|_| response_is_waiting))
+}
+
+/// Non-preemptive shortest job first.
+pub fn mg1_sjf(workload: &Workload) -> Prediction {
+    let w0 = workload.residual_work();
+    size_based(
+        workload,
+        "M/G/1 non-preemptive SJF",
+        |x, before, upto, _, _, _| x + w0 / ((1.0 - before) * (1.0 - upto)),
+        true,
+    )
+}
+
+/// Preemptive shortest job first, by the total burst like this simulator's SJF.
+pub fn mg1_psjf(workload: &Workload) -> Prediction {
+    let lambda = workload.arrival_rate;
+    size_based(
+        workload,
+        "M/G/1 preemptive SJF",
+        |x, _, upto, moment, _, _| x / (1.0 - upto) + lambda * moment / 2.0 / (1.0 - upto).powi(2),
+        false,
+    )
+}
+
+/// Shortest remaining processing time (Schrage and Miller).
+pub fn mg1_srpt(workload: &Workload) -> Prediction {
+    let lambda = workload.arrival_rate;
+    size_based(
+        workload,
+        "M/G/1 SRPT",
+        |x, _, upto, moment, larger, residence| {
+            lambda * (moment + x * x * larger) / (2.0 * (1.0 - upto).powi(2)) + residence
+        },
+        false,
+    )
+}
+
+/// Non-preemptive priority between MLQ's levels (Cobham), each level is a process type.
+pub fn mg1_priority(workload: &Workload) -> Prediction {
+    let rho = workload.load();
+    let prediction = Prediction::new(
+        "M/G/1 non-preemptive priority by process type",
+        workload.arrival_rate,
+        rho,
+    );
+    let total = workload.services.len() as f64;
+    if rho >= 1.0 || total == 0.0 {
+        return prediction;
+    }
+    let w0 = workload.residual_work();
+    let (mut sigma, mut waiting) = (0.0, 0.0);
+    for services in &workload.class_services {
+        if services.is_empty() {
+            continue;
+        }
+        let share = services.len() as f64 / total;
+        let previous = sigma;
+        sigma += workload.arrival_rate * share * moment(services, 1);
+        waiting += share * w0 / ((1.0 - previous) * (1.0 - sigma));
+    }
+    prediction.with_times(waiting + workload.mean_service(), waiting, Some(waiting))
+}
+
+/// The model that matches an algorithm of the simulator. HRRN and MLFQ have no closed form, and
+/// a script or a plugin could be anything.
+pub fn predict(queue: Queues, workload: &Workload) -> Option<Prediction> {
+    match queue {
+        Queues::FIFO | Queues::FCFS => Some(mg1_fcfs(workload)),
+        Queues::RR => Some(mg1_ps(workload)),
+        Queues::SPN => Some(mg1_sjf(workload)),
+        Queues::SJF => Some(mg1_psjf(workload)),
+        Queues::SRF => Some(mg1_srpt(workload)),
+        Queues::MLQ => Some(mg1_priority(workload)),
+        Queues::HRRN | Queues::MLFQ | Queues::SCRIPT | Queues::PLUGIN => None,
+    }
+}
+
+/// Every model for Poisson arrivals at `arrival_rate` and exponential service at
+/// `service_rate` (both per second), the same rates run_simulation generates processes with.
+pub fn baselines(arrival_rate: f64, service_rate: f64, servers: u32) -> Vec<Prediction> {
+    let workload = Workload::exponential(arrival_rate, service_rate);
+    let mut predictions = vec![
+        mm1(arrival_rate, service_rate),
+        mg1_ps(&workload),
+        mg1_sjf(&workload),
+        mg1_psjf(&workload),
+        mg1_srpt(&workload),
+        mg1_priority(&workload),
+    ];
+    if servers > 1 {
+        predictions.push(mmc(arrival_rate, service_rate, servers));
+    }
+    predictions
+}
+
+// Comparison --------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricError {
+    pub metric: String,
+    pub predicted: f64,
+    pub measured: f64,
+    pub relative_error: Option<f64>, // (measured - predicted) / predicted, none for 0 predicted
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyticReport {
+    pub prediction: Prediction,
+    pub errors: Vec<MetricError>,
+}
+
+/// The prediction next to what a run measured, for every metric both of them have.
+pub fn report(prediction: Prediction, measured: &HashMap<String, MetricValue>) -> AnalyticReport {
+    let mut errors: Vec<MetricError> = prediction
+        .metrics
+        .iter()
+        .filter_map(|(metric, predicted)| {
+            let measured = measured.get(metric)?.as_f64()?;
+            Some(MetricError {
+                metric: metric.clone(),
+                predicted: *predicted,
+                measured,
+                relative_error: (*predicted != 0.0).then(|| (measured - predicted) / predicted),
+            })
+        })
+        .collect();
+    errors.sort_by(|e1, e2| e1.metric.cmp(&e2.metric));
+    AnalyticReport { prediction, errors }
+}
+
+/// The arrival rate of a workload in processes per second, None for fewer than 2 arrivals.
+pub fn arrival_rate(arrivals: &[SystemTime]) -> Option<f64> {
+    let first = arrivals.iter().min()?;
+    let last = arrivals.iter().max()?;
+    let span = last.duration_since(*first).ok()?.as_secs_f64();
+    if arrivals.len() < 2 || span <= 0.0 {
+        return None;
+    }
+    Some((arrivals.len() - 1) as f64 / span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric(prediction: &Prediction, name: &str) -> f64 {
+        prediction.metrics[name]
+    }
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() <= tolerance * b.abs()
+    }
+
+    #[test]
+    fn pollaczek_khinchine_on_exponential_service_is_mm1() {
+        let (lambda, mu) = (8.0, 10.0);
+        let mg1 = mg1_fcfs(&Workload::exponential(lambda, mu));
+        let mm1 = mm1(lambda, mu);
+        assert!(close(mg1.utilization, mm1.utilization, 1e-9));
+        for name in ["average_turnaround_time", "average_waiting_time", "number_in_system"] {
+            // the quantiles cut off the tail of the distribution, a little of E[S²] with it
+            assert!(close(metric(&mg1, name), metric(&mm1, name), 0.01), "{}", name);
+        }
+    }
+
+    #[test]
+    fn erlang_c_with_one_server_is_mm1() {
+        let mmc = mmc(3.0, 5.0, 1);
+        let mm1 = mm1(3.0, 5.0);
+        assert_eq!(mmc.metrics.len(), mm1.metrics.len());
+        for (name, value) in &mm1.metrics {
+            assert!(close(metric(&mmc, name), *value, 1e-9), "{}", name);
+        }
+    }
+
+    #[test]
+    fn srpt_is_the_fastest() {
+        let workload = Workload::exponential(7.0, 10.0);
+        let srpt = metric(&mg1_srpt(&workload), "average_turnaround_time");
+        assert!(srpt <= metric(&mg1_psjf(&workload), "average_turnaround_time"));
+        assert!(srpt <= metric(&mg1_ps(&workload), "average_turnaround_time"));
+    }
+
+    #[test]
+    fn overloaded_models_are_unstable() {
+        let workload = Workload::exponential(12.0, 10.0);
+        let mut predictions = baselines(12.0, 10.0, 1);
+        predictions.push(mg1_fcfs(&workload));
+        predictions.push(mm1(10.0, 10.0));
+        predictions.push(mmc(25.0, 10.0, 2));
+        for prediction in predictions {
+            assert!(!prediction.stable, "{}", prediction.model);
+            assert!(prediction.metrics.is_empty(), "{}", prediction.model);
+        }
+    }
+
+    #[test]
+    fn nothing_predicted_has_no_relative_error() {
+        let mut prediction = Prediction::new("test", 1.0, 0.5);
+        prediction.metrics.insert(String::from("average_waiting_time"), 0.0);
+        prediction.metrics.insert(String::from("average_turnaround_time"), 4.0);
+        let measured = HashMap::from([
+            (String::from("average_waiting_time"), MetricValue::FloatValue(1.0)),
+            (String::from("average_turnaround_time"), MetricValue::FloatValue(5.0)),
+        ]);
+        let report = report(prediction, &measured);
+        assert_eq!(report.errors[0].metric, "average_turnaround_time");
+        assert_eq!(report.errors[0].relative_error, Some(0.25));
+        assert_eq!(report.errors[1].relative_error, None);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::session::Session;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+// Clock -------------------------------------------------------------------------------------------
+
+/// The simulated time of a run. Processes only ever look at this clock, so a run gives the same
+/// numbers whether it is paced to real time for the UI or runs as fast as possible headless.
+#[derive(Clone)]
+pub struct SimClock {
+    start: SystemTime,
+    elapsed: Duration,
+    // a run shown in the UI also waits for real on every advance, at the session's playback
+    // speed, so the UI can follow it. The simulated time is the same at any speed.
+    session: Option<Arc<Session>>,
+}
+
+impl SimClock {
+    pub fn new(start: SystemTime) -> Self {
+        SimClock {
+            start,
+            elapsed: Duration::from_secs(0),
+            session: None,
+        }
+    }
+
+    pub fn paced(start: SystemTime, session: Arc<Session>) -> Self {
+        SimClock {
+            start,
+            elapsed: Duration::from_secs(0),
+            session: Some(session),
+        }
+    }
+
+    pub fn now(&self) -> SystemTime {
+        self.start + self.elapsed
+    }
+
+    pub fn start(&self) -> SystemTime {
+        self.start
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn advance(&mut self, duration: Duration) {
+        self.elapsed += duration;
+        if let Some(session) = &self.session {
+            session.pace(duration);
+        }
+    }
+
+    /// Waits until the session lets the run make its next scheduling decision, false means the
+    /// run was cancelled. Runs nobody watches never wait.
+    pub fn checkpoint(&self) -> bool {
+        match &self.session {
+            Some(session) => session.checkpoint(self.elapsed),
+            None => true,
+        }
+    }
+
+    pub fn advance_to(&mut self, time: SystemTime) {
+        if let Ok(duration) = time.duration_since(self.now()) {
+            self.advance(duration);
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use crate::config::{GeneratorConfig, RunConfig};
+use crate::error::{FieldError, SimError};
+use crate::events::Recorder;
+use crate::process_gen::SerializableProcess;
+use crate::queue_engine::{MetricValue, QueueDiscipline, Queues};
+use crate::scenario::{ProcessSource, Scenario};
+use crate::sched_trace::KernelRun;
+use crate::simulator::{RunOptions, RunResult, Simulator};
+use crate::workload::WorkloadEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+// Utils -------------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparedRun {
+    pub queue: String,
+    pub metrics: HashMap<String, MetricValue>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RankedQueue {
+    pub queue: String,
+    pub value: f64, // the ranking metric, durations in milliseconds
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessDelta {
+    /// How one process of the shared workload did under every compared queue. Every vector is
+    /// in the order of `Comparison::runs`, deltas are in milliseconds against the first run.
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub cpu_burst_time: Duration,
+    pub waiting_time: Vec<Duration>,
+    pub turnaround_time: Vec<Duration>,
+    pub response_time: Vec<Duration>,
+    pub waiting_delta: Vec<f64>,
+    pub turnaround_delta: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+    pub rank_by: String,
+    pub runs: Vec<ComparedRun>,
+    pub ranking: Vec<RankedQueue>,
+    pub processes: Vec<ProcessDelta>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kernel: Option<KernelRun>, // the kernel's own run of a sched trace, for callers to add
+}
+
+/// What the app's `compare_algorithms` takes: the config of a run, whose queue is replaced by
+/// every queue in `queues`, and the processes of `generator` or `workload`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompareArgs {
+    pub config: RunConfig,
+    #[serde(default)]
+    pub generator: Option<GeneratorConfig>,
+    #[serde(default)]
+    pub workload: Option<Vec<WorkloadEntry>>,
+    pub queues: Vec<Queues>,
+    #[serde(default, alias = "rankBy")] // the browser build gets the app's `invoke` arguments
+    pub rank_by: Option<String>, // average_waiting_time unless given
+}
+
+impl CompareArgs {
+    /// The scenario the queues are compared on, validated like a run of the config would be.
+    pub fn scenario(&self) -> Result<Scenario, SimError> {
+        let source = match (&self.generator, &self.workload) {
+            (None, Some(workload)) => ProcessSource::Workload(workload.clone()),
+            (Some(generator), None) => generator.source(),
+            _ => {
+                let message = "give either a generator or a workload";
+                return Err(vec![FieldError::new("generator", message)].into());
+            }
+        };
+        Ok(self.config.validate(source)?)
+    }
+
+    pub fn compare(&self, start: SystemTime) -> Result<Comparison, SimError> {
+        let rank_by = self.rank_by.as_deref().unwrap_or("average_waiting_time");
+        compare(&self.scenario()?, &self.queues, start, rank_by)
+    }
+}
+
+fn higher_is_better(metric: &str) -> bool {
+    // utilization, throughput and Jain's fairness indices grow as a queue does better, every
+    // other metric is a time or a count of misses
+    metric == "cpu_utilization"
+        || metric.contains("throughput")
+        || metric.starts_with("jain_fairness")
+}
+
+/// The disciplines `queue` runs with, the scenario's levels only go to MLQ and MLFQ.
+fn levels(queue: Queues, disciplines: Option<[QueueDiscipline; 4]>) -> Option<[QueueDiscipline; 4]> {
+    disciplines.filter(|_| matches!(queue, Queues::MLQ | Queues::MLFQ))
+}
+
+fn delta(value: Duration, baseline: Duration) -> f64 {
+    (value.as_secs_f64() - baseline.as_secs_f64()) * 1000.0
+}
+
+// Comparison --------------------------------------------------------------------------------------
+
+fn process_deltas(results: &[RunResult]) -> Vec<ProcessDelta> {
+    // every run got clones of the same processes, so ids match between runs
+    let by_id: Vec<HashMap<Uuid, &SerializableProcess>> = results
+        .iter()
+        .map(|result| result.processes.iter().map(|p| (p.id, p)).collect())
+        .collect();
+    let mut baseline: Vec<&SerializableProcess> = results[0].processes.iter().collect();
+    baseline.sort_by_key(|p| p.arrival_time);
+
+    baseline
+        .iter()
+        .filter(|p| by_id.iter().all(|run| run.contains_key(&p.id)))
+        .map(|p| {
+            let runs: Vec<&SerializableProcess> = by_id.iter().map(|run| run[&p.id]).collect();
+            let waiting: Vec<Duration> = runs.iter().map(|r| r.metrics.total_waiting_time).collect();
+            let turnaround: Vec<Duration> = runs.iter().map(|r| r.metrics.total_time).collect();
+            ProcessDelta {
+                id: p.id,
+                name: p.name.clone(),
+                cpu_burst_time: p.cpu_burst_time,
+                waiting_delta: waiting.iter().map(|w| delta(*w, waiting[0])).collect(),
+                turnaround_delta: turnaround.iter().map(|t| delta(*t, turnaround[0])).collect(),
+                response_time: runs.iter().map(|r| r.metrics.response_time).collect(),
+                waiting_time: waiting,
+                turnaround_time: turnaround,
+            }
+        })
+        .collect()
+}
+
+/// Runs the processes of `scenario`, arriving from `start` on, through every queue in `queues`
+/// instead of its own, and ranks the queues by `rank_by`, one of the metric names of
+/// `calculate_metrics`. Every queue is validated as the queue of the scenario before anything
+/// runs. Runs are headless, so this returns as soon as the simulations are done. Of the
+/// scenario's options, only the MLFQ boost period, the scheduling script and the plugin are
+/// used, comparisons aren't sampled.
+pub fn compare(
+    scenario: &Scenario,
+    queues: &[Queues],
+    start: SystemTime,
+    rank_by: &str,
+) -> Result<Comparison, SimError> {
+    if queues.is_empty() {
+        let message = "choose at least one algorithm to compare";
+        return Err(vec![FieldError::new("queues", message)].into());
+    }
+    for queue in queues {
+        let run = Scenario {
+            queue: *queue,
+            disciplines: levels(*queue, scenario.disciplines),
+            ..scenario.clone()
+        };
+        SimError::check(run.validate())?;
+    }
+    let processes = scenario.source.build(start, &mut scenario.rng())?;
+    let options = &scenario.options;
+    let results: Vec<RunResult> = queues
+        .iter()
+        .map(|queue| {
+            Simulator::simulate(
+                *queue,
+                scenario.context_switch,
+                scenario.time_quantum,
+                levels(*queue, scenario.disciplines),
+                RunOptions {
+                    mlfq_boost_period: options.mlfq_boost_period,
+                    policy: options.policy.clone(),
+                    plugin: options.plugin.clone(),
+                    ..RunOptions::default()
+                },
+                processes.clone(),
+                Recorder::batch(queue.to_string(), start),
+            )
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut ranking = Vec::with_capacity(results.len());
+    for result in &results {
+        let value = result
+            .metrics
+            .get(rank_by)
+            .and_then(|m| m.as_f64())
+            .ok_or_else(|| {
+                let message = format!("\"{}\" is not a metric that can be ranked", rank_by);
+                SimError::from(vec![FieldError::new("rank_by", message)])
+            })?;
+        ranking.push(RankedQueue {
+            queue: result.queue.clone(),
+            value,
+        });
+    }
+    ranking.sort_by(|r1, r2| {
+        let order = r1.value.partial_cmp(&r2.value).unwrap_or(std::cmp::Ordering::Equal);
+        if higher_is_better(rank_by) {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+
+    Ok(Comparison {
+        rank_by: rank_by.to_string(),
+        processes: process_deltas(&results),
+        kernel: None,
+        ranking,
+        runs: results
+            .into_iter()
+            .map(|result| ComparedRun {
+                queue: result.queue,
+                metrics: result.metrics,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::ProcessSource;
+    use crate::workload::WorkloadEntry;
+
+    fn entry(name: &str, burst: u64) -> WorkloadEntry {
+        WorkloadEntry {
+            name: Some(name.to_string()),
+            arrival_time: Duration::ZERO,
+            cpu_burst_time: Duration::from_millis(burst),
+            process_type: None,
+            priority: None,
+            io_phases: Vec::new(),
+            deadline: None,
+        }
+    }
+
+    /// A long job ahead of two short ones, which FCFS runs in order and SJF runs last.
+    fn convoy() -> Scenario {
+        let workload = vec![entry("P1", 100), entry("P2", 10), entry("P3", 10)];
+        Scenario {
+            seed: Some(7),
+            ..Scenario::new(Queues::FCFS, ProcessSource::Workload(workload))
+        }
+    }
+
+    fn ranked(comparison: &Comparison) -> Vec<&str> {
+        comparison.ranking.iter().map(|r| r.queue.as_str()).collect()
+    }
+
+    #[test]
+    fn lower_waiting_time_ranks_first() {
+        let scenario = convoy();
+        let queues = [Queues::FCFS, Queues::SJF];
+        let comparison = compare(&scenario, &queues, SystemTime::now(), "average_waiting_time");
+        let comparison = comparison.unwrap();
+        assert_eq!(ranked(&comparison), ["SJF", "FCFS"]);
+        assert!(comparison.ranking[0].value < comparison.ranking[1].value);
+    }
+
+    #[test]
+    fn higher_fairness_ranks_first() {
+        let scenario = convoy();
+        let queues = [Queues::FCFS, Queues::SJF];
+        let comparison = compare(&scenario, &queues, SystemTime::now(), "jain_fairness_slowdown");
+        let comparison = comparison.unwrap();
+        assert_eq!(ranked(&comparison), ["SJF", "FCFS"]);
+        assert!(comparison.ranking[0].value > comparison.ranking[1].value);
+    }
+
+    #[test]
+    fn deltas_are_against_the_first_queue() {
+        let scenario = convoy();
+        let queues = [Queues::FCFS, Queues::SJF];
+        let comparison = compare(&scenario, &queues, SystemTime::now(), "average_waiting_time");
+        let processes = comparison.unwrap().processes;
+        assert_eq!(processes.len(), 3);
+        assert_eq!(processes[0].name.as_deref(), Some("P1"));
+        // the long job waits for both short ones under SJF
+        assert_eq!(processes[0].waiting_delta[0], 0.0);
+        assert!(processes[0].waiting_delta[1] > 0.0);
+        assert!(processes[1].waiting_delta[1] < 0.0);
+    }
+
+    #[test]
+    fn every_queue_is_validated() {
+        let scenario = Scenario {
+            time_quantum: Duration::ZERO,
+            ..convoy()
+        };
+        let queues = [Queues::FCFS, Queues::SRF];
+        let error = compare(&scenario, &queues, SystemTime::now(), "average_waiting_time");
+        assert!(error.unwrap_err().to_string().contains("quantum"));
+        assert!(compare(&scenario, &[], SystemTime::now(), "average_waiting_time").is_err());
+    }
+
+    #[test]
+    fn unknown_metrics_are_rejected() {
+        let comparison = compare(&convoy(), &[Queues::FCFS], SystemTime::now(), "speed");
+        assert!(comparison.unwrap_err().to_string().contains("\"speed\""));
+    }
+
+    #[test]
+    fn args_take_a_generator_or_a_workload() {
+        let json = r#"{
+            "config": { "queue": "RR", "time_quantum": 5 },
+            "generator": { "arrival_lambda": 1.0, "burst_lambda": 2.0, "processes": 5 },
+            "queues": ["FCFS", "SJF", "RR"]
+        }"#;
+        let mut args: CompareArgs = serde_json::from_str(json).unwrap();
+        let comparison = args.compare(SystemTime::now()).unwrap();
+        assert_eq!(comparison.processes.len(), 5);
+        assert_eq!(comparison.rank_by, "average_waiting_time");
+
+        let fields = |args: &CompareArgs| match args.scenario() {
+            Err(SimError::InvalidConfig { fields }) => {
+                fields.into_iter().map(|f| f.field).collect::<Vec<_>>()
+            }
+            _ => panic!("expected an invalid config"),
+        };
+        args.workload = Some(vec![entry("P1", 0)]);
+        assert_eq!(fields(&args), ["generator"]);
+        args.generator = None;
+        assert_eq!(fields(&args), ["workload[0].cpu_burst_time"]);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::error::FieldError;
+use crate::plugin::PluginPolicy;
+use crate::policy::ScriptPolicy;
+use crate::process_gen::ProcessType;
+use crate::queue_engine::{QueueDiscipline, Queues};
+use crate::scenario::{ProcessSource, Scenario};
+use crate::session::{self, PlaybackSpeed};
+use crate::simulator::RunOptions;
+use crate::workload::WorkloadEntry;
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Utils -------------------------------------------------------------------------------------------
+
+/// How a run shown in the UI is set up, the same for every run command. Durations are in ms.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunConfig {
+    pub queue: Queues,
+    #[serde(default)]
+    pub context_switch: u64,
+    #[serde(default)]
+    pub time_quantum: u64,
+    pub disciplines: Option<[QueueDiscipline; 4]>, // one per level, only for MLQ and MLFQ
+    pub starvation_threshold: Option<u64>,
+    pub script: Option<String>, // the Rhai source of the SCRIPT discipline
+    pub plugin: Option<String>, // the path of the WebAssembly policy of the PLUGIN discipline
+    #[serde(default)]
+    pub speed: PlaybackSpeed,
+    #[serde(default)]
+    pub paused: bool, // see Sessions::start
+}
+
+/// The processes `run_simulation` generates: exponential interarrival times and bursts.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GeneratorConfig {
+    pub arrival_lambda: f64,
+    pub burst_lambda: f64,
+    pub processes: usize,
+}
+
+/// The field of a run command's arguments a field of the scenario they set up comes from.
+fn config_field(field: &str) -> String {
+    let field = field.strip_prefix("scenario.").unwrap_or(field);
+    match field {
+        "options.policy" => String::from("config.script"),
+        "options.plugin" => String::from("config.plugin"),
+        "options.starvation_threshold" => String::from("config.starvation_threshold"),
+        "source.arrival_lambda" => String::from("generator.arrival_lambda"),
+        "source.burst_lambda" => String::from("generator.burst_lambda"),
+        "source.count" => String::from("generator.processes"),
+        _ => match field.strip_prefix("source.") {
+            Some(workload) => workload.to_string(),
+            None => format!("config.{}", field),
+        },
+    }
+}
+
+// Validation --------------------------------------------------------------------------------------
+
+impl RunConfig {
+    /// The run this config sets up for `source`, sampled for the UI's charts, or everything
+    /// wrong with the two. The rules are the scenario's, see `Scenario::validate`, named after
+    /// the fields they come from here. The script is compiled and the plugin loaded only here.
+    pub fn validate(&self, source: ProcessSource) -> Result<Scenario, Vec<FieldError>> {
+        let mut errors = Vec::new();
+        let policy = self.policy().unwrap_or_else(|e| {
+            errors.push(FieldError::new("config.script", e));
+            None
+        });
+        let plugin = self.plugin().unwrap_or_else(|e| {
+            errors.push(FieldError::new("config.plugin", e));
+            None
+        });
+        errors.extend(session::check_speed("config.speed", self.speed));
+        let scenario = Scenario {
+            queue: self.queue,
+            disciplines: self.disciplines,
+            time_quantum: self.time_quantum(),
+            context_switch: self.context_switch(),
+            options: RunOptions {
+                policy,
+                plugin,
+                ..RunOptions::ui(self.starvation_threshold())
+            },
+            source,
+            seed: None,
+        };
+        for error in scenario.validate() {
+            let field = config_field(&error.field);
+            // a script that doesn't compile isn't also missing
+            if !errors.iter().any(|e| e.field == field) {
+                errors.push(FieldError::new(field, error.message));
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(scenario),
+            false => Err(errors),
+        }
+    }
+
+    /// `validate` for the processes typed in by hand, (arrival time, burst, process type) with
+    /// times in ms, their type names checked too.
+    pub fn validate_manual(
+        &self,
+        processes: &[(u64, u64, Option<String>)],
+    ) -> Result<Scenario, Vec<FieldError>> {
+        let source = ProcessSource::Workload(manual_workload(processes));
+        let (scenario, mut errors) = match self.validate(source) {
+            Ok(scenario) => (Some(scenario), Vec::new()),
+            Err(errors) => (None, errors),
+        };
+        for (index, (_, _, process_type)) in processes.iter().enumerate() {
+            if let Some(name) = process_type {
+                if ProcessType::from_name(name).is_none() {
+                    errors.push(FieldError::new(
+                        format!("array_of_processes[{}].process_type", index),
+                        format!(
+                            "unknown process type \"{}\", expected system, interactive, batch or student",
+                            name
+                        ),
+                    ));
+                }
+            }
+        }
+        match scenario {
+            Some(scenario) if errors.is_empty() => Ok(scenario),
+            _ => Err(errors),
+        }
+    }
+
+    pub fn context_switch(&self) -> Duration {
+        Duration::from_millis(self.context_switch)
+    }
+
+    pub fn time_quantum(&self) -> Duration {
+        Duration::from_millis(self.time_quantum)
+    }
+
+    pub fn starvation_threshold(&self) -> Option<Duration> {
+        self.starvation_threshold.map(Duration::from_millis)
+    }
+
+    fn policy(&self) -> Result<Option<Arc<ScriptPolicy>>, String> {
+        self.script
+            .as_deref()
+            .map(|source| ScriptPolicy::compile("script", source).map(Arc::new))
+            .transpose()
+    }
+
+    fn plugin(&self) -> Result<Option<Arc<PluginPolicy>>, String> {
+        self.plugin
+            .as_deref()
+            .map(|path| PluginPolicy::load(Path::new(path)).map(Arc::new))
+            .transpose()
+    }
+}
+
+impl GeneratorConfig {
+    pub fn source(&self) -> ProcessSource {
+        ProcessSource::Generated {
+            arrival_lambda: self.arrival_lambda,
+            burst_lambda: self.burst_lambda,
+            count: self.processes,
+        }
+    }
+}
+
+/// The processes typed in by hand as a workload, see `RunConfig::validate_manual`.
+fn manual_workload(processes: &[(u64, u64, Option<String>)]) -> Vec<WorkloadEntry> {
+    processes
+        .iter()
+        .map(|p| WorkloadEntry {
+            name: None,
+            arrival_time: Duration::from_millis(p.0),
+            cpu_burst_time: Duration::from_millis(p.1),
+            process_type: p.2.as_deref().and_then(ProcessType::from_name),
+            priority: None,
+            io_phases: Vec::new(),
+            deadline: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> RunConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn fields(validated: Result<Scenario, Vec<FieldError>>) -> Vec<String> {
+        validated.unwrap_err().into_iter().map(|e| e.field).collect()
+    }
+
+    #[test]
+    fn the_scenario_rules_are_named_after_the_config() {
+        let config = config(r#"{ "queue": "FCFS", "disciplines": ["RR", "RR", "RR", "RR"] }"#);
+        let generator = ProcessSource::Generated {
+            arrival_lambda: 0.0,
+            burst_lambda: 1.0,
+            count: 0,
+        };
+        assert_eq!(
+            fields(config.validate(generator)),
+            ["config.disciplines", "generator.arrival_lambda", "generator.processes"]
+        );
+
+        let config = self::config(r#"{ "queue": "SRTF", "starvation_threshold": 0 }"#);
+        let workload = ProcessSource::Workload(manual_workload(&[(0, 3, None), (1, 0, None)]));
+        assert_eq!(
+            fields(config.validate(workload)),
+            ["config.time_quantum", "config.starvation_threshold", "workload[1].cpu_burst_time"]
+        );
+    }
+
+    #[test]
+    fn a_script_that_doesnt_compile_is_reported_once() {
+        let config = config(r#"{ "queue": "SCRIPT", "time_quantum": 2, "script": "fn (" }"#);
+        let workload = ProcessSource::Workload(manual_workload(&[(0, 3, None)]));
+        assert_eq!(fields(config.validate(workload)), ["config.script"]);
+    }
+
+    #[test]
+    fn process_type_names_are_checked() {
+        let config = config(r#"{ "queue": "FCFS" }"#);
+        let validated = config.validate_manual(&[(0, 3, Some(String::from("robot")))]);
+        assert_eq!(fields(validated), ["array_of_processes[0].process_type"]);
+    }
+}
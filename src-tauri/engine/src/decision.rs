@@ -0,0 +1,364 @@
+use crate::events::{EventLog, SimEvent};
+use crate::process_gen::Process;
+use crate::queue_engine::QueueDiscipline;
+use crate::util::{listed, millis, number};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+// Utils -------------------------------------------------------------------------------------------
+
+/// The event the decisions are sent to the UI as, and logged under.
+pub const EVENT: &str = "scheduling_decision";
+
+/// How a process is called in an explanation, its workload name or the start of its id.
+fn label(process: &Process) -> String {
+    match &process.name {
+        Some(name) => name.clone(),
+        None => process.id.to_string()[..8].to_string(),
+    }
+}
+
+fn remaining(process: &Process) -> Duration {
+    process.cpu_burst_time.saturating_sub(process.processed_time)
+}
+
+fn response_ratio(process: &Process, now: SystemTime) -> f64 {
+    (process.waiting_at(now) + process.cpu_burst_time).as_secs_f64()
+        / process.cpu_burst_time.as_secs_f64()
+}
+
+/// What `discipline`'s sort compares, lower first, so equal values are a tie. None when the
+/// queue isn't sorted.
+fn order(discipline: QueueDiscipline, process: &Process, now: SystemTime) -> Option<f64> {
+    match discipline {
+        QueueDiscipline::SPN | QueueDiscipline::SJF => Some(process.cpu_burst_time.as_secs_f64()),
+        QueueDiscipline::SRF => Some(remaining(process).as_secs_f64()),
+        QueueDiscipline::HRRN => Some(-response_ratio(process, now)),
+        // a process that never ran counts as having run at the epoch, like in the sort
+        QueueDiscipline::RR => Some(
+            process
+                .last_execution
+                .unwrap_or(SystemTime::UNIX_EPOCH)
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64(),
+        ),
+        QueueDiscipline::FIFO
+        | QueueDiscipline::FCFS
+        | QueueDiscipline::SCRIPT
+        | QueueDiscipline::PLUGIN => None,
+    }
+}
+
+fn key_name(discipline: QueueDiscipline) -> &'static str {
+    match discipline {
+        QueueDiscipline::FIFO | QueueDiscipline::FCFS => "queue order",
+        QueueDiscipline::SPN | QueueDiscipline::SJF => "burst",
+        QueueDiscipline::SRF => "remaining time",
+        QueueDiscipline::HRRN => "response ratio",
+        QueueDiscipline::RR => "last run",
+        QueueDiscipline::SCRIPT => "script",
+        QueueDiscipline::PLUGIN => "plugin",
+    }
+}
+
+// Decision ----------------------------------------------------------------------------------------
+
+/// A ready process as the scheduler saw it when it picked, every key a discipline can order by.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candidate {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub burst: Duration,
+    pub remaining: Duration,
+    pub waiting: Duration, // up to the decision
+    pub response_ratio: f64,
+    pub vruntime: Duration, // the CPU time it has had, what a fair share scheduler orders by
+    pub priority: Option<u8>,
+    pub last_run: Option<Duration>, // since the start of the run, none if it never ran
+}
+
+impl Candidate {
+    fn new(process: &Process, start: SystemTime, now: SystemTime) -> Self {
+        Candidate {
+            id: process.id,
+            name: process.name.clone(),
+            burst: process.cpu_burst_time,
+            remaining: remaining(process),
+            waiting: process.waiting_at(now),
+            response_ratio: response_ratio(process, now),
+            vruntime: process.processed_time,
+            priority: process.priority,
+            last_run: process.last_execution.map(|t| t.duration_since(start).unwrap_or_default()),
+        }
+    }
+}
+
+/// Why the scheduler did what it did, sent as the `scheduling_decision` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Decision {
+    /// `winner` was taken from `candidates`, the ready queue in its order before the pick, by
+    /// `key`. `tie_break` is the rule that chose between candidates with the winner's key.
+    Dispatch {
+        queue: i8,
+        discipline: QueueDiscipline,
+        key: String,
+        candidates: Vec<Candidate>,
+        winner: Uuid,
+        tie_break: Option<String>,
+        explanation: String,
+    },
+    Preemption {
+        id: Uuid,
+        queue: i8,
+        reason: String,
+    },
+    Demotion {
+        id: Uuid,
+        from: i8,
+        to: i8,
+        reason: String,
+    },
+    Promotion {
+        id: Uuid,
+        from: i8,
+        to: i8,
+        reason: String,
+    },
+}
+
+impl Decision {
+    /// `discipline` picked `winner` out of `ready` at `now`.
+    pub fn dispatch(
+        queue: i8,
+        discipline: QueueDiscipline,
+        ready: &[Process],
+        winner: &Process,
+        start: SystemTime,
+        now: SystemTime,
+    ) -> Self {
+        let name = label(winner);
+        let others: Vec<&Process> = ready.iter().filter(|p| p.id != winner.id).collect();
+        let tied: Vec<&Process> = match order(discipline, winner, now) {
+            Some(key) => others
+                .iter()
+                .copied()
+                .filter(|p| order(discipline, p, now) == Some(key))
+                .collect(),
+            None => Vec::new(),
+        };
+        let mut explanation = match discipline {
+            _ if others.is_empty() => format!("{} is the only ready process.", name),
+            QueueDiscipline::FIFO | QueueDiscipline::FCFS => {
+                format!("{} is first in the queue.", name)
+            }
+            QueueDiscipline::SPN | QueueDiscipline::SJF => format!(
+                "{} has the shortest burst, {} ms.",
+                name,
+                number(millis(winner.cpu_burst_time))
+            ),
+            QueueDiscipline::SRF => format!(
+                "{} has the least remaining time, {} ms.",
+                name,
+                number(millis(remaining(winner)))
+            ),
+            QueueDiscipline::HRRN => format!(
+                "{} has the highest response ratio, {} = ({} ms waiting + {} ms burst) / {} ms.",
+                name,
+                number(response_ratio(winner, now)),
+                number(millis(winner.waiting_at(now))),
+                number(millis(winner.cpu_burst_time)),
+                number(millis(winner.cpu_burst_time))
+            ),
+            QueueDiscipline::RR => match winner.last_execution {
+                Some(last) => format!(
+                    "{} ran longest ago, at {} ms.",
+                    name,
+                    number(millis(last.duration_since(start).unwrap_or_default()))
+                ),
+                None => format!("{} hasn't run yet, and those that haven't go first.", name),
+            },
+            QueueDiscipline::SCRIPT => format!("The script's pick_next chose {}.", name),
+            QueueDiscipline::PLUGIN => format!("The plugin's pick_next chose {}.", name),
+        };
+        let tie_break = (!tied.is_empty()).then(|| {
+            String::from("equal keys keep their queue order, the one that joined first goes first")
+        });
+        if !tied.is_empty() {
+            explanation += &format!(
+                " It ties with {} on {} and is ahead of {} in the queue.",
+                listed(&tied.iter().map(|p| label(p)).collect::<Vec<_>>()),
+                key_name(discipline),
+                if tied.len() == 1 { "it" } else { "them" }
+            );
+        }
+        Decision::Dispatch {
+            queue,
+            discipline,
+            key: key_name(discipline).to_string(),
+            candidates: ready.iter().map(|p| Candidate::new(p, start, now)).collect(),
+            winner: winner.id,
+            tie_break,
+            explanation,
+        }
+    }
+
+    /// `process` is stopped after a time quantum of `discipline`, `requeued` when it goes back
+    /// into the same queue rather than to another MLFQ level.
+    pub fn preemption(
+        queue: i8,
+        discipline: QueueDiscipline,
+        process: &Process,
+        time_quantum: Duration,
+        requeued: bool,
+    ) -> Self {
+        let quantum = number(millis(time_quantum));
+        let why = match discipline {
+            QueueDiscipline::RR if requeued => {
+                format!("used up its {} ms quantum and goes to the back of the queue", quantum)
+            }
+            QueueDiscipline::RR => format!("used up its {} ms quantum", quantum),
+            QueueDiscipline::SCRIPT => {
+                format!("ran a {} ms quantum and the script's preempt asked for it", quantum)
+            }
+            QueueDiscipline::PLUGIN => {
+                format!("ran a {} ms quantum and the plugin's on_tick asked for it", quantum)
+            }
+            discipline => format!("ran a {} ms quantum, then {} picks again", quantum, discipline),
+        };
+        Decision::Preemption {
+            id: process.id,
+            queue,
+            reason: format!(
+                "{} {}, with {} ms left.",
+                label(process),
+                why,
+                number(millis(remaining(process)))
+            ),
+        }
+    }
+
+    /// MLFQ moves `process` from level `from` to level `to` after it used its quantum there.
+    pub fn level_change(process: &Process, from: i8, to: i8) -> Self {
+        let name = label(process);
+        let waited = number(millis(process.waiting_time));
+        let burst = number(millis(process.cpu_burst_time));
+        if to < from {
+            return Decision::Promotion {
+                id: process.id,
+                from,
+                to,
+                reason: format!(
+                    "{} has waited {} ms, at least its {} ms burst, so it moves up to level {}.",
+                    name, waited, burst, to
+                ),
+            };
+        }
+        let reason = match from {
+            1 => format!("{} used its whole quantum in level 1, so it drops to level {}.", name, to),
+            _ => format!(
+                "{} used its whole quantum and has waited {} ms, less than its {} ms burst, so it \
+                 drops to level {}.",
+                name, waited, burst, to
+            ),
+        };
+        Decision::Demotion {
+            id: process.id,
+            from,
+            to,
+            reason,
+        }
+    }
+
+    /// MLFQ's priority boost moves `process` from level `from` back to level 1.
+    pub fn boost(process: &Process, from: i8, period: Duration) -> Self {
+        Decision::Promotion {
+            id: process.id,
+            from,
+            to: 1,
+            reason: format!(
+                "The priority boost every {} ms moves {} back to level 1.",
+                number(millis(period)),
+                label(process)
+            ),
+        }
+    }
+
+    /// The explanation, or the reason.
+    pub fn text(&self) -> &str {
+        match self {
+            Decision::Dispatch { explanation, .. } => explanation,
+            Decision::Preemption { reason, .. }
+            | Decision::Demotion { reason, .. }
+            | Decision::Promotion { reason, .. } => reason,
+        }
+    }
+}
+
+/// The decisions of a logged run, with the time since its start each was made at.
+pub fn from_log(log: &EventLog) -> Vec<(Duration, Decision)> {
+    log.events
+        .iter()
+        .filter_map(|logged| match &logged.event {
+            SimEvent::Emit { event, payload } if event == EVENT => {
+                let decision = serde_json::from_value(payload.clone()).ok()?;
+                Some((logged.time, decision))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_gen::ProcessType;
+    use crate::queue_engine::ReadyQueue;
+
+    fn process(name: &str, burst: u64, arrival: SystemTime) -> Process {
+        let mut process = Process::new(
+            Duration::from_millis(burst),
+            arrival,
+            Some(ProcessType::BatchProcess),
+        );
+        process.name = Some(name.to_string());
+        process
+    }
+
+    // P2 and P3 have the same burst, P1's is longer and it's first in the queue
+    #[test]
+    fn ties_are_explained_the_way_the_queue_breaks_them() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let now = start + Duration::from_millis(10);
+        let cases = [
+            (QueueDiscipline::FCFS, "P1", None),
+            (QueueDiscipline::SPN, "P2", Some("It ties with P3 on burst and is ahead of it")),
+            (QueueDiscipline::SRF, "P2", Some("It ties with P3 on remaining time and is ahead of it")),
+            (QueueDiscipline::HRRN, "P2", Some("It ties with P3 on response ratio and is ahead of it")),
+            // none of them ran, so they all count as having run at the epoch
+            (QueueDiscipline::RR, "P1", Some("It ties with P2 and P3 on last run and is ahead of them")),
+        ];
+        for (discipline, expected, tie) in cases {
+            let mut queue = ReadyQueue::new(discipline, Duration::ZERO, Duration::from_millis(5));
+            for p in [process("P1", 8, start), process("P2", 4, start), process("P3", 4, start)] {
+                queue.enqueue(p);
+            }
+            let ready = queue.ready().to_vec();
+            let winner = queue.dequeue(now).unwrap();
+            assert_eq!(winner.name.as_deref(), Some(expected), "{:?}", discipline);
+
+            let Decision::Dispatch { winner: id, tie_break, explanation, .. } =
+                Decision::dispatch(1, discipline, &ready, &winner, start, now)
+            else {
+                panic!("{:?} didn't dispatch", discipline);
+            };
+            assert_eq!(id, winner.id);
+            assert_eq!(tie_break.is_some(), tie.is_some(), "{:?}: {}", discipline, explanation);
+            if let Some(tie) = tie {
+                assert!(explanation.contains(tie), "{:?}: {}", discipline, explanation);
+            }
+        }
+    }
+}
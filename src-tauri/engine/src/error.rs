@@ -0,0 +1,118 @@
+use serde::Serialize;
+use std::fmt;
+use std::time::Duration;
+use uuid::Uuid;
+
+// Utils -------------------------------------------------------------------------------------------
+
+/// One thing wrong with what a command was given. `field` is the path of the value in the
+/// command's arguments, e.g. `config.time_quantum` or `workload[3]`, so the UI can put the
+/// message next to the input it came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        FieldError {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+// Error -------------------------------------------------------------------------------------------
+
+/// Why a run couldn't be started or didn't make it to the end. A command that can't start a run
+/// returns it as its error, a run that fails halfway sends it as `simulation_failed`. In JSON
+/// it's tagged by `kind`, e.g. `{"kind": "invalid_config", "fields": [...]}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SimError {
+    InvalidConfig { fields: Vec<FieldError> }, // every field that's wrong, not just the first
+    Session { message: String },
+    InvalidRate { rate: f64 }, // of an exponential distribution, it has to be positive
+    ProcessOverrun {
+        // the process was given more CPU time than its burst, the engine's accounting is off
+        id: Uuid,
+        processed: Duration,
+        burst: Duration,
+    },
+    Emit { event: String, message: String }, // the UI couldn't get an event, e.g. its window closed
+    Script {
+        // a scheduling script failed or returned something the engine can't use
+        script: String,
+        function: String,
+        message: String,
+    },
+    Plugin {
+        // a WebAssembly policy trapped, ran out of fuel or returned something the engine can't use
+        plugin: String,
+        function: String,
+        message: String,
+    },
+}
+
+impl SimError {
+    /// `Ok` when validation found nothing wrong.
+    pub fn check(fields: Vec<FieldError>) -> Result<(), SimError> {
+        if fields.is_empty() {
+            Ok(())
+        } else {
+            Err(SimError::InvalidConfig { fields })
+        }
+    }
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimError::InvalidConfig { fields } => {
+                let fields: Vec<String> = fields.iter().map(|e| e.to_string()).collect();
+                write!(f, "invalid configuration: {}", fields.join("; "))
+            }
+            SimError::Session { message } => write!(f, "{}", message),
+            SimError::InvalidRate { rate } => {
+                write!(f, "rate parameter must be positive, got {}", rate)
+            }
+            SimError::ProcessOverrun { id, processed, burst } => write!(
+                f,
+                "process {} ran for {:?}, longer than its burst of {:?}",
+                id, processed, burst
+            ),
+            SimError::Emit { event, message } => {
+                write!(f, "couldn't send {} to the UI: {}", event, message)
+            }
+            SimError::Script { script, function, message } => {
+                write!(f, "{} failed in {}: {}", script, function, message)
+            }
+            SimError::Plugin { plugin, function, message } => {
+                write!(f, "plugin {} failed in {}: {}", plugin, function, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SimError {}
+
+// commands and modules that report errors as plain messages take it with `?`
+impl From<SimError> for String {
+    fn from(error: SimError) -> Self {
+        error.to_string()
+    }
+}
+
+// what a `validate` that found something wrong returns
+impl From<Vec<FieldError>> for SimError {
+    fn from(fields: Vec<FieldError>) -> Self {
+        SimError::InvalidConfig { fields }
+    }
+}
@@ -0,0 +1,297 @@
+use crate::clock::SimClock;
+use crate::error::SimError;
+use crate::process_gen::SerializableProcess;
+use crate::session::{Session, SessionEvent, SessionId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+// Utils -------------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SimEvent {
+    /// Everything that happens during a run. `Emit` is what the UI was sent at that moment, we
+    /// keep it so a saved run can be played back without simulating it again.
+    RunStarted {
+        started_at: DateTime<Utc>,
+        queue: String,
+    },
+    Arrival {
+        process: SerializableProcess,
+    },
+    Dispatch {
+        id: Uuid,
+        queue: i8,
+    },
+    Preemption {
+        id: Uuid,
+        queue: i8,
+        remaining: Duration,
+    },
+    Demotion {
+        id: Uuid,
+        from: i8,
+        to: i8,
+    },
+    Promotion {
+        id: Uuid,
+        from: i8,
+        to: i8,
+    },
+    ContextSwitch {
+        duration: Duration,
+    },
+    IoStart {
+        id: Uuid,
+        queue: i8,
+        duration: Duration,
+    },
+    IoEnd {
+        id: Uuid,
+        queue: i8,
+    },
+    Completion {
+        id: Uuid,
+        queue: i8,
+    },
+    Emit {
+        event: String,
+        payload: Value,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    pub seq: u64,
+    pub time: Duration, // time since the start of the run
+    #[serde(flatten)]
+    pub event: SimEvent,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EventLog {
+    pub events: Vec<LoggedEvent>,
+}
+
+impl EventLog {
+    pub fn to_json_lines(&self) -> Result<String, serde_json::Error> {
+        let mut lines = String::new();
+        for event in &self.events {
+            lines.push_str(&serde_json::to_string(event)?);
+            lines.push('\n');
+        }
+        Ok(lines)
+    }
+
+    pub fn from_json_lines(reader: impl BufRead) -> Result<Self, String> {
+        let mut events = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event: LoggedEvent = serde_json::from_str(&line)
+                .map_err(|e| format!("line {}: {}", index + 1, e))?;
+            events.push(event);
+        }
+        Ok(EventLog { events })
+    }
+}
+
+/// Where the events of a live run go, the desktop app sends them to its window. The payload is
+/// already JSON, an error stops the run with `SimError::Emit`.
+pub trait EventSink: Send + Sync {
+    fn emit(&self, event: &str, payload: Value) -> Result<(), String>;
+}
+
+impl<F> EventSink for F
+where
+    F: Fn(&str, Value) -> Result<(), String> + Send + Sync,
+{
+    fn emit(&self, event: &str, payload: Value) -> Result<(), String> {
+        self(event, payload)
+    }
+}
+
+/// Serializes `payload` and sends it to `sink`.
+pub fn send<S: Serialize>(sink: &dyn EventSink, event: &str, payload: S) -> Result<(), String> {
+    let payload = serde_json::to_value(payload).map_err(|e| e.to_string())?;
+    sink.emit(event, payload)
+}
+
+// Recorder ----------------------------------------------------------------------------------------
+
+pub struct Recorder {
+    // the engine sends everything through the recorder instead of the sink directly, so
+    // every event the UI sees also ends up in the log. It also owns the run's clock since every
+    // event is stamped with it. Headless runs have no sink.
+    sink: Option<Arc<dyn EventSink>>,
+    session: Option<SessionId>,
+    pub clock: SimClock,
+    log: EventLog,
+    logging: bool, // batch runs only need the results, keeping the log would just slow them down
+    failure: Option<SimError>, // the first event the UI couldn't be sent, see check
+}
+
+impl Recorder {
+    /// A run shown live, its events go to the session's sink. The clock is paced to real time
+    /// so the UI can follow it and the session controls it.
+    pub fn new(queue: String, session: Arc<Session>) -> Self {
+        let id = session.id;
+        let sink = session.sink();
+        let mut recorder =
+            Recorder::start(Some(sink), SimClock::paced(SystemTime::now(), session), queue);
+        recorder.session = Some(id);
+        recorder
+    }
+
+    /// A run nobody watches, it goes as fast as the simulation can.
+    pub fn headless(queue: String, start: SystemTime) -> Self {
+        Recorder::start(None, SimClock::new(start), queue)
+    }
+
+    /// A headless run that keeps no event log, for comparisons and sweeps that run many times.
+    pub fn batch(queue: String, start: SystemTime) -> Self {
+        let mut recorder = Recorder::headless(queue, start);
+        recorder.logging = false;
+        recorder
+    }
+
+    fn start(sink: Option<Arc<dyn EventSink>>, clock: SimClock, queue: String) -> Self {
+        let mut recorder = Recorder {
+            sink,
+            session: None,
+            clock,
+            log: EventLog::default(),
+            logging: true,
+            failure: None,
+        };
+        recorder.record(SimEvent::RunStarted {
+            started_at: DateTime::<Utc>::from(recorder.clock.start()),
+            queue,
+        });
+        recorder
+    }
+
+    pub fn record(&mut self, event: SimEvent) {
+        if !self.logging {
+            return;
+        }
+        let time = self.clock.elapsed();
+        let seq = self.log.events.len() as u64;
+        self.log.events.push(LoggedEvent { seq, time, event });
+    }
+
+    /// Whether anything emitted goes anywhere, so the engine can skip building what nobody
+    /// would see.
+    pub fn is_recording(&self) -> bool {
+        self.logging || self.sink.is_some()
+    }
+
+    pub fn emit<S: Serialize + Clone>(&mut self, event: &str, payload: S) {
+        if !self.logging && self.sink.is_none() {
+            return;
+        }
+        let value = match serde_json::to_value(&payload) {
+            Ok(value) => value,
+            Err(e) => {
+                // a payload that isn't JSON can't be logged or sent, the run stops on it
+                self.failure.get_or_insert(SimError::Emit {
+                    event: event.to_string(),
+                    message: e.to_string(),
+                });
+                return;
+            }
+        };
+        self.record(SimEvent::Emit {
+            event: event.to_string(),
+            payload: value,
+        });
+        if let Some(sink) = &self.sink {
+            let session = self.session;
+            let payload = SessionEvent { session, data: payload };
+            if let Err(message) = send(sink.as_ref(), event, payload) {
+                self.failure.get_or_insert(SimError::Emit {
+                    event: event.to_string(),
+                    message,
+                });
+            }
+        }
+    }
+
+    /// Fails once an event couldn't be sent to the UI. Events are sent from deep inside the
+    /// engine, so the simulation checks this between steps instead of after every emit.
+    pub fn check(&mut self) -> Result<(), SimError> {
+        match self.failure.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    pub fn finish(self) -> EventLog {
+        self.log
+    }
+}
+
+// Export ------------------------------------------------------------------------------------------
+
+pub fn export_json_lines(log: &EventLog, path: &str) -> Result<usize, String> {
+    let lines = log.to_json_lines().map_err(|e| e.to_string())?;
+    let mut file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+    file.write_all(lines.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(log.events.len())
+}
+
+pub fn read_json_lines(path: &str) -> Result<EventLog, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    EventLog::from_json_lines(BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queue_engine::{QueueDiscipline, Queues};
+    use crate::scenario::{ProcessSource, Scenario};
+    use std::collections::HashMap;
+
+    #[test]
+    fn json_lines_read_back_the_same_log() {
+        let source = ProcessSource::Generated {
+            arrival_lambda: 1.0,
+            burst_lambda: 2.0,
+            count: 5,
+        };
+        let scenario = Scenario {
+            disciplines: Some([QueueDiscipline::RR; 4]),
+            seed: Some(1),
+            ..Scenario::new(Queues::MLFQ, source)
+        };
+        let log = scenario.run().unwrap().log;
+        let name = format!("mehrmux-{}-log.jsonl", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        let path = path.to_string_lossy();
+        assert_eq!(export_json_lines(&log, &path).unwrap(), log.events.len());
+        let read = read_json_lines(&path).unwrap();
+        std::fs::remove_file(path.as_ref()).unwrap();
+        assert_eq!(read.to_json_lines().unwrap(), log.to_json_lines().unwrap());
+        assert!(matches!(read.events[0].event, SimEvent::RunStarted { .. }));
+    }
+
+    #[test]
+    fn a_payload_that_isnt_json_fails_the_run() {
+        let mut recorder = Recorder::headless(String::from("FCFS"), SystemTime::UNIX_EPOCH);
+        let payload: HashMap<(u8, u8), u8> = HashMap::from([((1, 2), 3)]); // keys must be strings
+        recorder.emit("bad_payload", payload);
+        match recorder.check() {
+            Err(SimError::Emit { event, .. }) => assert_eq!(event, "bad_payload"),
+            other => panic!("expected an emit error, got {:?}", other),
+        }
+        // nothing half-made was logged
+        assert_eq!(recorder.finish().events.len(), 1);
+    }
+}
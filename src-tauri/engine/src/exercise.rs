@@ -0,0 +1,544 @@
+use crate::grading::{self, Explainer, Segment};
+use crate::queue_engine::Queues;
+use crate::scenario::{ProcessSource, Scenario};
+use crate::util::{algorithm_name, listed, number};
+use crate::workload::WorkloadEntry;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+// Utils -------------------------------------------------------------------------------------------
+
+// workloads tried before giving up on the properties asked for
+const MAX_ATTEMPTS: usize = 1000;
+
+// the algorithms an exercise is about unless it names others
+const DEFAULT_ALGORITHMS: [&str; 5] = ["FCFS", "SPN", "SRTF", "RR", "HRRN"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    Easy,   // 4 processes, bursts up to 6 ms
+    Medium, // 5 processes, bursts up to 8 ms
+    Hard,   // 6 processes, bursts up to 10 ms, spread over a longer time
+}
+
+impl Difficulty {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "easy" => Some(Difficulty::Easy),
+            "medium" => Some(Difficulty::Medium),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    /// How many processes, the latest arrival and the longest burst, in ms.
+    fn shape(&self) -> (usize, u64, u64) {
+        match self {
+            Difficulty::Easy => (4, 6, 6),
+            Difficulty::Medium => (5, 10, 8),
+            Difficulty::Hard => (6, 14, 10),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+/// Something the workload is made to show.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Property {
+    Tie,        // one of the algorithms that order by a key picks between equal keys
+    Preemption, // SJF or SRTF takes the CPU away from a process before its burst is done
+    Idle,       // the CPU has nothing to run for a while
+}
+
+impl Property {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "tie" | "ties" => Some(Property::Tie),
+            "preemption" => Some(Property::Preemption),
+            "idle" => Some(Property::Idle),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Property::Tie => write!(f, "tie"),
+            Property::Preemption => write!(f, "preemption"),
+            Property::Idle => write!(f, "idle"),
+        }
+    }
+}
+
+// Spec --------------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExerciseSpec {
+    /// The same spec and seed give the same exercise.
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub algorithms: Vec<String>, // FIFO, FCFS, SPN, SJF, SRTF, HRRN or RR
+    pub time_quantum: u64,       // RR's, in ms
+    pub context_switch: u64,     // ms
+    pub properties: Vec<Property>,
+}
+
+impl Default for ExerciseSpec {
+    fn default() -> Self {
+        ExerciseSpec {
+            seed: 0,
+            difficulty: Difficulty::Medium,
+            algorithms: DEFAULT_ALGORITHMS.iter().map(|a| a.to_string()).collect(),
+            time_quantum: 2,
+            context_switch: 0,
+            properties: Vec::new(),
+        }
+    }
+}
+
+impl ExerciseSpec {
+    fn queues(&self) -> Result<Vec<Queues>, String> {
+        if self.algorithms.is_empty() {
+            return Err(String::from("an exercise needs at least one algorithm"));
+        }
+        let mut queues = Vec::with_capacity(self.algorithms.len());
+        for name in &self.algorithms {
+            let queue = Queues::from_name(name).ok_or(format!("unknown algorithm \"{}\"", name))?;
+            if matches!(queue, Queues::MLQ | Queues::MLFQ | Queues::SCRIPT | Queues::PLUGIN) {
+                return Err(format!(
+                    "{} can't be an exercise, they're about FIFO, FCFS, SPN, SJF, SRTF, HRRN and RR",
+                    queue
+                ));
+            }
+            if !queues.contains(&queue) {
+                queues.push(queue);
+            }
+        }
+        Ok(queues)
+    }
+
+    fn check(&self, queues: &[Queues]) -> Result<(), String> {
+        if self.time_quantum == 0 && queues.contains(&Queues::RR) {
+            return Err(String::from("RR needs a time quantum"));
+        }
+        if self.properties.contains(&Property::Tie) && !queues.iter().any(|q| *q != Queues::RR) {
+            return Err(String::from(
+                "a tie needs an algorithm that orders by a key: FIFO, FCFS, SPN, SJF, SRTF or HRRN",
+            ));
+        }
+        if self.properties.contains(&Property::Preemption)
+            && !queues.iter().any(|q| matches!(q, Queues::SJF | Queues::SRF))
+        {
+            return Err(String::from("a preemption needs SJF or SRTF among the algorithms"));
+        }
+        Ok(())
+    }
+}
+
+// Exercise ----------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExerciseProcess {
+    pub name: String,
+    pub arrival: u64, // ms
+    pub burst: u64,   // ms
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessAnswer {
+    pub name: String,
+    pub completion: f64, // ms, and so on
+    pub turnaround: f64,
+    pub waiting: f64,
+}
+
+/// The solution for one algorithm.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnswerKey {
+    pub algorithm: String,
+    pub time_quantum: u64, // what the answer was run with, for grading it, see `Exercise`
+    pub schedule: Vec<Segment>,
+    pub processes: Vec<ProcessAnswer>,
+    pub average_waiting_time: f64,    // ms
+    pub average_turnaround_time: f64, // ms
+}
+
+/// A textbook problem: the processes, what to do with them and the answers. SJF and SRTF are
+/// answered with a 1 ms quantum, so a shorter process takes the CPU the moment it arrives,
+/// and grading a student's answer for them takes the answer's `time_quantum`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exercise {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub properties: Vec<Property>, // the ones the workload was made to show
+    pub context_switch: u64,       // ms
+    pub text: String,
+    pub processes: Vec<ExerciseProcess>,
+    pub answers: Vec<AnswerKey>,
+}
+
+/// Draws workloads from the spec's seed until one shows every property asked for.
+pub fn generate(spec: &ExerciseSpec) -> Result<Exercise, String> {
+    let queues = spec.queues()?;
+    spec.check(&queues)?;
+    let mut rng = StdRng::seed_from_u64(spec.seed);
+    for _ in 0..MAX_ATTEMPTS {
+        let processes = draw(spec.difficulty, &mut rng);
+        let answers = queues
+            .iter()
+            .map(|queue| answer(*queue, spec, &processes))
+            .collect::<Result<Vec<AnswerKey>, String>>()?;
+        if spec.properties.iter().all(|property| shows(*property, spec, &processes, &answers)) {
+            return Ok(Exercise {
+                seed: spec.seed,
+                difficulty: spec.difficulty,
+                properties: spec.properties.clone(),
+                context_switch: spec.context_switch,
+                text: problem_text(spec, &queues, &processes),
+                processes,
+                answers,
+            });
+        }
+    }
+    let properties: Vec<String> = spec.properties.iter().map(|p| p.to_string()).collect();
+    Err(format!(
+        "no {} workload shows {} in {} tries, try another seed or difficulty",
+        spec.difficulty,
+        listed(&properties),
+        MAX_ATTEMPTS
+    ))
+}
+
+/// Integer arrivals and bursts, P1 arriving at 0 and the rest in order of arrival.
+fn draw(difficulty: Difficulty, rng: &mut StdRng) -> Vec<ExerciseProcess> {
+    let (count, latest_arrival, longest_burst) = difficulty.shape();
+    let mut arrivals: Vec<u64> = (1..count).map(|_| rng.gen_range(0..=latest_arrival)).collect();
+    arrivals.push(0);
+    arrivals.sort_unstable();
+    arrivals
+        .into_iter()
+        .enumerate()
+        .map(|(index, arrival)| ExerciseProcess {
+            name: format!("P{}", index + 1),
+            arrival,
+            burst: rng.gen_range(1..=longest_burst),
+        })
+        .collect()
+}
+
+fn answer(queue: Queues, spec: &ExerciseSpec, processes: &[ExerciseProcess]) -> Result<AnswerKey, String> {
+    let time_quantum = match queue {
+        Queues::SJF | Queues::SRF => 1,
+        _ => spec.time_quantum.max(1),
+    };
+    let workload = processes
+        .iter()
+        .map(|p| WorkloadEntry {
+            name: Some(p.name.clone()),
+            arrival_time: Duration::from_millis(p.arrival),
+            cpu_burst_time: Duration::from_millis(p.burst),
+            process_type: None,
+            priority: None,
+            io_phases: Vec::new(),
+            deadline: None,
+        })
+        .collect();
+    let mut scenario = Scenario::new(queue, ProcessSource::Workload(workload));
+    scenario.time_quantum = Duration::from_millis(time_quantum);
+    scenario.context_switch = Duration::from_millis(spec.context_switch);
+    scenario.seed = Some(0); // the same as grading runs it with
+    let result = scenario.run().map_err(|e| e.to_string())?;
+
+    let schedule = grading::schedule(&result);
+    let answers = processes
+        .iter()
+        .map(|p| {
+            let completion = schedule
+                .iter()
+                .filter(|s| s.process == p.name)
+                .map(|s| s.end)
+                .fold(0.0, f64::max);
+            let turnaround = completion - p.arrival as f64;
+            ProcessAnswer {
+                name: p.name.clone(),
+                completion,
+                turnaround,
+                waiting: turnaround - p.burst as f64,
+            }
+        })
+        .collect();
+    let metric = |name: &str| result.metrics.get(name).and_then(|m| m.as_f64()).unwrap_or_default();
+    Ok(AnswerKey {
+        algorithm: algorithm_name(queue),
+        time_quantum,
+        average_waiting_time: metric("average_waiting_time"),
+        average_turnaround_time: metric("average_turnaround_time"),
+        schedule,
+        processes: answers,
+    })
+}
+
+fn shows(property: Property, spec: &ExerciseSpec, processes: &[ExerciseProcess], answers: &[AnswerKey]) -> bool {
+    let runs_more_than_once = |answer: &AnswerKey| {
+        processes
+            .iter()
+            .any(|p| answer.schedule.iter().filter(|s| s.process == p.name).count() > 1)
+    };
+    match property {
+        Property::Tie => answers.iter().any(|answer| {
+            let Some(queue) = Queues::from_name(&answer.algorithm).filter(|q| *q != Queues::RR) else {
+                return false;
+            };
+            let explainer = Explainer::new(
+                queue,
+                answer.time_quantum,
+                spec.context_switch,
+                processes.iter().map(|p| (p.arrival, p.burst)),
+                &answer.schedule,
+            );
+            (0..answer.schedule.len()).any(|index| explainer.tied_at(index))
+        }),
+        Property::Preemption => answers
+            .iter()
+            .filter(|answer| matches!(answer.algorithm.as_str(), "SJF" | "SRTF"))
+            .any(runs_more_than_once),
+        Property::Idle => answers.first().is_some_and(|answer| {
+            answer
+                .schedule
+                .windows(2)
+                .any(|pair| pair[1].start - pair[0].end > spec.context_switch as f64 + 1e-6)
+        }),
+    }
+}
+
+fn problem_text(spec: &ExerciseSpec, queues: &[Queues], processes: &[ExerciseProcess]) -> String {
+    let algorithms: Vec<String> = queues
+        .iter()
+        .map(|queue| match queue {
+            Queues::RR => format!("RR with a time quantum of {} ms", spec.time_quantum),
+            queue => algorithm_name(*queue),
+        })
+        .collect();
+    let mut text = format!(
+        "The {} processes below arrive at the given times and need the CPU for the given bursts, \
+         all in ms. For {}, draw the Gantt chart and compute each process's turnaround and \
+         waiting time, and their averages.",
+        processes.len(),
+        listed(&algorithms)
+    );
+    if spec.context_switch > 0 {
+        text += &format!(" Every context switch takes {} ms.", spec.context_switch);
+    }
+    let preemptive: Vec<String> = queues
+        .iter()
+        .filter(|q| matches!(q, Queues::SJF | Queues::SRF))
+        .map(|q| algorithm_name(*q))
+        .collect();
+    if !preemptive.is_empty() {
+        text += &format!(
+            " {} {} preemptive: a process that arrives with a shorter {} takes the CPU at once.",
+            listed(&preemptive),
+            if preemptive.len() == 1 { "is" } else { "are" },
+            match preemptive.as_slice() {
+                [only] if only == "SJF" => "burst",
+                [_] => "remaining time",
+                _ => "burst, or remaining time,",
+            }
+        );
+    }
+    text += " When two processes tie, the one ahead in the ready queue goes first.";
+    text
+}
+
+// Markdown ----------------------------------------------------------------------------------------
+
+/// A Gantt chart in text, the times under the bars, `-` where the CPU is idle or switching.
+fn gantt(schedule: &[Segment]) -> String {
+    let mut bars = String::from("|");
+    let mut times = String::new();
+    let mut cell = |label: &str, start: f64| {
+        let start = number(start);
+        let width = (label.len() + 2).max(start.len() + 1).max(4);
+        bars += &format!("{:^width$}|", label, width = width);
+        times += &format!("{:<width$}", start, width = width + 1);
+    };
+    let mut end = 0.0;
+    for segment in schedule {
+        if segment.start - end > 1e-6 {
+            cell("-", end);
+        }
+        cell(&segment.process, segment.start);
+        end = segment.end;
+    }
+    times += &number(end);
+    format!("{}\n{}", bars, times.trim_end())
+}
+
+impl Exercise {
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# Scheduling exercise ({}, seed {})\n\n", self.difficulty, self.seed);
+        md += &self.text;
+        md += "\n\n| Process | Arrival | Burst |\n| --- | --- | --- |\n";
+        for p in &self.processes {
+            md += &format!("| {} | {} | {} |\n", p.name, p.arrival, p.burst);
+        }
+        md += "\n## Answers\n";
+        for answer in &self.answers {
+            md += &format!("\n### {}\n\n```text\n{}\n```\n\n", answer.algorithm, gantt(&answer.schedule));
+            md += "| Process | Completion | Turnaround | Waiting |\n| --- | --- | --- | --- |\n";
+            for p in &answer.processes {
+                md += &format!(
+                    "| {} | {} | {} | {} |\n",
+                    p.name,
+                    number(p.completion),
+                    number(p.turnaround),
+                    number(p.waiting)
+                );
+            }
+            md += &format!(
+                "\nAverage waiting time {} ms, average turnaround time {} ms.\n",
+                number(answer.average_waiting_time),
+                number(answer.average_turnaround_time)
+            );
+        }
+        md
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grading::Homework;
+    use serde_json::json;
+
+    fn spec(seed: u64, difficulty: Difficulty) -> ExerciseSpec {
+        ExerciseSpec {
+            seed,
+            difficulty,
+            ..ExerciseSpec::default()
+        }
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_exercise() {
+        let spec = spec(2024, Difficulty::Medium);
+        let (first, second) = (generate(&spec).unwrap(), generate(&spec).unwrap());
+        assert_eq!(serde_json::to_value(&first).unwrap(), serde_json::to_value(&second).unwrap());
+        assert_eq!(first.to_markdown(), second.to_markdown());
+
+        let arrivals = |seed| -> Vec<(u64, u64)> {
+            let exercise = generate(&self::spec(seed, Difficulty::Medium)).unwrap();
+            exercise.processes.iter().map(|p| (p.arrival, p.burst)).collect()
+        };
+        assert!((1..5).any(|seed| arrivals(seed) != arrivals(2024)));
+    }
+
+    #[test]
+    fn workloads_have_the_shape_of_their_difficulty() {
+        for (difficulty, count) in [
+            (Difficulty::Easy, 4),
+            (Difficulty::Medium, 5),
+            (Difficulty::Hard, 6),
+        ] {
+            let exercise = generate(&spec(7, difficulty)).unwrap();
+            let (_, latest_arrival, longest_burst) = difficulty.shape();
+            assert_eq!(exercise.processes.len(), count);
+            assert_eq!(exercise.processes[0].arrival, 0);
+            assert!(exercise.processes.windows(2).all(|p| p[0].arrival <= p[1].arrival));
+            assert!(exercise.processes.iter().all(|p| p.arrival <= latest_arrival));
+            assert!(exercise.processes.iter().all(|p| (1..=longest_burst).contains(&p.burst)));
+            assert_eq!(exercise.answers.len(), DEFAULT_ALGORITHMS.len());
+        }
+    }
+
+    #[test]
+    fn answers_add_up() {
+        let exercise = generate(&spec(3, Difficulty::Hard)).unwrap();
+        for answer in &exercise.answers {
+            let waiting: f64 = answer.processes.iter().map(|p| p.waiting).sum();
+            let average = waiting / answer.processes.len() as f64;
+            assert!((average - answer.average_waiting_time).abs() < 1e-6, "{}", answer.algorithm);
+            for (process, given) in answer.processes.iter().zip(&exercise.processes) {
+                let ran: f64 = answer
+                    .schedule
+                    .iter()
+                    .filter(|s| s.process == given.name)
+                    .map(|s| s.end - s.start)
+                    .sum();
+                assert!((ran - given.burst as f64).abs() < 1e-6);
+                assert!((process.turnaround - process.waiting - given.burst as f64).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn an_answer_key_grades_as_correct() {
+        let exercise = generate(&spec(11, Difficulty::Easy)).unwrap();
+        let answer = exercise.answers.iter().find(|a| a.algorithm == "SRTF").unwrap();
+        let homework: Homework = serde_json::from_value(json!({
+            "config": {
+                "queue": "SRTF",
+                "time_quantum": answer.time_quantum,
+                "context_switch": exercise.context_switch,
+            },
+            "array_of_processes": exercise
+                .processes
+                .iter()
+                .map(|p| (p.arrival, p.burst, None::<String>))
+                .collect::<Vec<_>>(),
+            "schedule": answer.schedule,
+            "metrics": { "average_waiting_time": answer.average_waiting_time },
+        }))
+        .unwrap();
+        assert!(grading::grade(&homework).unwrap().correct);
+    }
+
+    #[test]
+    fn properties_are_shown() {
+        let spec = ExerciseSpec {
+            properties: vec![Property::Preemption, Property::Idle],
+            ..spec(5, Difficulty::Hard)
+        };
+        let exercise = generate(&spec).unwrap();
+        let srtf = exercise.answers.iter().find(|a| a.algorithm == "SRTF").unwrap();
+        assert!(srtf.schedule.len() > exercise.processes.len());
+        let fcfs = &exercise.answers[0].schedule;
+        assert!(fcfs.windows(2).any(|pair| pair[1].start > pair[0].end));
+    }
+
+    #[test]
+    fn bad_specs_are_rejected() {
+        let mlfq = ExerciseSpec {
+            algorithms: vec![String::from("MLFQ")],
+            ..ExerciseSpec::default()
+        };
+        assert!(generate(&mlfq).is_err());
+        let no_quantum = ExerciseSpec {
+            time_quantum: 0,
+            ..ExerciseSpec::default()
+        };
+        assert_eq!(generate(&no_quantum).unwrap_err(), "RR needs a time quantum");
+        let preemption = ExerciseSpec {
+            algorithms: vec![String::from("FCFS")],
+            properties: vec![Property::Preemption],
+            ..ExerciseSpec::default()
+        };
+        assert!(generate(&preemption).is_err());
+    }
+}
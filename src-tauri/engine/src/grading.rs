@@ -0,0 +1,670 @@
+use crate::config::RunConfig;
+use crate::error::{FieldError, SimError};
+use crate::queue_engine::Queues;
+use crate::report;
+use crate::scenario::{ProcessSource, Scenario};
+use crate::simulator::RunResult;
+use crate::util::{algorithm_name, millis, number, rounded};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Utils -------------------------------------------------------------------------------------------
+
+// times closer than this, in ms, are the same time
+const EPSILON: f64 = 1e-6;
+
+fn close(a: f64, b: f64, tolerance: f64) -> bool {
+    (a - b).abs() <= tolerance + EPSILON
+}
+
+/// The processes are named by their row, P1 is the first one.
+fn process_name(index: usize) -> String {
+    format!("P{}", index + 1)
+}
+
+/// One bar of a Gantt chart: `process` had the CPU from `start` to `end`, in ms.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    pub process: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Joins bars of the same process that touch, the way a chart drawn by hand shows them.
+fn merge(mut segments: Vec<Segment>) -> Vec<Segment> {
+    segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+    let mut merged: Vec<Segment> = Vec::with_capacity(segments.len());
+    for segment in segments {
+        match merged.last_mut() {
+            Some(last) if last.process == segment.process && close(last.end, segment.start, 0.0) => {
+                last.end = segment.end
+            }
+            _ => merged.push(segment),
+        }
+    }
+    merged
+}
+
+/// The CPU lane of the run's Gantt chart, bars of the same process that touch joined.
+pub(crate) fn schedule(result: &RunResult) -> Vec<Segment> {
+    merge(
+        report::bars_from_log(&result.log)
+            .into_iter()
+            .filter(|bar| bar.lane == "CPU")
+            .map(|bar| Segment {
+                process: bar.label,
+                start: millis(bar.start),
+                end: millis(bar.end),
+            })
+            .collect(),
+    )
+}
+
+// Homework ----------------------------------------------------------------------------------------
+
+/// A student's answer to "draw the Gantt chart and compute the averages". The processes are
+/// given like `run_with_parameters` takes them, (arrival, burst, process type) in ms, and the
+/// schedule calls them P1, P2, ... in that order.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Homework {
+    pub config: RunConfig,
+    pub array_of_processes: Vec<(u64, u64, Option<String>)>,
+    pub schedule: Vec<Segment>,
+    #[serde(default)]
+    pub metrics: BTreeMap<String, f64>, // e.g. average_waiting_time, durations in ms
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64, // how far off, in ms, a time or a metric can be and still count
+}
+
+fn default_tolerance() -> f64 {
+    0.01
+}
+
+impl Homework {
+    /// The homework's run, or everything wrong with the homework.
+    pub fn validate(&self) -> Result<Scenario, Vec<FieldError>> {
+        let (scenario, mut errors) = match self.config.validate_manual(&self.array_of_processes) {
+            Ok(scenario) => (Some(scenario), Vec::new()),
+            Err(errors) => (None, errors),
+        };
+        for (index, segment) in self.schedule.iter().enumerate() {
+            if self.process_index(&segment.process).is_none() {
+                errors.push(FieldError::new(
+                    format!("schedule[{}].process", index),
+                    format!(
+                        "there's no process \"{}\", they're P1 to P{}",
+                        segment.process,
+                        self.array_of_processes.len()
+                    ),
+                ));
+            }
+            if !segment.start.is_finite() || !segment.end.is_finite() || segment.start < 0.0 {
+                errors.push(FieldError::new(
+                    format!("schedule[{}]", index),
+                    "start and end must be times from 0 on",
+                ));
+            } else if segment.end <= segment.start {
+                errors.push(FieldError::new(
+                    format!("schedule[{}].end", index),
+                    "must be after the start",
+                ));
+            }
+        }
+        if !self.tolerance.is_finite() || self.tolerance < 0.0 {
+            errors.push(FieldError::new("tolerance", "must be zero or more"));
+        }
+        match scenario {
+            Some(scenario) if errors.is_empty() => Ok(scenario),
+            _ => Err(errors),
+        }
+    }
+
+    fn process_index(&self, name: &str) -> Option<usize> {
+        let name = name.trim().to_uppercase();
+        let number: usize = name.strip_prefix('P')?.parse().ok()?;
+        (1..=self.array_of_processes.len()).contains(&number).then(|| number - 1)
+    }
+}
+
+// Grade -------------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Correct,
+    WrongProcess,
+    WrongTimes, // the right process, starting or ending at the wrong time
+    Missing,    // the submission ends before the schedule does
+    Extra,      // the submission goes on after the schedule ends
+}
+
+/// The `index`th bar of both charts, after bars of the same process that touch are joined.
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentDiff {
+    pub index: usize,
+    pub expected: Option<Segment>,
+    pub submitted: Option<Segment>,
+    pub verdict: Verdict,
+}
+
+/// Where the submission first goes wrong, and what the algorithm does there instead.
+#[derive(Debug, Clone, Serialize)]
+pub struct Divergence {
+    pub index: usize,
+    pub time: f64, // ms
+    pub explanation: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricGrade {
+    pub metric: String,
+    pub expected: f64,
+    pub submitted: f64,
+    pub error: f64,                  // submitted minus expected
+    pub relative_error: Option<f64>, // none when the expected value is zero
+    pub correct: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Grade {
+    pub algorithm: String,
+    pub expected: Vec<Segment>, // the simulator's schedule, bars joined like the submission's
+    pub segments: Vec<SegmentDiff>,
+    pub divergence: Option<Divergence>,
+    pub metrics: Vec<MetricGrade>,
+    pub correct: bool, // every segment and every metric
+}
+
+/// Runs the homework's processes the way `run_with_parameters` would, seeded so the answer is
+/// the same every time, and checks the student's schedule and metrics against it.
+pub fn grade(homework: &Homework) -> Result<Grade, SimError> {
+    let mut scenario = homework.validate()?;
+    if let ProcessSource::Workload(workload) = &mut scenario.source {
+        for (index, entry) in workload.iter_mut().enumerate() {
+            entry.name = Some(process_name(index));
+        }
+    }
+    scenario.seed = Some(0); // processes without a type get the same random one every time
+    let result = scenario.run()?;
+
+    let mut fields = Vec::new();
+    let mut metrics = Vec::with_capacity(homework.metrics.len());
+    for (metric, submitted) in &homework.metrics {
+        let Some(expected) = result.metrics.get(metric).and_then(|m| m.as_f64()) else {
+            fields.push(FieldError::new(format!("metrics.{}", metric), "not a metric of the run"));
+            continue;
+        };
+        let error = submitted - expected;
+        metrics.push(MetricGrade {
+            metric: metric.clone(),
+            expected,
+            submitted: *submitted,
+            error,
+            relative_error: (expected != 0.0).then(|| error / expected),
+            correct: close(*submitted, expected, homework.tolerance),
+        });
+    }
+    SimError::check(fields)?;
+
+    let expected = schedule(&result);
+    let submitted = merge(
+        homework
+            .schedule
+            .iter()
+            .map(|segment| Segment {
+                process: homework.process_index(&segment.process).map(process_name).unwrap_or_default(),
+                ..segment.clone()
+            })
+            .collect(),
+    );
+
+    let tolerance = homework.tolerance;
+    let segments: Vec<SegmentDiff> = (0..expected.len().max(submitted.len()))
+        .map(|index| {
+            let (e, s) = (expected.get(index), submitted.get(index));
+            let verdict = match (e, s) {
+                (Some(e), Some(s)) if e.process != s.process => Verdict::WrongProcess,
+                (Some(e), Some(s)) if close(e.start, s.start, tolerance) && close(e.end, s.end, tolerance) => {
+                    Verdict::Correct
+                }
+                (Some(_), Some(_)) => Verdict::WrongTimes,
+                (Some(_), None) => Verdict::Missing,
+                (None, _) => Verdict::Extra,
+            };
+            SegmentDiff {
+                index,
+                expected: e.cloned(),
+                submitted: s.cloned(),
+                verdict,
+            }
+        })
+        .collect();
+
+    let explainer = Explainer::new(
+        homework.config.queue,
+        homework.config.time_quantum,
+        homework.config.context_switch,
+        homework.array_of_processes.iter().map(|(arrival, burst, _)| (*arrival, *burst)),
+        &expected,
+    );
+    let divergence = segments
+        .iter()
+        .find(|diff| diff.verdict != Verdict::Correct)
+        .map(|diff| explainer.explain(diff, &submitted));
+    let correct = divergence.is_none() && metrics.iter().all(|m| m.correct);
+    Ok(Grade {
+        algorithm: result.queue,
+        expected,
+        segments,
+        divergence,
+        metrics,
+        correct,
+    })
+}
+
+// Explanations ------------------------------------------------------------------------------------
+
+struct Job {
+    name: String,
+    arrival: f64,
+    burst: f64,
+}
+
+/// What's known about the exercise at any time of the expected schedule, to explain it with.
+pub(crate) struct Explainer<'a> {
+    queue: Queues,
+    quantum: f64,
+    context_switch: f64,
+    jobs: Vec<Job>,
+    schedule: &'a [Segment],
+}
+
+impl<'a> Explainer<'a> {
+    /// `queue`'s `schedule` of the (arrival, burst) `processes`, times in ms.
+    pub(crate) fn new(
+        queue: Queues,
+        quantum: u64,
+        context_switch: u64,
+        processes: impl IntoIterator<Item = (u64, u64)>,
+        schedule: &'a [Segment],
+    ) -> Self {
+        let jobs = processes
+            .into_iter()
+            .enumerate()
+            .map(|(index, (arrival, burst))| Job {
+                name: process_name(index),
+                arrival: arrival as f64,
+                burst: burst as f64,
+            })
+            .collect();
+        Explainer {
+            queue,
+            quantum: quantum as f64,
+            context_switch: context_switch as f64,
+            jobs,
+            schedule,
+        }
+    }
+
+    /// Whether the process that starts the `index`th bar shares its key with another ready
+    /// process, so the algorithm has to break a tie there.
+    pub(crate) fn tied_at(&self, index: usize) -> bool {
+        let Some(segment) = self.schedule.get(index) else {
+            return false;
+        };
+        let Some((_, picked, _)) = self.job(&segment.process).and_then(|job| self.key(job, segment.start)) else {
+            return false;
+        };
+        self.ready(segment.start)
+            .into_iter()
+            .filter(|job| job.name != segment.process)
+            .filter_map(|job| self.key(job, segment.start))
+            .any(|(_, key, _)| close(key, picked, 0.0))
+    }
+
+    fn job(&self, name: &str) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.name == name)
+    }
+
+    /// CPU time `job` got before `time`.
+    fn served(&self, job: &Job, time: f64) -> f64 {
+        self.schedule
+            .iter()
+            .filter(|s| s.process == job.name && s.start < time)
+            .map(|s| s.end.min(time) - s.start)
+            .sum()
+    }
+
+    fn remaining(&self, job: &Job, time: f64) -> f64 {
+        job.burst - self.served(job, time)
+    }
+
+    fn finished_at(&self, job: &Job) -> f64 {
+        self.schedule.iter().filter(|s| s.process == job.name).map(|s| s.end).fold(0.0, f64::max)
+    }
+
+    /// The processes that arrived by `time` and aren't done.
+    fn ready(&self, time: f64) -> Vec<&Job> {
+        self.jobs
+            .iter()
+            .filter(|job| job.arrival <= time + EPSILON && self.remaining(job, time) > EPSILON)
+            .collect()
+    }
+
+    /// What the algorithm orders the ready processes by, the value and whether lower wins.
+    fn key(&self, job: &Job, time: f64) -> Option<(&'static str, f64, bool)> {
+        match self.queue {
+            Queues::FIFO | Queues::FCFS => Some(("arrival", job.arrival, true)),
+            Queues::SPN | Queues::SJF => Some(("burst", job.burst, true)),
+            Queues::SRF => Some(("remaining time", self.remaining(job, time), true)),
+            Queues::HRRN => {
+                let waiting = time - job.arrival - self.served(job, time);
+                Some(("response ratio", (waiting + job.burst) / job.burst, false))
+            }
+            _ => None,
+        }
+    }
+
+    /// The ready processes with their keys, e.g. "P2 (burst 4), P3 (burst 1)".
+    fn keyed(&self, jobs: &[&Job], time: f64) -> String {
+        let keyed: Vec<String> = jobs
+            .iter()
+            .map(|job| match self.key(job, time) {
+                Some((key, value, _)) => format!("{} ({} {})", job.name, key, number(value)),
+                None => job.name.clone(),
+            })
+            .collect();
+        keyed.join(", ")
+    }
+
+    /// Why the algorithm gives the CPU to `name` at `time`.
+    fn why_picked(&self, name: &str, time: f64) -> String {
+        let ready = self.ready(time);
+        let Some(job) = self.job(name) else {
+            return String::new();
+        };
+        match self.key(job, time) {
+            Some((key, _, lower)) => format!(
+                "Of the ready processes {}, {} takes the one with the {} {}.",
+                self.keyed(&ready, time),
+                algorithm_name(self.queue),
+                if lower { "lowest" } else { "highest" },
+                key
+            ),
+            None => match self.queue {
+                Queues::RR => format!(
+                    "Round robin gives the ready processes {} the CPU in turn, for at most {} ms \
+                     each, and a preempted process goes to the back of the queue.",
+                    self.keyed(&ready, time),
+                    number(self.quantum)
+                ),
+                Queues::MLQ => String::from(
+                    "MLQ runs the highest level that has a ready process, each process stays in \
+                     the level of its type.",
+                ),
+                Queues::MLFQ => format!(
+                    "MLFQ runs the highest level that has a ready process, and a process that \
+                     uses up its {} ms quantum drops a level.",
+                    number(self.quantum)
+                ),
+                _ => format!("{}'s policy picks it.", algorithm_name(self.queue)),
+            },
+        }
+    }
+
+    fn explain(&self, diff: &SegmentDiff, submitted: &[Segment]) -> Divergence {
+        let (time, explanation) = match (&diff.expected, &diff.submitted) {
+            (Some(e), Some(s)) if diff.verdict == Verdict::WrongProcess => {
+                (e.start, self.wrong_process(e, s))
+            }
+            (Some(e), Some(s)) if !close(e.start, s.start, 0.0) => {
+                (e.start.min(s.start), self.wrong_start(diff.index, e, s))
+            }
+            (Some(e), Some(s)) => (e.end.min(s.end), self.wrong_end(diff.index, e, s)),
+            (Some(e), None) => {
+                let last = submitted.last().map_or(0.0, |s| s.end);
+                let text = format!(
+                    "The submission ends at {} ms, but {} still runs from {} to {} ms.",
+                    number(last),
+                    e.process,
+                    number(e.start),
+                    number(e.end)
+                );
+                (e.start, text)
+            }
+            (None, Some(s)) => {
+                let last = self.schedule.last().map_or(0.0, |e| e.end);
+                let text = format!(
+                    "Every process is done at {} ms, so nothing runs from {} to {} ms.",
+                    number(last),
+                    number(s.start),
+                    number(s.end)
+                );
+                (s.start, text)
+            }
+            (None, None) => (0.0, String::new()),
+        };
+        Divergence {
+            index: diff.index,
+            time: rounded(time),
+            explanation,
+        }
+    }
+
+    fn wrong_process(&self, expected: &Segment, submitted: &Segment) -> String {
+        let time = expected.start;
+        let mut text = format!(
+            "At {} ms {} runs {}, not {}. ",
+            number(time),
+            algorithm_name(self.queue),
+            expected.process,
+            submitted.process
+        );
+        match self.job(&submitted.process) {
+            Some(job) if job.arrival > time + EPSILON => {
+                text += &format!("{} only arrives at {} ms.", job.name, number(job.arrival))
+            }
+            Some(job) if self.remaining(job, time) <= EPSILON => {
+                text += &format!("{} is already done at {} ms.", job.name, number(self.finished_at(job)))
+            }
+            Some(job) => {
+                text += &self.why_picked(&expected.process, time);
+                let tied = self.job(&expected.process).and_then(|e| self.key(e, time)).zip(self.key(job, time));
+                if tied.is_some_and(|((_, a, _), (_, b, _))| close(a, b, 0.0)) {
+                    text += &format!(
+                        " {} and {} tie, and the one ahead in the queue goes first.",
+                        expected.process, job.name
+                    );
+                }
+            }
+            None => {}
+        }
+        text
+    }
+
+    fn wrong_start(&self, index: usize, expected: &Segment, submitted: &Segment) -> String {
+        let mut text = format!(
+            "{} starts at {} ms, not {}. ",
+            expected.process,
+            number(expected.start),
+            number(submitted.start)
+        );
+        let previous = index.checked_sub(1).and_then(|i| self.schedule.get(i));
+        let job = self.job(&expected.process);
+        if expected.start > submitted.start {
+            match (job, previous) {
+                (Some(job), _) if job.arrival > submitted.start + EPSILON => {
+                    text += &format!("It arrives at {} ms.", number(job.arrival))
+                }
+                (_, Some(previous)) if self.context_switch > 0.0 => {
+                    text += &format!(
+                        "The CPU is busy with {} until {} ms, then switches context for {} ms.",
+                        previous.process,
+                        number(previous.end),
+                        number(self.context_switch)
+                    )
+                }
+                (_, Some(previous)) => {
+                    text += &format!("The CPU is busy with {} until then.", previous.process)
+                }
+                _ => {}
+            }
+        } else {
+            text += "The CPU takes the next process as soon as it's free";
+            if self.context_switch > 0.0 && previous.is_some() {
+                text += &format!(", after a context switch of {} ms", number(self.context_switch));
+            }
+            text += ".";
+        }
+        text
+    }
+
+    fn wrong_end(&self, index: usize, expected: &Segment, submitted: &Segment) -> String {
+        let mut text = format!(
+            "{} runs until {} ms, not {}. ",
+            expected.process,
+            number(expected.end),
+            number(submitted.end)
+        );
+        let Some(job) = self.job(&expected.process) else {
+            return text;
+        };
+        if expected.end > submitted.end {
+            text += &self.why_kept(job, submitted.end);
+        } else if close(self.remaining(job, expected.start), expected.end - expected.start, 0.0) {
+            text += &format!("That's when its {} ms burst is done.", number(job.burst));
+        } else if let Some(next) = self.schedule.get(index + 1) {
+            text += &format!(
+                "It's preempted at the end of a {} ms quantum and {} goes next. ",
+                number(self.quantum),
+                next.process
+            );
+            text += &self.why_picked(&next.process, expected.end);
+        }
+        text
+    }
+
+    /// Why `job` still has the CPU at `time`.
+    fn why_kept(&self, job: &Job, time: f64) -> String {
+        let others: Vec<&Job> =
+            self.ready(time).into_iter().filter(|other| other.name != job.name).collect();
+        if others.is_empty() {
+            return format!("No other process is ready at {} ms.", number(time));
+        }
+        match self.queue {
+            Queues::FIFO | Queues::FCFS | Queues::SPN | Queues::HRRN => format!(
+                "{} doesn't preempt, a process keeps the CPU until its burst is done.",
+                algorithm_name(self.queue)
+            ),
+            Queues::SJF | Queues::SRF => {
+                let mut ready = others;
+                ready.push(job);
+                let key = self.key(job, time).map_or("", |(key, _, _)| key);
+                format!(
+                    "At {} ms it still has the lowest {} of the ready processes {}.",
+                    number(time),
+                    key,
+                    self.keyed(&ready, time)
+                )
+            }
+            Queues::RR if others.iter().all(|other| close(other.arrival, time, 0.0)) => {
+                let names: Vec<&str> = others.iter().map(|other| other.name.as_str()).collect();
+                format!(
+                    "{} arrives as its quantum ends and joins the queue behind it.",
+                    names.join(" and ")
+                )
+            }
+            _ => format!("{} keeps it on the CPU at {} ms.", algorithm_name(self.queue), number(time)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // P1 runs until the shorter P2 arrives, P2 until the even shorter P3 does, then they finish
+    // shortest first
+    const SRTF: &str = r#"{
+        "config": { "queue": "SRTF", "time_quantum": 1, "context_switch": 0 },
+        "array_of_processes": [[0, 8, null], [1, 4, null], [2, 2, null]],
+        "schedule": [
+            { "process": "P1", "start": 0, "end": 1 },
+            { "process": "P2", "start": 1, "end": 2 },
+            { "process": "P3", "start": 2, "end": 4 },
+            { "process": "P2", "start": 4, "end": 7 },
+            { "process": "P1", "start": 7, "end": 14 }
+        ],
+        "metrics": { "average_waiting_time": 2.667, "average_turnaround_time": 7.333 }
+    }"#;
+
+    fn homework() -> Homework {
+        serde_json::from_str(SRTF).unwrap()
+    }
+
+    fn segment(process: &str, start: f64, end: f64) -> Segment {
+        Segment {
+            process: process.to_string(),
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn a_correct_srtf_homework() {
+        let grade = grade(&homework()).unwrap();
+        assert!(grade.correct, "{:?}", grade.segments);
+        assert_eq!(grade.expected.len(), 5);
+        assert!(grade.segments.iter().all(|s| s.verdict == Verdict::Correct));
+        assert!(grade.metrics.iter().all(|m| m.correct));
+        assert!(grade.divergence.is_none());
+    }
+
+    #[test]
+    fn an_srtf_homework_drawn_without_preemption() {
+        let mut homework = homework();
+        homework.schedule = vec![
+            segment("P1", 0.0, 8.0),
+            segment("P3", 8.0, 10.0),
+            segment("P2", 10.0, 14.0),
+        ];
+        homework.metrics.insert(String::from("average_waiting_time"), 5.333);
+        let grade = grade(&homework).unwrap();
+        assert!(!grade.correct);
+        let verdicts: Vec<Verdict> = grade.segments.iter().map(|s| s.verdict).collect();
+        assert_eq!(
+            verdicts,
+            [
+                Verdict::WrongTimes,
+                Verdict::WrongProcess,
+                Verdict::WrongProcess,
+                Verdict::Missing,
+                Verdict::Missing
+            ]
+        );
+        let divergence = grade.divergence.unwrap();
+        assert_eq!((divergence.index, divergence.time), (0, 1.0));
+        let waiting = &grade.metrics[1];
+        assert_eq!(waiting.metric, "average_waiting_time");
+        assert!(!waiting.correct && waiting.error > 2.0);
+    }
+
+    #[test]
+    fn touching_bars_of_a_process_are_joined() {
+        let mut homework = homework();
+        homework.schedule.insert(3, segment("p2", 4.0, 5.0));
+        homework.schedule[4].start = 5.0;
+        assert!(grade(&homework).unwrap().correct);
+    }
+
+    #[test]
+    fn unknown_processes_and_metrics_are_rejected() {
+        let mut homework = homework();
+        homework.schedule[0].process = String::from("P4");
+        assert!(matches!(grade(&homework), Err(SimError::InvalidConfig { .. })));
+
+        let mut homework = self::homework();
+        homework.metrics.insert(String::from("happiness"), 1.0);
+        assert!(matches!(grade(&homework), Err(SimError::InvalidConfig { .. })));
+    }
+}
@@ -0,0 +1,66 @@
+//! The scheduling engine of Mehrmux, without the app around it.
+//!
+//! A [`Scenario`] is one run: the algorithm, its parameters and where the processes come from.
+//! [`Scenario::run`] runs it headless and returns a [`RunResult`] with the metrics, the finished
+//! processes and the run's [`EventLog`]. To follow a run while it happens instead, start a
+//! [`Session`] with an [`EventSink`] and [`Scenario::run_in`] it, every event the desktop app
+//! draws from is sent to the sink.
+//!
+//! ```no_run
+//! use mehrmux_engine::{ProcessSource, Queues, Scenario};
+//! use std::time::Duration;
+//!
+//! let source = ProcessSource::Generated {
+//!     arrival_lambda: 1.0,
+//!     burst_lambda: 2.0,
+//!     count: 20,
+//! };
+//! let mut scenario = Scenario::new(Queues::RR, source);
+//! scenario.time_quantum = Duration::from_millis(20);
+//! scenario.seed = Some(7);
+//! let result = scenario.run().unwrap();
+//! println!("{:?}", result.metrics.get("average_waiting_time"));
+//! ```
+//!
+//! Comparisons and parameter sweeps are in [`compare`] and [`sweep`], reading workloads from
+//! files in [`workload`], [`swf`] and [`sched_trace`]. Policies of your own run as the `SCRIPT`
+//! discipline, written in Rhai ([`policy`]), or the `PLUGIN` one, compiled to WebAssembly
+//! ([`plugin`], with the `plugins` feature). For teaching, [`exercise`] writes problems with
+//! their answer keys and [`grading`] checks a student's answers.
+
+pub mod analytic;
+pub mod clock;
+pub mod compare;
+pub mod config;
+pub mod decision;
+pub mod error;
+pub mod events;
+pub mod exercise;
+pub mod grading;
+pub mod plugin;
+pub mod policy;
+pub mod process_gen;
+pub mod queue_engine;
+pub mod report;
+pub mod sampling;
+pub mod scenario;
+pub mod sched_trace;
+pub mod session;
+pub mod simulator;
+pub mod starvation;
+pub mod stats;
+pub mod sweep;
+pub mod swf;
+pub(crate) mod util;
+pub mod workload;
+
+pub use config::{GeneratorConfig, RunConfig};
+pub use error::{FieldError, SimError};
+pub use events::{EventLog, EventSink, SimEvent};
+pub use plugin::PluginPolicy;
+pub use policy::ScriptPolicy;
+pub use queue_engine::{QueueDiscipline, Queues};
+pub use scenario::{ProcessSource, Scenario};
+pub use session::{PlaybackSpeed, Session, SessionEvent, SessionId, SessionStatus, Sessions};
+pub use simulator::{RunOptions, RunResult};
+pub use workload::WorkloadEntry;
@@ -0,0 +1,397 @@
+use crate::error::SimError;
+use crate::process_gen::Process;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+#[cfg(feature = "plugins")]
+use crate::process_gen::ProcessType;
+#[cfg(feature = "plugins")]
+use crate::util::{millis, millis_since};
+#[cfg(feature = "plugins")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "plugins")]
+use uuid::Uuid;
+#[cfg(feature = "plugins")]
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder, Trap, TypedFunc};
+
+// Utils -------------------------------------------------------------------------------------------
+
+// a call gets this much fuel, about one unit per instruction, a loop that never ends runs out
+#[cfg(feature = "plugins")]
+const FUEL_PER_CALL: u64 = 10_000_000;
+
+// the most memory an instance can grow to
+#[cfg(feature = "plugins")]
+const MAX_MEMORY: usize = 16 << 20;
+
+#[cfg(feature = "plugins")]
+fn type_code(process_type: ProcessType) -> i32 {
+    match process_type {
+        ProcessType::SystemProcess => 0,
+        ProcessType::InteractiveProcess => 1,
+        ProcessType::BatchProcess => 2,
+        ProcessType::StudentProcess => 3,
+    }
+}
+
+// Plugin Policy -----------------------------------------------------------------------------------
+
+/// A scheduling policy compiled to WebAssembly, run as the `PLUGIN` discipline. The module
+/// imports nothing and exports
+///
+/// ```text
+/// // `process` joined the queue. Times are in ms since the start of the run, `priority` is -1
+/// // when the process has none, `process_type` is 0 system, 1 interactive, 2 batch, 3 student
+/// on_arrival(process: i32, arrival: f64, burst: f64, remaining: f64, priority: i32, process_type: i32)
+///
+/// // the process to run next, one `on_arrival` told about, it leaves the queue
+/// pick_next(now: f64) -> i32
+///
+/// // optional: `process` is about to run, non-zero preempts it after a time quantum, zero runs
+/// // it until it finishes or blocks on I/O. Without it every process is preempted.
+/// on_tick(process: i32, now: f64) -> i32
+/// ```
+///
+/// A process keeps its number for the whole run, and comes back through `on_arrival` every
+/// time it rejoins the queue. Every queue gets an instance of its own, and one that runs out of
+/// fuel is stopped.
+#[cfg(feature = "plugins")]
+pub struct PluginPolicy {
+    pub name: String, // the file stem, for errors
+    engine: Engine,
+    module: Module,
+}
+
+#[cfg(feature = "plugins")]
+impl PluginPolicy {
+    /// Loads a `.wasm` module, or a `.wat` one in the text format.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+        let name = path.file_stem().unwrap_or(path.as_os_str()).to_string_lossy();
+        PluginPolicy::from_bytes(name, &bytes)
+    }
+
+    /// Compiles `bytes` and checks the exports once, so a broken plugin fails before any run.
+    pub fn from_bytes(name: impl Into<String>, bytes: &[u8]) -> Result<Self, String> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| e.to_string())?;
+        let module = Module::new(&engine, bytes).map_err(|e| e.to_string())?;
+        if let Some(import) = module.imports().next() {
+            return Err(format!(
+                "a plugin can't import anything, this one imports {}::{}",
+                import.module(),
+                import.name()
+            ));
+        }
+        let policy = PluginPolicy {
+            name: name.into(),
+            engine,
+            module,
+        };
+        policy.exports()?;
+        Ok(policy)
+    }
+
+    fn exports(&self) -> Result<Exports, String> {
+        let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(FUEL_PER_CALL).map_err(|e| e.to_string())?;
+        let instance = Instance::new(&mut store, &self.module, &[]).map_err(|e| e.to_string())?;
+        let on_arrival = instance
+            .get_typed_func(&mut store, "on_arrival")
+            .map_err(|e| format!("on_arrival: {}", e))?;
+        let pick_next = instance
+            .get_typed_func(&mut store, "pick_next")
+            .map_err(|e| format!("pick_next: {}", e))?;
+        let on_tick = match instance.get_func(&mut store, "on_tick") {
+            Some(f) => Some(f.typed(&store).map_err(|e| format!("on_tick: {}", e))?),
+            None => None,
+        };
+        Ok(Exports {
+            store,
+            on_arrival,
+            pick_next,
+            on_tick,
+        })
+    }
+
+    /// A fresh instance for one queue.
+    pub fn instantiate(self: &Arc<Self>) -> Result<PluginInstance, SimError> {
+        Ok(PluginInstance {
+            exports: self.exports().map_err(|message| self.error("instantiate", message))?,
+            policy: self.clone(),
+            handles: HashMap::new(),
+            announced: HashSet::new(),
+        })
+    }
+
+    fn error(&self, function: &str, message: String) -> SimError {
+        SimError::Plugin {
+            plugin: self.name.clone(),
+            function: function.to_string(),
+            message,
+        }
+    }
+}
+
+impl fmt::Debug for PluginPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginPolicy").field("name", &self.name).finish()
+    }
+}
+
+// Plugin Instance ---------------------------------------------------------------------------------
+
+#[cfg(feature = "plugins")]
+struct Exports {
+    store: Store<StoreLimits>,
+    on_arrival: TypedFunc<(i32, f64, f64, f64, i32, i32), ()>,
+    pick_next: TypedFunc<f64, i32>,
+    on_tick: Option<TypedFunc<(i32, f64), i32>>,
+}
+
+/// A plugin running one queue, with the state it keeps between calls.
+#[cfg(feature = "plugins")]
+pub struct PluginInstance {
+    policy: Arc<PluginPolicy>,
+    exports: Exports,
+    handles: HashMap<Uuid, i32>, // the number each process goes by in the plugin
+    announced: HashSet<Uuid>,    // what the plugin was told is in the queue
+}
+
+#[cfg(feature = "plugins")]
+impl PluginInstance {
+    fn handle(&mut self, id: Uuid) -> i32 {
+        let next = self.handles.len() as i32;
+        *self.handles.entry(id).or_insert(next)
+    }
+
+    fn error(&self, function: &str, error: wasmtime::Error) -> SimError {
+        let message = match error.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => format!("ran out of fuel after {} instructions", FUEL_PER_CALL),
+            Some(trap) => trap.to_string(),
+            None => error.to_string(),
+        };
+        self.policy.error(function, message)
+    }
+
+    fn refuel(&mut self, function: &str) -> Result<(), SimError> {
+        self.exports.store.set_fuel(FUEL_PER_CALL).map_err(|e| self.error(function, e))
+    }
+
+    /// Which of `ready` runs next.
+    pub fn pick_next(
+        &mut self,
+        ready: &[Process],
+        start: SystemTime,
+        now: SystemTime,
+    ) -> Result<usize, SimError> {
+        if self.announced.iter().any(|id| !ready.iter().any(|p| p.id == *id)) {
+            // something left the queue without being picked, MLFQ's priority boost took it,
+            // so the plugin starts over and is told about the queue again
+            self.exports = self.policy.exports().map_err(|m| self.policy.error("instantiate", m))?;
+            self.announced.clear();
+        }
+        for process in ready {
+            if !self.announced.insert(process.id) {
+                continue;
+            }
+            let args = (
+                self.handle(process.id),
+                millis_since(process.arrival_time, start),
+                millis(process.cpu_burst_time),
+                millis(process.cpu_burst_time.saturating_sub(process.processed_time)),
+                process.priority.map_or(-1, i32::from),
+                type_code(process.process_type),
+            );
+            self.refuel("on_arrival")?;
+            let on_arrival = self.exports.on_arrival.clone();
+            on_arrival
+                .call(&mut self.exports.store, args)
+                .map_err(|e| self.error("on_arrival", e))?;
+        }
+
+        self.refuel("pick_next")?;
+        let pick_next = self.exports.pick_next.clone();
+        let picked = pick_next
+            .call(&mut self.exports.store, millis_since(now, start))
+            .map_err(|e| self.error("pick_next", e))?;
+        let index = ready
+            .iter()
+            .position(|p| self.announced.contains(&p.id) && self.handles.get(&p.id) == Some(&picked))
+            .ok_or(self.policy.error(
+                "pick_next",
+                format!("returned {}, which isn't a process in the queue", picked),
+            ))?;
+        self.announced.remove(&ready[index].id);
+        Ok(index)
+    }
+
+    /// Whether `process` goes back to the queue after a time quantum.
+    pub fn on_tick(
+        &mut self,
+        process: &Process,
+        start: SystemTime,
+        now: SystemTime,
+    ) -> Result<bool, SimError> {
+        let Some(on_tick) = self.exports.on_tick.clone() else {
+            return Ok(true);
+        };
+        let handle = self.handle(process.id);
+        self.refuel("on_tick")?;
+        let preempt = on_tick
+            .call(&mut self.exports.store, (handle, millis_since(now, start)))
+            .map_err(|e| self.error("on_tick", e))?;
+        Ok(preempt != 0)
+    }
+}
+
+// Without Plugins ---------------------------------------------------------------------------------
+
+// the engine built without the `plugins` feature has no WebAssembly runtime, nothing loads and
+// nothing of these can exist
+
+#[cfg(not(feature = "plugins"))]
+pub struct PluginPolicy {
+    pub name: String,
+    never: std::convert::Infallible,
+}
+
+#[cfg(not(feature = "plugins"))]
+impl PluginPolicy {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        PluginPolicy::from_bytes(path.display().to_string(), &[])
+    }
+
+    pub fn from_bytes(_name: impl Into<String>, _bytes: &[u8]) -> Result<Self, String> {
+        Err(String::from("the engine was built without the `plugins` feature"))
+    }
+
+    pub fn instantiate(self: &Arc<Self>) -> Result<PluginInstance, SimError> {
+        match self.never {}
+    }
+}
+
+#[cfg(not(feature = "plugins"))]
+pub struct PluginInstance {
+    never: std::convert::Infallible,
+}
+
+#[cfg(not(feature = "plugins"))]
+impl PluginInstance {
+    pub fn pick_next(
+        &mut self,
+        _ready: &[Process],
+        _start: SystemTime,
+        _now: SystemTime,
+    ) -> Result<usize, SimError> {
+        match self.never {}
+    }
+
+    pub fn on_tick(
+        &mut self,
+        _process: &Process,
+        _start: SystemTime,
+        _now: SystemTime,
+    ) -> Result<bool, SimError> {
+        match self.never {}
+    }
+}
+
+#[cfg(all(test, feature = "plugins"))]
+mod tests {
+    use super::*;
+    use crate::queue_engine::Queues;
+    use crate::scenario::{ProcessSource, Scenario};
+    use std::time::Duration;
+
+    // on_arrival ignores what it's told, `pick_next` is the body of pick_next
+    fn plugin(pick_next: &str) -> Arc<PluginPolicy> {
+        let wat = format!(
+            r#"(module
+                 (func (export "on_arrival") (param i32 f64 f64 f64 i32 i32))
+                 (func (export "pick_next") (param f64) (result i32) {}))"#,
+            pick_next
+        );
+        Arc::new(PluginPolicy::from_bytes("test", wat.as_bytes()).unwrap())
+    }
+
+    fn srtf() -> Arc<PluginPolicy> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../plugins/srtf.wat");
+        Arc::new(PluginPolicy::load(&path).unwrap())
+    }
+
+    fn process(burst: u64, start: SystemTime) -> Process {
+        Process::new(Duration::from_millis(burst), start, Some(ProcessType::BatchProcess))
+    }
+
+    fn message(error: SimError) -> (String, String) {
+        match error {
+            SimError::Plugin { function, message, .. } => (function, message),
+            error => panic!("not a plugin error: {}", error),
+        }
+    }
+
+    #[test]
+    fn a_plugin_that_never_returns_runs_out_of_fuel() {
+        let start = SystemTime::now();
+        let mut instance = plugin("(loop $forever (br $forever)) (i32.const 0)").instantiate().unwrap();
+        let error = instance.pick_next(&[process(4, start)], start, start).unwrap_err();
+        let (function, message) = message(error);
+        assert_eq!(function, "pick_next");
+        assert!(message.starts_with("ran out of fuel"), "{}", message);
+    }
+
+    #[test]
+    fn imports_are_refused() {
+        let wat = r#"(module (import "env" "clock" (func)))"#;
+        let error = PluginPolicy::from_bytes("test", wat.as_bytes()).unwrap_err();
+        assert_eq!(error, "a plugin can't import anything, this one imports env::clock");
+    }
+
+    #[test]
+    fn picking_a_process_that_isnt_queued_fails() {
+        let start = SystemTime::now();
+        let mut instance = plugin("(i32.const 42)").instantiate().unwrap();
+        let error = instance.pick_next(&[process(4, start)], start, start).unwrap_err();
+        let (function, message) = message(error);
+        assert_eq!(function, "pick_next");
+        assert_eq!(message, "returned 42, which isn't a process in the queue");
+    }
+
+    #[test]
+    fn a_process_taken_by_a_boost_is_forgotten() {
+        let start = SystemTime::now();
+        let mut instance = srtf().instantiate().unwrap();
+        let (short, long) = (process(4, start), process(8, start));
+        assert_eq!(instance.pick_next(&[short, long.clone()], start, start).unwrap(), 0);
+        // the boost took `long` while the plugin still has it queued, it starts over and
+        // only knows about what's really in the queue
+        let longer = process(10, start);
+        assert_eq!(instance.pick_next(&[longer], start, start).unwrap(), 0);
+    }
+
+    #[test]
+    fn srtf_plugin_schedules_like_the_built_in_srtf() {
+        let run = |queue: Queues, plugin: Option<Arc<PluginPolicy>>| {
+            let source = ProcessSource::Generated {
+                arrival_lambda: 1.0,
+                burst_lambda: 2.0,
+                count: 20,
+            };
+            let mut scenario = Scenario {
+                seed: Some(1),
+                ..Scenario::new(queue, source)
+            };
+            scenario.options.plugin = plugin;
+            let mut result = scenario.run().unwrap();
+            result.metrics.remove("queue_discipline");
+            serde_json::to_value((result.metrics, result.processes)).unwrap()
+        };
+        assert_eq!(run(Queues::PLUGIN, Some(srtf())), run(Queues::SRF, None));
+    }
+}
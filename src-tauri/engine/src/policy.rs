@@ -0,0 +1,238 @@
+use crate::error::SimError;
+use crate::process_gen::Process;
+use crate::util::{millis, millis_since};
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST, FLOAT, INT};
+use std::fmt;
+use std::time::SystemTime;
+
+// Utils -------------------------------------------------------------------------------------------
+
+// a script gets this many operations per call, a loop that never ends is stopped by it
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+fn optional<T: Into<Dynamic>>(value: Option<T>) -> Dynamic {
+    value.map_or(Dynamic::UNIT, Into::into)
+}
+
+/// What a script sees of a process, a copy, so nothing the script does changes the run. Times
+/// are in milliseconds since the start of the run, durations in milliseconds.
+fn view(process: &Process, start: SystemTime, now: SystemTime) -> Map {
+    let io_phases: Array = process
+        .io_phases
+        .iter()
+        .map(|io| {
+            let mut phase = Map::new();
+            phase.insert("at".into(), millis(io.at).into());
+            phase.insert("duration".into(), millis(io.duration).into());
+            phase.into()
+        })
+        .collect();
+    let mut map = Map::new();
+    map.insert("id".into(), process.id.to_string().into());
+    map.insert("name".into(), optional(process.name.clone()));
+    map.insert("process_type".into(), process.process_type.short_name().into());
+    map.insert("priority".into(), optional(process.priority.map(INT::from)));
+    map.insert("arrival_time".into(), millis_since(process.arrival_time, start).into());
+    map.insert("cpu_burst_time".into(), millis(process.cpu_burst_time).into());
+    map.insert("processed_time".into(), millis(process.processed_time).into());
+    map.insert(
+        "remaining_time".into(),
+        millis(process.cpu_burst_time.saturating_sub(process.processed_time)).into(),
+    );
+    map.insert("waiting_time".into(), millis(process.waiting_at(now)).into());
+    map.insert(
+        "last_execution".into(),
+        optional(process.last_execution.map(|t| millis_since(t, start))),
+    );
+    map.insert("deadline".into(), optional(process.deadline.map(|d| millis_since(d, start))));
+    map.insert("io_phases".into(), io_phases.into());
+    map.insert("completed_io".into(), (process.completed_io as INT).into());
+    map
+}
+
+// Script Policy -----------------------------------------------------------------------------------
+
+/// A scheduling policy written in Rhai, run as the `SCRIPT` discipline. The script defines
+///
+/// ```rhai
+/// // the index in `ready` of the process to run next, `now` is the time of the run in ms
+/// fn pick_next(ready, now) { ... }
+///
+/// // optional: true runs `process` for one time quantum, false until it finishes or blocks on
+/// // I/O. Without it every process is preempted after a quantum.
+/// fn preempt(process, now) { ... }
+/// ```
+///
+/// Every process is a map with the fields of `view`. The script can't reach files, modules or
+/// the engine, and a call that runs too long is stopped.
+pub struct ScriptPolicy {
+    pub name: String, // where the script came from, for errors
+    engine: Engine,
+    ast: AST,
+    has_preempt: bool,
+}
+
+impl fmt::Debug for ScriptPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScriptPolicy").field("name", &self.name).finish()
+    }
+}
+
+impl ScriptPolicy {
+    /// Compiles `source`, the error is Rhai's, with its line and column.
+    pub fn compile(name: impl Into<String>, source: &str) -> Result<Self, String> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(64);
+        engine.set_max_expr_depths(64, 64);
+        engine.set_max_string_size(10_000);
+        engine.set_max_array_size(10_000);
+        engine.set_max_map_size(1_000);
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.on_print(|_| {});
+        engine.on_debug(|_, _, _| {});
+
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        let defines = |function: &str| {
+            ast.iter_functions().any(|f| f.name == function && f.params.len() == 2)
+        };
+        if !defines("pick_next") {
+            return Err(String::from("the script needs a `fn pick_next(ready, now)`"));
+        }
+        let has_preempt = defines("preempt");
+        Ok(ScriptPolicy {
+            name: name.into(),
+            engine,
+            ast,
+            has_preempt,
+        })
+    }
+
+    fn call(&self, function: &str, args: (Dynamic, FLOAT)) -> Result<Dynamic, SimError> {
+        self.engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &self.ast, function, args)
+            .map_err(|e| self.error(function, e.to_string()))
+    }
+
+    fn error(&self, function: &str, message: String) -> SimError {
+        SimError::Script {
+            script: self.name.clone(),
+            function: function.to_string(),
+            message,
+        }
+    }
+
+    /// Which of `ready` runs next.
+    pub fn pick_next(
+        &self,
+        ready: &[Process],
+        start: SystemTime,
+        now: SystemTime,
+    ) -> Result<usize, SimError> {
+        let views: Array = ready.iter().map(|p| view(p, start, now).into()).collect();
+        let picked = self.call("pick_next", (views.into(), millis_since(now, start)))?;
+        let index = picked.as_int().map_err(|kind| {
+            self.error("pick_next", format!("returned a {}, not the index of a process", kind))
+        })?;
+        usize::try_from(index).ok().filter(|i| *i < ready.len()).ok_or(self.error(
+            "pick_next",
+            format!("returned {}, the ready queue has {} processes", index, ready.len()),
+        ))
+    }
+
+    /// Whether `process` goes back to the ready queue after a time quantum.
+    pub fn preempt(
+        &self,
+        process: &Process,
+        start: SystemTime,
+        now: SystemTime,
+    ) -> Result<bool, SimError> {
+        if !self.has_preempt {
+            return Ok(true);
+        }
+        let process = view(process, start, now);
+        let preempt = self.call("preempt", (process.into(), millis_since(now, start)))?;
+        preempt
+            .as_bool()
+            .map_err(|kind| self.error("preempt", format!("returned a {}, not true or false", kind)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_gen::ProcessType;
+    use std::time::Duration;
+
+    fn process(burst: u64, start: SystemTime) -> Process {
+        Process::new(Duration::from_millis(burst), start, Some(ProcessType::BatchProcess))
+    }
+
+    fn failure(error: SimError) -> (String, String) {
+        match error {
+            SimError::Script { function, message, .. } => (function, message),
+            error => panic!("not a script error: {}", error),
+        }
+    }
+
+    /// What `pick_next` of a script whose body is `body` makes of a queue of two.
+    fn picked(body: &str) -> Result<usize, SimError> {
+        let source = format!("fn pick_next(ready, now) {{ {} }}", body);
+        let policy = ScriptPolicy::compile("test", &source).unwrap();
+        let start = SystemTime::now();
+        policy.pick_next(&[process(4, start), process(8, start)], start, start)
+    }
+
+    #[test]
+    fn compile_errors_are_rhais() {
+        let error = ScriptPolicy::compile("test", "fn pick_next(ready, now) { 0 ").unwrap_err();
+        assert!(error.contains("line 1"), "{}", error);
+    }
+
+    #[test]
+    fn a_script_needs_pick_next() {
+        let error = ScriptPolicy::compile("test", "fn preempt(process, now) { true }").unwrap_err();
+        assert_eq!(error, "the script needs a `fn pick_next(ready, now)`");
+    }
+
+    #[test]
+    fn picks_the_index_returned() {
+        assert_eq!(picked("if ready[0].cpu_burst_time > ready[1].cpu_burst_time { 0 } else { 1 }").unwrap(), 1);
+    }
+
+    #[test]
+    fn a_loop_that_never_ends_is_stopped() {
+        let (function, message) = failure(picked("loop {}").unwrap_err());
+        assert_eq!(function, "pick_next");
+        assert!(message.contains("Too many operations"), "{}", message);
+    }
+
+    #[test]
+    fn returns_that_arent_an_index_fail() {
+        let (_, message) = failure(picked("2").unwrap_err());
+        assert_eq!(message, "returned 2, the ready queue has 2 processes");
+        let (_, message) = failure(picked("-1").unwrap_err());
+        assert_eq!(message, "returned -1, the ready queue has 2 processes");
+        let (_, message) = failure(picked("\"P1\"").unwrap_err());
+        assert!(message.starts_with("returned a string"), "{}", message);
+    }
+
+    #[test]
+    fn preempt_has_to_return_a_bool() {
+        let source = "fn pick_next(ready, now) { 0 } fn preempt(process, now) { 1 }";
+        let policy = ScriptPolicy::compile("test", source).unwrap();
+        let start = SystemTime::now();
+        let (function, message) = failure(policy.preempt(&process(4, start), start, start).unwrap_err());
+        assert_eq!(function, "preempt");
+        assert!(message.starts_with("returned a i64"), "{}", message);
+    }
+
+    #[test]
+    fn without_preempt_every_process_is_preempted() {
+        let policy = ScriptPolicy::compile("test", "fn pick_next(ready, now) { 0 }").unwrap();
+        let start = SystemTime::now();
+        assert!(policy.preempt(&process(4, start), start, start).unwrap());
+    }
+}
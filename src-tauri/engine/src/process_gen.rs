@@ -0,0 +1,283 @@
+use crate::clock::SimClock;
+use crate::error::SimError;
+use chrono::{DateTime, Utc};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+// Utils -------------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ProcessStatus {
+    /// Determines the current state of the process which could be one of these: New,
+    /// Ready, Running, Waiting, Terminated
+    New, // The process has been created but not yet ready to run.
+    Ready,      // The process is ready to run but is waiting for CPU time.
+    Running,    // The process is currently executing.
+    Waiting,    // The process is waiting for an event (e.g., I/O completion).
+    Terminated, // The process has finished execution.
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ProcessType {
+    /// SystemProcess represents processes that are critical to the system's operation,
+    /// often having higher priority and requiring swift execution.
+    SystemProcess,
+
+    /// InteractiveProcess represents processes that interact with the user, such as GUI applications.
+    /// These processes typically require lower latency to provide a responsive user experience.
+    InteractiveProcess,
+
+    /// BatchProcess represents processes that are run in the background, often with lower priority,
+    /// and do not require immediate user interaction, such as scheduled tasks or data processing jobs.
+    BatchProcess,
+
+    /// LowPriorityProcess represents processes with the lowest priority, which can tolerate delays
+    /// and are executed only when resources are not being used by higher-priority tasks.
+    StudentProcess,
+}
+
+impl ProcessType {
+    /// Parses the short names the UI and workload files use ("system", "interactive",
+    /// "batch", "student"). Returns None for anything else.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "system" => Some(ProcessType::SystemProcess),
+            "batch" => Some(ProcessType::BatchProcess),
+            "interactive" => Some(ProcessType::InteractiveProcess),
+            "student" => Some(ProcessType::StudentProcess),
+            _ => None,
+        }
+    }
+
+    /// The name from_name reads back.
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            ProcessType::SystemProcess => "system",
+            ProcessType::InteractiveProcess => "interactive",
+            ProcessType::BatchProcess => "batch",
+            ProcessType::StudentProcess => "student",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct IoPhase {
+    /// An I/O request the process makes once it has used `at` of CPU time. While the I/O is in
+    /// progress the process is blocked and does not compete for the CPU.
+    pub at: Duration,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Metrics {
+    pub response_time: Duration, // Time taken from process arrival to its first response.
+    pub total_waiting_time: Duration, // Total time the process spent waiting in queues.
+    pub total_time: Duration,    // Total time from process arrival to completion.
+}
+
+impl Metrics {
+    fn new() -> Self {
+        Metrics {
+            response_time: Duration::from_millis(0),
+            total_time: Duration::from_millis(0),
+            total_waiting_time: Duration::from_millis(0),
+        }
+    }
+}
+
+//This Struct Is the data we send to frontend
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SerializableProcess {
+    pub id: Uuid,
+    pub arrival_time: DateTime<Utc>,
+    pub cpu_burst_time: Duration,
+    pub status: ProcessStatus,
+    pub waiting_time: Duration,
+    pub processed_time: Duration,
+    pub process_type: ProcessType,
+    pub last_execution: Option<DateTime<Utc>>,
+    pub metrics: Metrics,
+    pub name: Option<String>,
+    pub priority: Option<u8>,
+    pub deadline: Option<DateTime<Utc>>,
+    pub io_phases: Vec<IoPhase>,
+}
+
+// Process -----------------------------------------------------------------------------------------
+#[derive(Debug, Clone)]
+pub struct Process {
+    pub id: Uuid,                        // Unique identifier for the process.
+    pub arrival_time: SystemTime,           // The time when the process arrived in the system.
+    pub cpu_burst_time: Duration,        // The total CPU time required by the process.
+    pub status: ProcessStatus,           // Current status of the process.
+    pub waiting_time: Duration,          // Accumulated time the process has spent waiting.
+    pub processed_time: Duration,        // Total time the process has been executed.
+    pub process_type: ProcessType,       // Type of the process (e.g., system, interactive, batch).
+    pub last_execution: Option<SystemTime>, // The last time the process was executed.
+    pub metrics: Metrics,                // Performance metrics related to the process.
+    pub name: Option<String>,            // Label given by an imported workload (e.g. "P1").
+    pub priority: Option<u8>,            // Optional priority given by an imported workload.
+    pub deadline: Option<SystemTime>,    // Optional absolute deadline for completion.
+    pub io_phases: Vec<IoPhase>,         // I/O requests, ordered by the CPU time they happen at.
+    pub completed_io: usize,             // Number of entries of io_phases already performed.
+    pub ready_since: Option<SystemTime>, // When the process last became ready again (after a slice or I/O).
+}
+
+impl Process {
+    pub fn calculate_waiting_time(&mut self, current_time: SystemTime) {
+        // Check if this is the first time the process is being executed
+        if self.last_execution.is_none() {
+            self.waiting_time += current_time
+                .duration_since(self.arrival_time)
+                .unwrap_or(Duration::from_secs(0));
+            self.metrics.response_time = self.waiting_time;
+        }
+
+        // Logic for subsequent executions: it waited since it was put back in the ready queue
+        if let Some(ready_since) = self.ready_since {
+            self.waiting_time += current_time
+                .duration_since(ready_since)
+                .unwrap_or(Duration::from_secs(0));
+        }
+        self.last_execution = Some(current_time);
+        self.ready_since = None;
+        self.metrics.total_waiting_time = self.waiting_time;
+    }
+
+    /// The waiting time the process would have if it was dispatched at `current_time`.
+    pub fn waiting_at(&self, current_time: SystemTime) -> Duration {
+        let since = match self.last_execution {
+            None => Some(self.arrival_time),
+            Some(_) => self.ready_since,
+        };
+        let waiting = since.and_then(|since| current_time.duration_since(since).ok());
+        self.waiting_time + waiting.unwrap_or_default()
+    }
+
+    pub fn run_with_interrupt(
+        &mut self,
+        &quantum_time: &Duration,
+        clock: &mut SimClock,
+    ) -> Result<(), SimError> {
+        // calculate waiting time
+        self.calculate_waiting_time(clock.now());
+
+        //simulating process work ...
+
+        let remaining_time = self.time_until_stop()?;
+        if remaining_time >= quantum_time {
+            clock.advance(quantum_time);
+            self.processed_time += quantum_time;
+        } else {
+            clock.advance(remaining_time);
+            self.processed_time += remaining_time;
+        }
+        self.ready_since = Some(clock.now());
+        self.metrics.total_time = self.processed_time + self.waiting_time + self.io_time();
+        Ok(()) // Returns Ok(()) if there is no error
+    }
+
+    pub fn run(&mut self, clock: &mut SimClock) -> Result<(), SimError> {
+        //simulating process work ...
+        self.calculate_waiting_time(clock.now());
+        let remaining_time = self.time_until_stop()?;
+        clock.advance(remaining_time);
+        self.processed_time += remaining_time;
+        self.ready_since = Some(clock.now());
+        self.metrics.total_time = self.processed_time + self.waiting_time + self.io_time();
+        Ok(()) // Returns Ok(()) if there is no error
+    }
+
+    fn time_until_stop(&self) -> Result<Duration, SimError> {
+        // the process runs until it either finishes or reaches its next I/O request
+        let remaining_time = self.cpu_burst_time.checked_sub(self.processed_time).ok_or(
+            SimError::ProcessOverrun {
+                id: self.id,
+                processed: self.processed_time,
+                burst: self.cpu_burst_time,
+            },
+        )?;
+        Ok(match self.io_phases.get(self.completed_io) {
+            Some(io) if io.at > self.processed_time => remaining_time.min(io.at - self.processed_time),
+            _ => remaining_time,
+        })
+    }
+
+    /// Returns the I/O phase the process has to perform now, if it just reached one.
+    pub fn pending_io(&self) -> Option<IoPhase> {
+        self.io_phases
+            .get(self.completed_io)
+            .filter(|io| io.at <= self.processed_time && self.processed_time < self.cpu_burst_time)
+            .copied()
+    }
+
+    fn io_time(&self) -> Duration {
+        self.io_phases[..self.completed_io].iter().map(|io| io.duration).sum()
+    }
+
+    pub fn new(cbt: Duration, at: SystemTime, pt: Option<ProcessType>) -> Self {
+        Process::with_rng(cbt, at, pt, &mut thread_rng())
+    }
+
+    /// Same as `new`, but the random type and id come from `rng` so seeded runs are repeatable.
+    pub fn with_rng<R: Rng>(cbt: Duration, at: SystemTime, pt: Option<ProcessType>, rng: &mut R) -> Self {
+        let process_variants = [
+            ProcessType::BatchProcess,
+            ProcessType::InteractiveProcess,
+            ProcessType::StudentProcess,
+            ProcessType::SystemProcess,
+        ];
+        let process_type: ProcessType;
+        if let Some(process) = pt {
+            process_type = process;
+        } else {
+            process_type =  process_variants[rng.gen_range(0..process_variants.len())];
+        }
+        Process {
+            id: uuid::Builder::from_random_bytes(rng.gen()).into_uuid(),
+            cpu_burst_time: cbt,
+            arrival_time: at,
+            status: ProcessStatus::New,
+            processed_time: Duration::from_secs(0),
+            waiting_time: Duration::from_secs(0),
+            last_execution: None,
+            process_type,
+            metrics: Metrics::new(),
+            name: None,
+            priority: None,
+            deadline: None,
+            io_phases: Vec::new(),
+            completed_io: 0,
+            ready_since: None,
+        }
+    }
+    fn systemtime_to_datetime(system_time: SystemTime) -> DateTime<Utc> {
+        // chrono takes times before the UNIX epoch too, so there's nothing to fail here
+        DateTime::<Utc>::from(system_time)
+    }
+
+    pub fn to_serializable(&self) -> SerializableProcess {
+        let at: DateTime<Utc> = Process::systemtime_to_datetime(self.arrival_time);
+        let mut le = None;
+        if let Some(systime) = self.last_execution {
+            le = Some(Process::systemtime_to_datetime(systime));
+        }
+        SerializableProcess {
+            id: self.id,
+            arrival_time: at, // Convert to DateTime<Utc> when serializing
+            cpu_burst_time: self.cpu_burst_time,
+            status: self.status.clone(),
+            waiting_time: self.waiting_time,
+            processed_time: self.processed_time,
+            process_type: self.process_type,
+            last_execution: le, // Convert Instant to DateTime<Utc> if needed
+            metrics: self.metrics,
+            name: self.name.clone(),
+            priority: self.priority,
+            deadline: self.deadline.map(Process::systemtime_to_datetime),
+            io_phases: self.io_phases.clone(),
+        }
+    }
+}
@@ -0,0 +1,977 @@
+use crate::decision::{self, Decision};
+use crate::error::SimError;
+use crate::events::{Recorder, SimEvent};
+use crate::plugin::{PluginInstance, PluginPolicy};
+use crate::policy::ScriptPolicy;
+use crate::stats;
+use crate::process_gen::{Metrics, Process, ProcessStatus, ProcessType, SerializableProcess};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+// Utils -------------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum QueueDiscipline {
+    /// queue discipline is the way cpu scheduler prioritize processes
+    /// with this enum we choose the algorithm and pass it to the ready
+    /// queue
+    FIFO,
+    SPN,
+    FCFS,
+    SJF,
+    HRRN,
+    RR,
+    #[serde(rename = "SRTF", alias = "SRF")]
+    SRF,
+    SCRIPT, // the run's scheduling script picks the process, see policy.rs
+    PLUGIN, // the run's WebAssembly policy picks the process, see plugin.rs
+}
+
+impl QueueDiscipline {
+    /// The names the UI sends, SRTF and SRF are the same algorithm.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_uppercase().as_str() {
+            "FIFO" => Some(QueueDiscipline::FIFO),
+            "SPN" => Some(QueueDiscipline::SPN),
+            "FCFS" => Some(QueueDiscipline::FCFS),
+            "SJF" => Some(QueueDiscipline::SJF),
+            "HRRN" => Some(QueueDiscipline::HRRN),
+            "RR" => Some(QueueDiscipline::RR),
+            "SRTF" | "SRF" => Some(QueueDiscipline::SRF),
+            "SCRIPT" => Some(QueueDiscipline::SCRIPT),
+            "PLUGIN" => Some(QueueDiscipline::PLUGIN),
+            _ => None,
+        }
+    }
+
+    /// Whether a process runs a time quantum at a time, so the discipline needs one.
+    pub fn is_preemptive(&self) -> bool {
+        match self {
+            QueueDiscipline::FIFO => false,
+            QueueDiscipline::SPN => false,
+            QueueDiscipline::FCFS => false,
+            QueueDiscipline::SJF => true,
+            QueueDiscipline::HRRN => false,
+            QueueDiscipline::RR => true,
+            QueueDiscipline::SRF => true,
+            QueueDiscipline::SCRIPT => true, // unless the script's preempt says otherwise
+            QueueDiscipline::PLUGIN => true, // unless the plugin's on_tick says otherwise
+        }
+    }
+}
+
+impl fmt::Display for QueueDiscipline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueueDiscipline::FIFO => write!(f, "FIFO"),
+            QueueDiscipline::SPN => write!(f, "SPN"),
+            QueueDiscipline::FCFS => write!(f, "FCFS"),
+            QueueDiscipline::SJF => write!(f, "SJF"),
+            QueueDiscipline::HRRN => write!(f, "HRRN"),
+            QueueDiscipline::RR => write!(f, "RR"),
+            QueueDiscipline::SRF => write!(f, "SRTF"),
+            QueueDiscipline::SCRIPT => write!(f, "SCRIPT"),
+            QueueDiscipline::PLUGIN => write!(f, "PLUGIN"),
+        }
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Queues {
+    // we use this enum to find out which algorithm is selected by the user.
+    //typically using enum is cleaner than to use strings
+    FIFO,
+    SPN,
+    FCFS,
+    SJF,
+    HRRN,
+    RR,
+    #[serde(alias = "SRTF")]
+    SRF,
+    MLQ,
+    MLFQ,
+    SCRIPT, // a single queue run by the scheduling script
+    PLUGIN, // a single queue run by the WebAssembly policy
+}
+
+impl fmt::Display for Queues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Queues::FIFO => write!(f, "FIFO"),
+            Queues::SPN => write!(f, "SPN"),
+            Queues::FCFS => write!(f, "FCFS"),
+            Queues::SJF => write!(f, "SJF"),
+            Queues::HRRN => write!(f, "HRRN"),
+            Queues::RR => write!(f, "RR"),
+            Queues::SRF => write!(f, "SRF"),
+            Queues::MLQ => write!(f, "MLQ"),
+            Queues::MLFQ => write!(f, "MLFQ"),
+            Queues::SCRIPT => write!(f, "SCRIPT"),
+            Queues::PLUGIN => write!(f, "PLUGIN"),
+        }
+    }
+}
+
+impl Queues {
+    /// The names the UI sends, SRTF and SRF are the same algorithm.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_uppercase().as_str() {
+            "FCFS" => Some(Queues::FCFS),
+            "FIFO" => Some(Queues::FIFO),
+            "SPN" => Some(Queues::SPN),
+            "SJF" => Some(Queues::SJF),
+            "HRRN" => Some(Queues::HRRN),
+            "RR" => Some(Queues::RR),
+            "SRTF" | "SRF" => Some(Queues::SRF),
+            "MLQ" => Some(Queues::MLQ),
+            "MLFQ" => Some(Queues::MLFQ),
+            "SCRIPT" => Some(Queues::SCRIPT),
+            "PLUGIN" => Some(Queues::PLUGIN),
+            _ => None,
+        }
+    }
+
+    pub fn is_multilevel(&self) -> bool {
+        matches!(self, Queues::MLQ | Queues::MLFQ)
+    }
+
+    /// Whether the queue, or one of its levels when it's MLQ or MLFQ, runs `discipline`.
+    pub fn uses(&self, disciplines: Option<[QueueDiscipline; 4]>, discipline: QueueDiscipline) -> bool {
+        match self {
+            Queues::MLQ | Queues::MLFQ => disciplines.is_some_and(|d| d.contains(&discipline)),
+            Queues::SCRIPT => discipline == QueueDiscipline::SCRIPT,
+            Queues::PLUGIN => discipline == QueueDiscipline::PLUGIN,
+            _ => false,
+        }
+    }
+
+    /// What runs a time quantum at a time, the queue or the first such level of MLQ and MLFQ,
+    /// whose levels are RR unless `disciplines` says otherwise. None when nothing does.
+    pub fn needs_quantum(&self, disciplines: Option<[QueueDiscipline; 4]>) -> Option<String> {
+        let single = match self {
+            Queues::MLQ | Queues::MLFQ => {
+                let levels = disciplines.unwrap_or([QueueDiscipline::RR; 4]);
+                let level = levels.iter().position(|d| d.is_preemptive())?;
+                return Some(format!("level {} of {} ({})", level + 1, self, levels[level]));
+            }
+            Queues::FIFO | Queues::SPN | Queues::FCFS | Queues::HRRN => false,
+            Queues::SJF | Queues::RR | Queues::SRF | Queues::SCRIPT | Queues::PLUGIN => true,
+        };
+        single.then(|| self.to_string())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum MetricValue {
+    // Since metric values' types differ. in order to store these metrics in a single
+    // hashmap we need to define this enum to store multiple types inside of it.
+    DurationValue(Duration),
+    PercentageValue(f64),
+    IntegerValue(i32),
+    StringValue(String),
+    FloatValue(f64), // a plain number, like a fairness index
+}
+
+impl MetricValue {
+    /// The value as a plain number so metrics can be compared and averaged, durations are in
+    /// milliseconds. Names have no numeric value.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            MetricValue::DurationValue(d) => Some(d.as_secs_f64() * 1000.0),
+            MetricValue::PercentageValue(p) => Some(*p),
+            MetricValue::IntegerValue(i) => Some(*i as f64),
+            MetricValue::StringValue(_) => None,
+            MetricValue::FloatValue(f) => Some(*f),
+        }
+    }
+}
+
+/// Averages over the processes that finished, used for a single queue as well as for every
+/// level of a multilevel queue together.
+pub fn calculate_metrics(
+    finished_processes: &[Process],
+    queue_discipline: String,
+) -> HashMap<String, MetricValue> {
+    let process_metrics: Vec<Metrics> = finished_processes
+        .iter()
+        .map(|process| process.metrics)
+        .collect();
+    let length_of_processes: u32 = process_metrics.len() as u32;
+    let mut average_metrics: HashMap<String, MetricValue> = HashMap::new();
+    if length_of_processes == 0 {
+        average_metrics.insert(
+            String::from("average_turnaround_time"),
+            MetricValue::DurationValue(Duration::from_secs(0)),
+        );
+        average_metrics.insert(
+            String::from("average_waiting_time"),
+            MetricValue::DurationValue(Duration::from_secs(0)),
+        );
+        average_metrics.insert(
+            String::from("average_response_time"),
+            MetricValue::DurationValue(Duration::from_secs(0)),
+        );
+        average_metrics.insert(
+            String::from("cpu_utilization"),
+            MetricValue::PercentageValue(0.0),
+        );
+        average_metrics.insert(
+            String::from("queue_discipline"),
+            MetricValue::StringValue(queue_discipline),
+        );
+        return average_metrics;
+    }
+    average_metrics.insert(
+        String::from("queue_discipline"),
+        MetricValue::StringValue(queue_discipline),
+    );
+    average_metrics.insert(
+        String::from("average_turnaround_time"),
+        MetricValue::DurationValue(
+            process_metrics
+                .iter()
+                .map(|m| m.total_time)
+                .sum::<Duration>()
+                / length_of_processes,
+        ),
+    );
+    average_metrics.insert(
+        String::from("average_waiting_time"),
+        MetricValue::DurationValue(
+            process_metrics
+                .iter()
+                .map(|m| m.total_waiting_time)
+                .sum::<Duration>()
+                / length_of_processes,
+        ),
+    );
+    average_metrics.insert(
+        String::from("average_response_time"),
+        MetricValue::DurationValue(
+            process_metrics
+                .iter()
+                .map(|m| m.response_time)
+                .sum::<Duration>()
+                / length_of_processes,
+        ),
+    );
+    let total_cpu_burst_time = finished_processes
+        .iter()
+        .map(|p| p.cpu_burst_time)
+        .sum::<Duration>();
+    let total_time = finished_processes
+        .iter()
+        .map(|p| p.metrics.total_time)
+        .sum::<Duration>();
+    average_metrics.insert(
+        String::from("cpu_utilization"),
+        MetricValue::PercentageValue(
+            (total_cpu_burst_time.as_secs_f64() / total_time.as_secs_f64()) * 100.0,
+        ),
+    );
+    // fairness: 1 when every process is treated alike, 1/n when one process gets it all ---------
+    let slowdowns: Vec<f64> = finished_processes
+        .iter()
+        .filter(|p| !p.cpu_burst_time.is_zero())
+        .map(|p| p.metrics.total_time.as_secs_f64() / p.cpu_burst_time.as_secs_f64())
+        .collect();
+    let cpu_shares: Vec<f64> = slowdowns.iter().map(|slowdown| 1.0 / slowdown).collect();
+    average_metrics.insert(
+        String::from("jain_fairness_slowdown"),
+        MetricValue::FloatValue(stats::jain_index(&slowdowns)),
+    );
+    average_metrics.insert(
+        String::from("jain_fairness_cpu_share"),
+        MetricValue::FloatValue(stats::jain_index(&cpu_shares)),
+    );
+    average_metrics.insert(
+        String::from("max_waiting_time"),
+        MetricValue::DurationValue(
+            process_metrics.iter().map(|m| m.total_waiting_time).max().unwrap_or_default(),
+        ),
+    );
+    for process_type in [
+        ProcessType::SystemProcess,
+        ProcessType::InteractiveProcess,
+        ProcessType::BatchProcess,
+        ProcessType::StudentProcess,
+    ] {
+        let max_waiting = finished_processes
+            .iter()
+            .filter(|p| p.process_type == process_type)
+            .map(|p| p.metrics.total_waiting_time)
+            .max();
+        if let Some(max_waiting) = max_waiting {
+            average_metrics.insert(
+                format!("max_waiting_time_{}", process_type.short_name()),
+                MetricValue::DurationValue(max_waiting),
+            );
+        }
+    }
+    // deadlines only exist for imported workloads, so the metric is only sent for them
+    let with_deadline: Vec<&Process> = finished_processes
+        .iter()
+        .filter(|p| p.deadline.is_some())
+        .collect();
+    if !with_deadline.is_empty() {
+        let missed = with_deadline
+            .iter()
+            .filter(|p| p.deadline.is_some_and(|d| p.arrival_time + p.metrics.total_time > d))
+            .count();
+        average_metrics.insert(
+            String::from("missed_deadlines"),
+            MetricValue::IntegerValue(missed as i32),
+        );
+    }
+    average_metrics
+}
+
+// Ready Queue -------------------------------------------------------------------------------------
+
+pub struct ReadyQueue {
+    queue_number: i8,
+    processes: Vec<Process>,
+    discipline: QueueDiscipline,
+    time_quantum: Duration,
+    context_switch: Duration,
+    finished_processes: Vec<Process>, // processes that finished their process time are pushed
+                                      // into this vector so rust won't drop their value.
+    blocked: Vec<(SystemTime, Process)>, // processes doing I/O, with the time their I/O ends.
+    policy: Option<Arc<ScriptPolicy>>, // picks the next process when the discipline is SCRIPT
+    plugin: Option<PluginInstance>,    // picks the next process when the discipline is PLUGIN
+}
+
+impl ReadyQueue {
+    pub fn new(
+        discipline: QueueDiscipline,
+        context_switch: Duration,
+        time_quantum: Duration,
+    ) -> Self {
+        ReadyQueue {
+            queue_number: 1,
+            processes: Vec::new(),
+            discipline,
+            time_quantum,
+            context_switch,
+            finished_processes: Vec::new(),
+            blocked: Vec::new(),
+            policy: None,
+            plugin: None,
+        }
+    }
+
+    pub fn set_policy(&mut self, policy: Arc<ScriptPolicy>) {
+        self.policy = Some(policy);
+    }
+
+    /// Starts an instance of `plugin` for this queue, if its discipline is PLUGIN.
+    pub fn set_plugin(&mut self, plugin: &Arc<PluginPolicy>) -> Result<(), SimError> {
+        if self.discipline == QueueDiscipline::PLUGIN {
+            self.plugin = Some(plugin.instantiate()?);
+        }
+        Ok(())
+    }
+
+    /// Puts the process to run next first. `now` is for HRRN, whose ratios grow with waiting.
+    pub fn sort(&mut self, now: SystemTime) {
+        match self.discipline {
+            QueueDiscipline::FIFO => {}
+            QueueDiscipline::SPN => {
+                self.processes.sort_by_key(|p| p.cpu_burst_time);
+            }
+            QueueDiscipline::FCFS => {}
+            QueueDiscipline::SJF => {
+                self.processes.sort_by_key(|p| p.cpu_burst_time);
+            }
+            QueueDiscipline::HRRN => {
+                self.processes.sort_by(|p1, p2| {
+                    // the waiting up to now, a process that never ran has waited since it arrived
+                    let p1_ratio = (p1.waiting_at(now) + p1.cpu_burst_time).as_secs_f64()
+                        / p1.cpu_burst_time.as_secs_f64();
+                    let p2_ratio = (p2.waiting_at(now) + p2.cpu_burst_time).as_secs_f64()
+                        / p2.cpu_burst_time.as_secs_f64();
+                    p2_ratio
+                        .partial_cmp(&p1_ratio)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+            }
+            QueueDiscipline::RR => {
+                self.processes.sort_by(|p1, p2| {
+                    let p1_le = p1.last_execution.unwrap_or(SystemTime::UNIX_EPOCH);
+                    let p2_le = p2.last_execution.unwrap_or(SystemTime::UNIX_EPOCH);
+
+                    p1_le
+                        .partial_cmp(&p2_le)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+            }
+            QueueDiscipline::SRF => {
+                self.processes.sort_by(|p1, p2| {
+                    let p1_remaining_time = p1.cpu_burst_time.saturating_sub(p1.processed_time);
+                    let p2_remaining_time = p2.cpu_burst_time.saturating_sub(p2.processed_time);
+                    p1_remaining_time
+                        .partial_cmp(&p2_remaining_time)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+            }
+            QueueDiscipline::SCRIPT => {} // the script picks, see pick_next
+            QueueDiscipline::PLUGIN => {} // and so does the plugin
+        }
+    }
+
+    pub fn is_preemptive(&self) -> bool {
+        self.discipline.is_preemptive()
+    }
+
+    pub fn enqueue(&mut self, mut process: Process) {
+        process.status = ProcessStatus::Ready;
+        self.processes.push(process);
+    }
+
+    pub fn dequeue(&mut self, now: SystemTime) -> Option<Process> {
+        self.sort(now);
+        if self.processes.is_empty() {
+            None
+        } else {
+            Some(self.processes.remove(0))
+        }
+    }
+
+    fn policy(&self) -> Result<&ScriptPolicy, SimError> {
+        self.policy.as_deref().ok_or(SimError::Script {
+            script: String::from("SCRIPT"),
+            function: String::from("pick_next"),
+            message: format!("queue {} has no scheduling script", self.queue_number),
+        })
+    }
+
+    // a field of its own, so the plugin can be borrowed along with the processes
+    fn plugin(plugin: &mut Option<PluginInstance>, queue: i8) -> Result<&mut PluginInstance, SimError> {
+        plugin.as_mut().ok_or(SimError::Plugin {
+            plugin: String::from("PLUGIN"),
+            function: String::from("pick_next"),
+            message: format!("queue {} has no plugin", queue),
+        })
+    }
+
+    /// The process to run next, the script's or the plugin's pick when the discipline is
+    /// SCRIPT or PLUGIN.
+    fn pick_next(&mut self, recorder: &Recorder) -> Result<Option<Process>, SimError> {
+        if self.processes.is_empty() {
+            return Ok(None);
+        }
+        let (start, now) = (recorder.clock.start(), recorder.clock.now());
+        let index = match self.discipline {
+            QueueDiscipline::SCRIPT => self.policy()?.pick_next(&self.processes, start, now)?,
+            QueueDiscipline::PLUGIN => Self::plugin(&mut self.plugin, self.queue_number)?
+                .pick_next(&self.processes, start, now)?,
+            _ => return Ok(self.dequeue(now)),
+        };
+        Ok(Some(self.processes.remove(index)))
+    }
+
+    fn preempts(&mut self, process: &Process, recorder: &Recorder) -> Result<bool, SimError> {
+        let (start, now) = (recorder.clock.start(), recorder.clock.now());
+        match self.discipline {
+            QueueDiscipline::SCRIPT => self.policy()?.preempt(process, start, now),
+            QueueDiscipline::PLUGIN => {
+                Self::plugin(&mut self.plugin, self.queue_number)?.on_tick(process, start, now)
+            }
+            _ => Ok(self.is_preemptive()),
+        }
+    }
+
+    fn context_switch(&self, recorder: &mut Recorder) {
+        recorder.record(SimEvent::ContextSwitch {
+            duration: self.context_switch,
+        });
+        recorder.clock.advance(self.context_switch); // simulating context_switch
+    }
+
+    /// Runs the next process for one slice. A process MLFQ has to move to another level comes
+    /// back instead of going back into this queue.
+    pub fn execute_next(
+        &mut self,
+        recorder: &mut Recorder,
+        is_mlfq: bool,
+    ) -> Result<Option<Process>, SimError> {
+        // the queue as it was before the pick, to explain it with
+        let candidates = recorder.is_recording().then(|| self.processes.clone());
+        if let Some(mut process) = self.pick_next(recorder)? {
+            if process.processed_time == process.cpu_burst_time {
+                return Ok(None);
+            }
+            process.status = ProcessStatus::Running;
+            recorder.record(SimEvent::Dispatch {
+                id: process.id,
+                queue: self.queue_number,
+            });
+            if let Some(candidates) = candidates {
+                let (start, now) = (recorder.clock.start(), recorder.clock.now());
+                let decision = Decision::dispatch(
+                    self.queue_number,
+                    self.discipline,
+                    &candidates,
+                    &process,
+                    start,
+                    now,
+                );
+                recorder.emit(decision::EVENT, decision);
+            }
+
+            if self.preempts(&process, recorder)? {
+                process.run_with_interrupt(&self.time_quantum, &mut recorder.clock)?;
+            } else {
+                process.run(&mut recorder.clock)?;
+            }
+
+            recorder.emit(
+                "process_stopped",
+                (self.queue_number, process.clone().to_serializable()),
+            );
+            if let Some(io) = process.pending_io() {
+                // the process blocks on I/O and comes back to this same queue once
+                // it's done, so an I/O request never counts as a demotion in MLFQ.
+                process.status = ProcessStatus::Waiting;
+                process.completed_io += 1;
+                recorder.record(SimEvent::IoStart {
+                    id: process.id,
+                    queue: self.queue_number,
+                    duration: io.duration,
+                });
+                self.blocked.push((recorder.clock.now() + io.duration, process));
+                self.context_switch(recorder);
+                return Ok(None);
+            }
+            return if process.processed_time == process.cpu_burst_time {
+                process.status = ProcessStatus::Terminated;
+                recorder.record(SimEvent::Completion {
+                    id: process.id,
+                    queue: self.queue_number,
+                });
+                self.finished_processes.push(process.clone());
+                let data_to_be_sent: Vec<SerializableProcess> = self
+                    .finished_processes
+                    .iter()
+                    .map(|p| p.to_serializable())
+                    .collect();
+                recorder.emit("finished_process", data_to_be_sent);
+                self.context_switch(recorder);
+                Ok(None)
+            } else {
+                process.status = ProcessStatus::Waiting;
+                recorder.record(SimEvent::Preemption {
+                    id: process.id,
+                    queue: self.queue_number,
+                    remaining: process.cpu_burst_time.saturating_sub(process.processed_time),
+                });
+                if recorder.is_recording() {
+                    let decision = Decision::preemption(
+                        self.queue_number,
+                        self.discipline,
+                        &process,
+                        self.time_quantum,
+                        !is_mlfq,
+                    );
+                    recorder.emit(decision::EVENT, decision);
+                }
+                let copy = process.clone();
+                if !is_mlfq {
+                    self.processes.push(process);
+                }
+                self.context_switch(recorder);
+                Ok(Some(copy))
+            };
+        }
+        // nothing was ready, the simulator checks has_ready first so it doesn't come to this
+        Ok(None)
+    }
+
+    pub fn calculate_metrics(&self) -> HashMap<String, MetricValue> {
+        calculate_metrics(&self.finished_processes, self.discipline.to_string())
+    }
+
+    pub fn wake_blocked(&mut self, recorder: &mut Recorder) {
+        // moves every process whose I/O has finished back into the ready queue
+        let right_now = recorder.clock.now();
+        let mut index = 0;
+        while index < self.blocked.len() {
+            if self.blocked[index].0 <= right_now {
+                let (io_end, mut process) = self.blocked.remove(index);
+                process.ready_since = Some(io_end); // time spent on I/O isn't waiting time
+                recorder.record(SimEvent::IoEnd {
+                    id: process.id,
+                    queue: self.queue_number,
+                });
+                self.enqueue(process);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    pub fn has_ready(&self) -> bool {
+        !self.processes.is_empty()
+    }
+
+    pub fn ready(&self) -> &[Process] {
+        &self.processes
+    }
+
+    pub fn ready_len(&self) -> usize {
+        self.processes.len()
+    }
+
+    pub fn blocked_len(&self) -> usize {
+        self.blocked.len()
+    }
+
+    /// When the next blocked process finishes its I/O, so an idle CPU knows how long to wait.
+    pub fn next_wake(&self) -> Option<SystemTime> {
+        self.blocked.iter().map(|(io_end, _)| *io_end).min()
+    }
+
+    pub fn finished(&self) -> &[Process] {
+        &self.finished_processes
+    }
+
+    pub fn is_queue_empty(&self) -> bool {
+        self.processes.is_empty() && self.blocked.is_empty()
+    }
+
+    pub fn get_data(&self) -> Vec<SerializableProcess> {
+        self.processes.iter().map(|p| p.to_serializable()).collect()
+    }
+}
+
+// MultiLevel Queue --------------------------------------------------------------------------------
+
+pub struct MLQ {
+    pub queue_1: ReadyQueue,
+    pub queue_2: ReadyQueue,
+    pub queue_3: ReadyQueue,
+    pub queue_4: ReadyQueue,
+}
+
+impl MLQ {
+    pub fn init(
+        q1_d: QueueDiscipline,
+        q2_d: QueueDiscipline,
+        q3_d: QueueDiscipline,
+        q4_d: QueueDiscipline,
+        context_switch: Duration,
+        time_quantum: Duration,
+    ) -> Self {
+        let mut q1 = ReadyQueue::new(q1_d, context_switch, time_quantum);
+        q1.queue_number = 1;
+        let mut q2 = ReadyQueue::new(q2_d, context_switch, time_quantum);
+        q2.queue_number = 2;
+        let mut q3 = ReadyQueue::new(q3_d, context_switch, time_quantum);
+        q3.queue_number = 3;
+        let mut q4 = ReadyQueue::new(q4_d, context_switch, time_quantum);
+        q4.queue_number = 4;
+
+        MLQ {
+            queue_1: q1,
+            queue_2: q2,
+            queue_3: q3,
+            queue_4: q4,
+        }
+    }
+    /// Gives `policy` to every level, the SCRIPT ones use it.
+    pub fn set_policy(&mut self, policy: Arc<ScriptPolicy>) {
+        for level in [&mut self.queue_1, &mut self.queue_2, &mut self.queue_3, &mut self.queue_4] {
+            level.set_policy(policy.clone());
+        }
+    }
+
+    /// Starts `plugin` on every PLUGIN level, each with an instance of its own.
+    pub fn set_plugin(&mut self, plugin: &Arc<PluginPolicy>) -> Result<(), SimError> {
+        for level in [&mut self.queue_1, &mut self.queue_2, &mut self.queue_3, &mut self.queue_4] {
+            level.set_plugin(plugin)?;
+        }
+        Ok(())
+    }
+
+    pub fn enqueue(&mut self, process: Process) {
+        match process.process_type {
+            ProcessType::SystemProcess => self.queue_1.enqueue(process),
+            ProcessType::InteractiveProcess => self.queue_2.enqueue(process),
+            ProcessType::BatchProcess => self.queue_3.enqueue(process),
+            ProcessType::StudentProcess => self.queue_4.enqueue(process),
+        }
+    }
+
+    pub fn execute_next(&mut self, recorder: &mut Recorder) -> Result<(), SimError> {
+        if !self.queue_1.processes.is_empty() {
+            self.queue_1.execute_next(recorder, false)?;
+        } else if !self.queue_2.processes.is_empty() {
+            self.queue_2.execute_next(recorder, false)?;
+        } else if !self.queue_3.processes.is_empty() {
+            self.queue_3.execute_next(recorder, false)?;
+        } else if !self.queue_4.processes.is_empty() {
+            self.queue_4.execute_next(recorder, false)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_queue_empty(&self) -> bool {
+        self.queue_1.is_queue_empty()
+            && self.queue_2.is_queue_empty()
+            && self.queue_3.is_queue_empty()
+            && self.queue_4.is_queue_empty()
+    }
+
+    pub fn has_ready(&self) -> bool {
+        self.queue_1.has_ready()
+            || self.queue_2.has_ready()
+            || self.queue_3.has_ready()
+            || self.queue_4.has_ready()
+    }
+
+    pub fn wake_blocked(&mut self, recorder: &mut Recorder) {
+        self.queue_1.wake_blocked(recorder);
+        self.queue_2.wake_blocked(recorder);
+        self.queue_3.wake_blocked(recorder);
+        self.queue_4.wake_blocked(recorder);
+    }
+
+    pub fn next_wake(&self) -> Option<SystemTime> {
+        [&self.queue_1, &self.queue_2, &self.queue_3, &self.queue_4]
+            .iter()
+            .filter_map(|q| q.next_wake())
+            .min()
+    }
+
+    pub fn finished(&self) -> Vec<Process> {
+        [&self.queue_1, &self.queue_2, &self.queue_3, &self.queue_4]
+            .iter()
+            .flat_map(|q| q.finished().iter().cloned())
+            .collect()
+    }
+
+    pub fn calculate_metric(&self) -> [HashMap<String, MetricValue>; 4] {
+        let m1 = self.queue_1.calculate_metrics();
+        let m2 = self.queue_2.calculate_metrics();
+        let m3 = self.queue_3.calculate_metrics();
+        let m4 = self.queue_4.calculate_metrics();
+
+        [m1, m2, m3, m4]
+    }
+}
+
+// MultiLevel FeedBack Algorithm -------------------------------------------------------------------
+
+pub struct MLFQ {
+    pub queue_1: ReadyQueue,
+    pub queue_2: ReadyQueue,
+    pub queue_3: ReadyQueue,
+    pub queue_4: ReadyQueue,
+    pub boost_period: Option<Duration>, // every period all ready processes go back to queue 1
+    last_boost: Option<SystemTime>,
+}
+
+/// Records `process` moving from level `from` to level `to`, explained when anyone sees it.
+fn record_level_change(recorder: &mut Recorder, process: &Process, from: i8, to: i8) {
+    let id = process.id;
+    recorder.record(match to > from {
+        true => SimEvent::Demotion { id, from, to },
+        false => SimEvent::Promotion { id, from, to },
+    });
+    if recorder.is_recording() {
+        recorder.emit(decision::EVENT, Decision::level_change(process, from, to));
+    }
+}
+
+impl MLFQ {
+    pub fn init(
+        q1_d: QueueDiscipline,
+        q2_d: QueueDiscipline,
+        q3_d: QueueDiscipline,
+        q4_d: QueueDiscipline,
+        context_switch: Duration,
+        time_quantum: Duration,
+    ) -> Self {
+        let mut q1 = ReadyQueue::new(q1_d, context_switch, time_quantum);
+        q1.queue_number = 1;
+        let mut q2 = ReadyQueue::new(q2_d, context_switch, time_quantum);
+        q2.queue_number = 2;
+        let mut q3 = ReadyQueue::new(q3_d, context_switch, time_quantum);
+        q3.queue_number = 3;
+        let mut q4 = ReadyQueue::new(q4_d, context_switch, time_quantum);
+        q4.queue_number = 4;
+        MLFQ {
+            queue_1: q1,
+            queue_2: q2,
+            queue_3: q3,
+            queue_4: q4,
+            boost_period: None,
+            last_boost: None,
+        }
+    }
+
+    /// Gives `policy` to every level, the SCRIPT ones use it.
+    pub fn set_policy(&mut self, policy: Arc<ScriptPolicy>) {
+        for level in [&mut self.queue_1, &mut self.queue_2, &mut self.queue_3, &mut self.queue_4] {
+            level.set_policy(policy.clone());
+        }
+    }
+
+    /// Starts `plugin` on every PLUGIN level, each with an instance of its own.
+    pub fn set_plugin(&mut self, plugin: &Arc<PluginPolicy>) -> Result<(), SimError> {
+        for level in [&mut self.queue_1, &mut self.queue_2, &mut self.queue_3, &mut self.queue_4] {
+            level.set_plugin(plugin)?;
+        }
+        Ok(())
+    }
+
+    pub fn enqueue(&mut self, process: Process) {
+        self.queue_1.enqueue(process);
+    }
+
+    fn boost(&mut self, period: Duration, recorder: &mut Recorder) {
+        // the priority boost keeps long processes stuck in the lower levels from starving
+        let levels = [&mut self.queue_2, &mut self.queue_3, &mut self.queue_4];
+        for level in levels {
+            let from = level.queue_number;
+            for process in level.processes.drain(..) {
+                recorder.record(SimEvent::Promotion { id: process.id, from, to: 1 });
+                if recorder.is_recording() {
+                    recorder.emit(decision::EVENT, Decision::boost(&process, from, period));
+                }
+                self.queue_1.processes.push(process);
+            }
+        }
+    }
+
+    pub fn execute_next(&mut self, recorder: &mut Recorder) -> Result<(), SimError> {
+        if let Some(period) = self.boost_period {
+            let right_now = recorder.clock.now();
+            let last_boost = *self.last_boost.get_or_insert(right_now);
+            if right_now.duration_since(last_boost).unwrap_or_default() >= period {
+                self.boost(period, recorder);
+                self.last_boost = Some(right_now);
+            }
+        }
+        if !self.queue_1.processes.is_empty() {
+            let process = self.queue_1.execute_next(recorder, true)?;
+            if let Some(process) = process {
+                record_level_change(recorder, &process, 1, 2);
+                self.queue_2.enqueue(process)
+            }
+        } else if !self.queue_2.processes.is_empty() {
+            let process = self.queue_2.execute_next(recorder, true)?;
+            if let Some(process) = process {
+                if process.waiting_time >= process.cpu_burst_time {
+                    record_level_change(recorder, &process, 2, 1);
+                    self.queue_1.enqueue(process)
+                } else {
+                    record_level_change(recorder, &process, 2, 3);
+                    self.queue_3.enqueue(process)
+                }
+            }
+        } else if !self.queue_3.processes.is_empty() {
+            let process = self.queue_3.execute_next(recorder, true)?;
+            if let Some(process) = process {
+                if process.waiting_time >= process.cpu_burst_time {
+                    record_level_change(recorder, &process, 3, 2);
+                    self.queue_2.enqueue(process)
+                } else {
+                    record_level_change(recorder, &process, 3, 4);
+                    self.queue_4.enqueue(process)
+                }
+            }
+        } else if !self.queue_4.processes.is_empty() {
+            let process = self.queue_4.execute_next(recorder, true)?;
+            if let Some(process) = process {
+                if process.waiting_time >= process.cpu_burst_time {
+                    record_level_change(recorder, &process, 4, 3);
+                    self.queue_3.enqueue(process)
+                } else {
+                    self.queue_4.enqueue(process)
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn calculate_metric(&self) -> [HashMap<String, MetricValue>; 4] {
+        let m1 = self.queue_1.calculate_metrics();
+        let m2 = self.queue_2.calculate_metrics();
+        let m3 = self.queue_3.calculate_metrics();
+        let m4 = self.queue_4.calculate_metrics();
+
+        [m1, m2, m3, m4]
+    }
+
+    pub fn is_queue_empty(&self) -> bool {
+        self.queue_1.is_queue_empty()
+            && self.queue_2.is_queue_empty()
+            && self.queue_3.is_queue_empty()
+            && self.queue_4.is_queue_empty()
+    }
+
+    pub fn has_ready(&self) -> bool {
+        self.queue_1.has_ready()
+            || self.queue_2.has_ready()
+            || self.queue_3.has_ready()
+            || self.queue_4.has_ready()
+    }
+
+    pub fn wake_blocked(&mut self, recorder: &mut Recorder) {
+        self.queue_1.wake_blocked(recorder);
+        self.queue_2.wake_blocked(recorder);
+        self.queue_3.wake_blocked(recorder);
+        self.queue_4.wake_blocked(recorder);
+    }
+
+    pub fn next_wake(&self) -> Option<SystemTime> {
+        [&self.queue_1, &self.queue_2, &self.queue_3, &self.queue_4]
+            .iter()
+            .filter_map(|q| q.next_wake())
+            .min()
+    }
+
+    pub fn finished(&self) -> Vec<Process> {
+        [&self.queue_1, &self.queue_2, &self.queue_3, &self.queue_4]
+            .iter()
+            .flat_map(|q| q.finished().iter().cloned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished(process_type: ProcessType, burst: u64, waiting: u64) -> Process {
+        let mut process = Process::new(
+            Duration::from_millis(burst),
+            SystemTime::UNIX_EPOCH,
+            Some(process_type),
+        );
+        process.metrics.total_waiting_time = Duration::from_millis(waiting);
+        process.metrics.total_time = Duration::from_millis(burst + waiting);
+        process
+    }
+
+    #[test]
+    fn fairness_and_waiting_per_type() {
+        // slowdowns of 1 and 3, CPU shares of 1 and 1/3, Jain's index is 0.8 for both
+        let processes = [
+            finished(ProcessType::BatchProcess, 10, 0),
+            finished(ProcessType::InteractiveProcess, 10, 20),
+        ];
+        let metrics = calculate_metrics(&processes, String::from("RR"));
+        let value = |name: &str| metrics.get(name).and_then(MetricValue::as_f64);
+        assert!((value("jain_fairness_slowdown").unwrap() - 0.8).abs() < 1e-9);
+        assert!((value("jain_fairness_cpu_share").unwrap() - 0.8).abs() < 1e-9);
+        assert_eq!(value("max_waiting_time"), Some(20.0));
+        assert_eq!(value("max_waiting_time_batch"), Some(0.0));
+        assert_eq!(value("max_waiting_time_interactive"), Some(20.0));
+        // a type none of the processes have gets no entry
+        assert_eq!(value("max_waiting_time_system"), None);
+        assert_eq!(value("max_waiting_time_student"), None);
+    }
+}
@@ -0,0 +1,599 @@
+use crate::events::{EventLog, SimEvent};
+use crate::process_gen::SerializableProcess;
+use crate::queue_engine::MetricValue;
+use crate::util::millis;
+use crate::sampling::{LittlesLaw, QueueSample};
+use crate::sched_trace::TimelineSegment;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::time::Duration;
+use uuid::Uuid;
+
+// Utils -------------------------------------------------------------------------------------------
+
+// same palette the Gantt chart in the UI uses
+const COLORS: [&str; 20] = [
+    "#F38181", "#FCE38A", "#EAFFD0", "#95E1D3", "#A8D8EA", "#AA96DA", "#FC5185", "#3FC1C9",
+    "#FFDD59", "#FF5722", "#C1C8E4", "#FFD460", "#B8DE6F", "#FF6B6B", "#6A0572", "#FFE5B4",
+    "#9DDCDC", "#FFB6C1", "#F8B195", "#355C7D",
+];
+
+const LABEL_WIDTH: f64 = 90.0;
+const CHART_WIDTH: f64 = 1000.0;
+const LANE_HEIGHT: f64 = 32.0;
+const AXIS_HEIGHT: f64 = 30.0;
+const PLOT_HEIGHT: f64 = 200.0;
+
+/// One bar of the chart. Bars of the same lane are drawn on the same row.
+#[derive(Debug, Clone)]
+pub struct GanttBar {
+    pub lane: String,
+    pub label: String,
+    pub start: Duration,
+    pub end: Duration,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn format_metric(value: &MetricValue) -> String {
+    match value {
+        MetricValue::DurationValue(d) => format!("{:.3} ms", millis(*d)),
+        MetricValue::PercentageValue(p) => format!("{:.2}%", p),
+        MetricValue::IntegerValue(i) => i.to_string(),
+        MetricValue::StringValue(s) => s.clone(),
+        MetricValue::FloatValue(f) => format!("{:.4}", f),
+    }
+}
+
+fn text_color(hex: &str) -> &'static str {
+    // black text on bright bars, white on dark ones, like getContrastColor in the UI
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0) as f64;
+    let luminance = 0.299 * channel(1) + 0.587 * channel(3) + 0.114 * channel(5);
+    if luminance > 180.0 {
+        "#000"
+    } else {
+        "#fff"
+    }
+}
+
+fn tick_step(total_ms: f64) -> f64 {
+    // a 1 / 2 / 5 step that gives roughly ten ticks on the axis
+    let raw = (total_ms / 10.0).max(0.001);
+    let magnitude = 10f64.powf(raw.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|step| *step >= raw)
+        .unwrap_or(raw)
+}
+
+// Building Bars -----------------------------------------------------------------------------------
+
+fn process_labels(log: &EventLog) -> HashMap<Uuid, String> {
+    // imported processes have names, generated ones get the first 8 characters of their id
+    // just like the UI shows them
+    log.events
+        .iter()
+        .filter_map(|e| match &e.event {
+            SimEvent::Arrival { process } => Some((
+                process.id,
+                process
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| process.id.to_string()[..8].to_string()),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Turns the dispatches of a run into bars. Every run gets a CPU lane; runs of MLQ / MLFQ also
+/// get a lane per queue level so you can see which level each slice came from.
+pub fn bars_from_log(log: &EventLog) -> Vec<GanttBar> {
+    let labels = process_labels(log);
+    let multilevel = log.events.iter().any(|e| match &e.event {
+        SimEvent::Dispatch { queue, .. } => *queue != 1,
+        SimEvent::RunStarted { queue, .. } => queue == "MLQ" || queue == "MLFQ",
+        _ => false,
+    });
+
+    let mut running: HashMap<Uuid, (Duration, i8)> = HashMap::new();
+    let mut bars = Vec::new();
+    for logged in &log.events {
+        let stopped = match &logged.event {
+            SimEvent::Dispatch { id, queue } => {
+                running.insert(*id, (logged.time, *queue));
+                None
+            }
+            SimEvent::Preemption { id, .. }
+            | SimEvent::IoStart { id, .. }
+            | SimEvent::Completion { id, .. } => Some(*id),
+            _ => None,
+        };
+        if let Some((id, (start, queue))) = stopped.and_then(|id| running.remove_entry(&id)) {
+            let label = labels.get(&id).cloned().unwrap_or_else(|| id.to_string()[..8].to_string());
+            bars.push(GanttBar {
+                lane: String::from("CPU"),
+                label: label.clone(),
+                start,
+                end: logged.time,
+            });
+            if multilevel {
+                bars.push(GanttBar {
+                    lane: format!("Queue {}", queue),
+                    label,
+                    start,
+                    end: logged.time,
+                });
+            }
+        }
+    }
+    bars
+}
+
+/// Bars of the kernel's own schedule from an imported sched trace, one lane per core.
+pub fn bars_from_timeline(timeline: &[TimelineSegment]) -> Vec<GanttBar> {
+    timeline
+        .iter()
+        .map(|segment| GanttBar {
+            lane: format!("CPU {}", segment.cpu),
+            label: format!("{}-{}", segment.name, segment.pid),
+            start: segment.start,
+            end: segment.end,
+        })
+        .collect()
+}
+
+// Rendering ---------------------------------------------------------------------------------------
+
+/// "CPU" lanes first, then the queue levels, lanes named alike in the order of their number so
+/// "CPU 2" comes before "CPU 10".
+fn lanes(bars: &[GanttBar]) -> Vec<&str> {
+    let mut lanes: Vec<&str> = Vec::new();
+    for bar in bars {
+        if !lanes.contains(&bar.lane.as_str()) {
+            lanes.push(&bar.lane);
+        }
+    }
+    lanes.sort_by_key(|lane| {
+        let (name, number) = match lane.rsplit_once(' ').map(|(n, i)| (n, i.parse::<u64>())) {
+            Some((name, Ok(number))) => (name, number),
+            _ => (*lane, 0),
+        };
+        (!lane.starts_with("CPU"), name, number)
+    });
+    lanes
+}
+
+/// Draws a standalone SVG Gantt chart, it doesn't load any fonts or scripts.
+pub fn render_svg(bars: &[GanttBar]) -> String {
+    let lanes = lanes(bars);
+
+    let mut colors: HashMap<&str, &str> = HashMap::new();
+    for bar in bars {
+        let next = COLORS[colors.len() % COLORS.len()];
+        colors.entry(bar.label.as_str()).or_insert(next);
+    }
+
+    let total_ms = bars.iter().map(|b| millis(b.end)).fold(0.0, f64::max).max(0.001);
+    let scale = CHART_WIDTH / total_ms;
+    let width = LABEL_WIDTH + CHART_WIDTH + 20.0;
+    let height = lanes.len() as f64 * LANE_HEIGHT + AXIS_HEIGHT + 10.0;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="11">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(svg, r##"<rect width="100%" height="100%" fill="#ffffff"/>"##);
+
+    for (index, lane) in lanes.iter().enumerate() {
+        let y = index as f64 * LANE_HEIGHT;
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="{fill}"/>"#,
+            x = LABEL_WIDTH,
+            y = y,
+            w = CHART_WIDTH,
+            h = LANE_HEIGHT,
+            fill = if index % 2 == 0 { "#f5f5f5" } else { "#ebebeb" }
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="6" y="{y}" dominant-baseline="middle">{lane}</text>"#,
+            y = y + LANE_HEIGHT / 2.0,
+            lane = escape(lane)
+        );
+    }
+
+    for bar in bars {
+        let lane = lanes.iter().position(|l| *l == bar.lane).unwrap_or(0);
+        let x = LABEL_WIDTH + millis(bar.start) * scale;
+        let w = ((millis(bar.end) - millis(bar.start)) * scale).max(1.0);
+        let y = lane as f64 * LANE_HEIGHT + 4.0;
+        let color = colors.get(bar.label.as_str()).copied().unwrap_or(COLORS[0]);
+        let _ = writeln!(
+            svg,
+            r##"<g><title>{label}: {start:.3} ms - {end:.3} ms</title><rect x="{x:.2}" y="{y}" width="{w:.2}" height="{h}" rx="3" fill="{color}" stroke="#333" stroke-width="0.5"/>"##,
+            label = escape(&bar.label),
+            start = millis(bar.start),
+            end = millis(bar.end),
+            x = x,
+            y = y,
+            w = w,
+            h = LANE_HEIGHT - 8.0,
+            color = color
+        );
+        // only label bars that have room for it
+        if w > bar.label.len() as f64 * 6.5 + 4.0 {
+            let _ = write!(
+                svg,
+                r#"<text x="{x:.2}" y="{y}" text-anchor="middle" dominant-baseline="middle" fill="{fill}">{label}</text>"#,
+                x = x + w / 2.0,
+                y = y + (LANE_HEIGHT - 8.0) / 2.0,
+                fill = text_color(color),
+                label = escape(&bar.label)
+            );
+        }
+        let _ = writeln!(svg, "</g>");
+    }
+
+    let axis_y = lanes.len() as f64 * LANE_HEIGHT;
+    let _ = writeln!(
+        svg,
+        r##"<line x1="{x1}" y1="{y}" x2="{x2}" y2="{y}" stroke="#333"/>"##,
+        x1 = LABEL_WIDTH,
+        x2 = LABEL_WIDTH + CHART_WIDTH,
+        y = axis_y
+    );
+    let step = tick_step(total_ms);
+    let mut tick = 0.0;
+    while tick <= total_ms + step * 0.001 {
+        let x = LABEL_WIDTH + tick * scale;
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{x:.2}" y1="{y1}" x2="{x:.2}" y2="{y2}" stroke="#333"/><text x="{x:.2}" y="{ty}" text-anchor="middle">{tick}</text>"##,
+            x = x,
+            y1 = axis_y,
+            y2 = axis_y + 5.0,
+            ty = axis_y + 17.0,
+            tick = (tick * 1000.0).round() / 1000.0
+        );
+        tick += step;
+    }
+    let _ = writeln!(
+        svg,
+        r#"<text x="{x}" y="{y}" text-anchor="end">ms</text>"#,
+        x = LABEL_WIDTH + CHART_WIDTH + 16.0,
+        y = axis_y + 17.0
+    );
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Draws the number in system and the number of ready processes over the run as step lines.
+pub fn render_samples_svg(samples: &[QueueSample]) -> String {
+    let total_ms = samples.last().map(|s| millis(s.time)).unwrap_or(0.0).max(0.001);
+    let highest = samples.iter().map(|s| s.in_system).max().unwrap_or(0).max(1) as f64;
+    let scale_x = CHART_WIDTH / total_ms;
+    let scale_y = PLOT_HEIGHT / highest;
+    let width = LABEL_WIDTH + CHART_WIDTH + 20.0;
+    let height = PLOT_HEIGHT + AXIS_HEIGHT + 20.0;
+    let top = 10.0;
+    let bottom = top + PLOT_HEIGHT;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="11">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(svg, r##"<rect width="100%" height="100%" fill="#ffffff"/>"##);
+    for (index, (name, color)) in [("in system", "#3FC1C9"), ("ready", "#FC5185")].iter().enumerate() {
+        let value = |sample: &QueueSample| match index {
+            0 => sample.in_system,
+            _ => sample.ready.iter().sum(),
+        };
+        let mut points = String::new();
+        let mut previous: Option<f64> = None;
+        for sample in samples {
+            let x = LABEL_WIDTH + millis(sample.time) * scale_x;
+            let y = bottom - value(sample) as f64 * scale_y;
+            if let Some(previous) = previous {
+                let _ = write!(points, "{:.2},{:.2} ", x, previous);
+            }
+            let _ = write!(points, "{:.2},{:.2} ", x, y);
+            previous = Some(y);
+        }
+        let _ = writeln!(
+            svg,
+            r#"<polyline points="{points}" fill="none" stroke="{color}" stroke-width="1.5"/>"#,
+            points = points.trim_end(),
+            color = color
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="6" y="{y}" fill="{color}">{name}</text>"#,
+            y = top + 12.0 + index as f64 * 14.0,
+            color = color,
+            name = name
+        );
+    }
+
+    let _ = writeln!(
+        svg,
+        r##"<line x1="{x}" y1="{top}" x2="{x}" y2="{bottom}" stroke="#333"/><text x="{tx}" y="{ty}" text-anchor="end">{highest}</text><text x="{tx}" y="{bottom}" text-anchor="end">0</text>"##,
+        x = LABEL_WIDTH,
+        tx = LABEL_WIDTH - 4.0,
+        ty = top + 4.0,
+        top = top,
+        bottom = bottom,
+        highest = highest
+    );
+    let _ = writeln!(
+        svg,
+        r##"<line x1="{x1}" y1="{y}" x2="{x2}" y2="{y}" stroke="#333"/>"##,
+        x1 = LABEL_WIDTH,
+        x2 = LABEL_WIDTH + CHART_WIDTH,
+        y = bottom
+    );
+    let step = tick_step(total_ms);
+    let mut tick = 0.0;
+    while tick <= total_ms + step * 0.001 {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{x:.2}" y="{y}" text-anchor="middle">{tick}</text>"#,
+            x = LABEL_WIDTH + tick * scale_x,
+            y = bottom + 17.0,
+            tick = (tick * 1000.0).round() / 1000.0
+        );
+        tick += step;
+    }
+    let _ = writeln!(
+        svg,
+        r#"<text x="{x}" y="{y}" text-anchor="end">ms</text>"#,
+        x = LABEL_WIDTH + CHART_WIDTH + 16.0,
+        y = bottom + 17.0
+    );
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn samples_from_log(log: &EventLog) -> (Vec<QueueSample>, Option<LittlesLaw>) {
+    let mut samples = Vec::new();
+    let mut littles_law = None;
+    for logged in &log.events {
+        if let SimEvent::Emit { event, payload } = &logged.event {
+            match event.as_str() {
+                "queue_sample" => samples.extend(serde_json::from_value(payload.clone()).ok()),
+                "littles_law" => littles_law = serde_json::from_value(payload.clone()).ok(),
+                _ => {}
+            }
+        }
+    }
+    (samples, littles_law)
+}
+
+fn metrics_from_log(log: &EventLog) -> Vec<(String, HashMap<String, MetricValue>)> {
+    // the metrics the UI was sent at the end of the run, one table per queue level for MLQ / MLFQ
+    let mut tables = Vec::new();
+    for logged in &log.events {
+        if let SimEvent::Emit { event, payload } = &logged.event {
+            match event.as_str() {
+                "send_metrics" => {
+                    if let Ok(metrics) = serde_json::from_value(payload.clone()) {
+                        tables.push((String::from("Overall"), metrics));
+                    }
+                }
+                "send_metrics_mlq" | "send_metrics_mlfq" => {
+                    if let Ok(levels) =
+                        serde_json::from_value::<Vec<HashMap<String, MetricValue>>>(payload.clone())
+                    {
+                        for (index, metrics) in levels.into_iter().enumerate() {
+                            tables.push((format!("Queue {}", index + 1), metrics));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    tables
+}
+
+fn finished_from_log(log: &EventLog) -> Vec<SerializableProcess> {
+    // every finished_process event has the finished processes of one queue so far, the last
+    // copy of each process is the final one
+    let mut finished: BTreeMap<String, SerializableProcess> = BTreeMap::new();
+    for logged in &log.events {
+        if let SimEvent::Emit { event, payload } = &logged.event {
+            if event == "finished_process" {
+                if let Ok(processes) =
+                    serde_json::from_value::<Vec<SerializableProcess>>(payload.clone())
+                {
+                    for process in processes {
+                        finished.insert(process.id.to_string(), process);
+                    }
+                }
+            }
+        }
+    }
+    let mut finished: Vec<SerializableProcess> = finished.into_values().collect();
+    finished.sort_by_key(|p| p.arrival_time);
+    finished
+}
+
+/// A single HTML file with the chart, the metrics tables and every finished process, meant to
+/// be attached to lab reports or kept as a CI artifact.
+pub fn render_html(title: &str, log: &EventLog) -> String {
+    let bars = bars_from_log(log);
+    let started_at = log.events.iter().find_map(|e| match &e.event {
+        SimEvent::RunStarted { started_at, .. } => Some(*started_at),
+        _ => None,
+    });
+    let labels = process_labels(log);
+
+    let mut html = String::new();
+    let _ = writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>",
+        escape(title)
+    );
+    html.push_str(
+        "<style>\nbody { font-family: sans-serif; margin: 2em; color: #222; }\n\
+         table { border-collapse: collapse; margin: 1em 0 2em 0; }\n\
+         th, td { border: 1px solid #ccc; padding: 4px 10px; text-align: right; }\n\
+         th { background: #f0f0f0; }\n\
+         td:first-child, th:first-child { text-align: left; }\n\
+         .chart { overflow-x: auto; }\n</style>\n</head>\n<body>\n",
+    );
+    let _ = writeln!(html, "<h1>{}</h1>", escape(title));
+    if let Some(started_at) = started_at {
+        let _ = writeln!(html, "<p>Run started at {}</p>", started_at.to_rfc3339());
+    }
+    let _ = writeln!(html, "<h2>Gantt Chart</h2>\n<div class=\"chart\">\n{}</div>", render_svg(&bars));
+
+    let (samples, littles_law) = samples_from_log(log);
+    if !samples.is_empty() {
+        let _ = writeln!(
+            html,
+            "<h2>Queue Length</h2>\n<div class=\"chart\">\n{}</div>",
+            render_samples_svg(&samples)
+        );
+    }
+    if let Some(littles_law) = littles_law {
+        let _ = writeln!(
+            html,
+            "<p>Little's law: L = {:.3} sampled, λW = {:.3}/s × {:.3} s = {:.3}{}</p>",
+            littles_law.average_in_system,
+            littles_law.arrival_rate,
+            littles_law.average_turnaround_time / 1000.0,
+            littles_law.expected_in_system,
+            littles_law.relative_error.map_or(String::new(), |e| format!(" ({:+.1}%)", e * 100.0))
+        );
+    }
+
+    let _ = writeln!(html, "<h2>Metrics</h2>");
+    for (name, metrics) in metrics_from_log(log) {
+        let _ = writeln!(html, "<h3>{}</h3>\n<table>\n<tr><th>Metric</th><th>Value</th></tr>", escape(&name));
+        let mut keys: Vec<&String> = metrics.keys().collect();
+        keys.sort();
+        for key in keys {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td></tr>",
+                escape(key),
+                escape(&format_metric(&metrics[key]))
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    let _ = writeln!(
+        html,
+        "<h2>Processes</h2>\n<table>\n<tr><th>Process</th><th>Type</th><th>Arrival (ms)</th>\
+         <th>Burst (ms)</th><th>Waiting (ms)</th><th>Response (ms)</th><th>Turnaround (ms)</th></tr>"
+    );
+    for process in finished_from_log(log) {
+        let arrival = started_at
+            .and_then(|s| (process.arrival_time - s).to_std().ok())
+            .unwrap_or(Duration::from_secs(0));
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{:?}</td><td>{:.3}</td><td>{:.3}</td><td>{:.3}</td><td>{:.3}</td><td>{:.3}</td></tr>",
+            escape(labels.get(&process.id).map(|l| l.as_str()).unwrap_or("?")),
+            process.process_type,
+            millis(arrival),
+            millis(process.cpu_burst_time),
+            millis(process.metrics.total_waiting_time),
+            millis(process.metrics.response_time),
+            millis(process.metrics.total_time)
+        );
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queue_engine::{QueueDiscipline, Queues};
+    use crate::scenario::{ProcessSource, Scenario};
+    use crate::workload::WorkloadEntry;
+
+    fn bar(lane: &str) -> GanttBar {
+        GanttBar {
+            lane: lane.to_string(),
+            label: String::from("P1"),
+            start: Duration::ZERO,
+            end: Duration::from_millis(1),
+        }
+    }
+
+    fn entry(name: &str, burst: u64) -> WorkloadEntry {
+        WorkloadEntry {
+            name: Some(name.to_string()),
+            arrival_time: Duration::ZERO,
+            cpu_burst_time: Duration::from_millis(burst),
+            process_type: None,
+            priority: None,
+            io_phases: Vec::new(),
+            deadline: None,
+        }
+    }
+
+    /// Two long processes through `queue`, both outlast a quantum on level 1.
+    fn run(queue: Queues, names: [&str; 2]) -> EventLog {
+        let workload = names.iter().map(|name| entry(name, 30)).collect();
+        let scenario = Scenario {
+            disciplines: queue.is_multilevel().then_some([QueueDiscipline::RR; 4]),
+            seed: Some(1),
+            ..Scenario::new(queue, ProcessSource::Workload(workload))
+        };
+        scenario.run().unwrap().log
+    }
+
+    #[test]
+    fn lanes_are_in_the_order_of_their_number() {
+        let bars: Vec<GanttBar> =
+            ["Queue 10", "CPU 10", "Queue 2", "CPU 2", "CPU 1"].into_iter().map(bar).collect();
+        assert_eq!(lanes(&bars), ["CPU 1", "CPU 2", "CPU 10", "Queue 2", "Queue 10"]);
+    }
+
+    #[test]
+    fn mlfq_gets_a_lane_per_queue() {
+        let bars = bars_from_log(&run(Queues::MLFQ, ["P1", "P2"]));
+        // demoted after their first quantum, the rest of the bursts run on lower levels
+        assert_eq!(lanes(&bars)[..3], ["CPU", "Queue 1", "Queue 2"]);
+        let cpu = bars.iter().filter(|b| b.lane == "CPU").count();
+        assert_eq!(cpu * 2, bars.len());
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        let html = render_html("<b>Tom & Jerry</b>", &run(Queues::RR, ["<P1>", "\"P2\""]));
+        assert!(html.contains("<title>&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;</title>"));
+        assert!(html.contains("&lt;P1&gt;") && html.contains("&quot;P2&quot;"));
+        assert!(!html.contains("<P1>") && !html.contains("<b>"));
+    }
+
+    #[test]
+    fn multilevel_runs_get_a_metrics_table_per_queue() {
+        let html = render_html("MLQ", &run(Queues::MLQ, ["P1", "P2"]));
+        // the levels are all a multilevel run sends, there's no overall table
+        for table in ["Queue 1", "Queue 2", "Queue 3", "Queue 4"] {
+            assert!(html.contains(&format!("<h3>{}</h3>", table)), "{}", table);
+        }
+        assert!(!html.contains("<h3>Overall</h3>"));
+        let html = render_html("RR", &run(Queues::RR, ["P1", "P2"]));
+        assert!(html.contains("<h3>Overall</h3>") && !html.contains("<h3>Queue 1</h3>"));
+    }
+}
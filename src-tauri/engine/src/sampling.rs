@@ -0,0 +1,244 @@
+use crate::process_gen::Process;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+
+// Utils -------------------------------------------------------------------------------------------
+
+/// How often the UI samples the queues, on the simulated clock.
+pub const DEFAULT_SAMPLE_PERIOD: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueSample {
+    pub time: Duration,    // since the start of the run
+    pub ready: Vec<usize>, // ready processes per level, without the running one
+    pub running: usize,
+    pub blocked: usize,
+    pub in_system: usize, // every process that arrived and hasn't finished
+    pub busy: bool,
+    pub utilization: f64, // share of the time so far the CPU was busy
+    pub completed: usize,
+    pub throughput: f64, // completed processes per second so far
+}
+
+/// The queues as the scheduler left them at a step. It holds until the next step, which is
+/// when the simulator looks at the queues again.
+#[derive(Debug, Clone)]
+pub struct QueueState {
+    pub ready: Vec<usize>,
+    pub running: usize,
+    pub blocked: usize,
+    pub completed: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LittlesLaw {
+    /// L = λW checked against the samples: the time average of the number in system next to
+    /// the arrival rate times the average turnaround time. A run that ends empty satisfies it
+    /// exactly, so what's left is the error of sampling every `sample_period`.
+    pub average_in_system: f64,
+    pub arrival_rate: f64, // processes per second over the run
+    pub average_turnaround_time: f64, // in milliseconds
+    pub expected_in_system: f64,
+    pub relative_error: Option<f64>, // none when λW is zero
+}
+
+// Sampler -----------------------------------------------------------------------------------------
+
+pub struct Sampler {
+    period: Duration,
+    start: SystemTime,
+    next: Duration,
+    busy_time: Duration,
+    pub samples: Vec<QueueSample>,
+}
+
+impl Sampler {
+    pub fn new(period: Duration, start: SystemTime) -> Self {
+        Sampler {
+            period,
+            start,
+            next: Duration::ZERO,
+            busy_time: Duration::ZERO,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Samples every point of `[from, to)`, the time the scheduler spent on one step with the
+    /// queues in `state`. `pending` are the processes that haven't been let in yet (sorted by
+    /// arrival); the simulator only lets them in between steps, but they are in the system
+    /// from their arrival on. Returns how many samples were taken.
+    pub fn step(
+        &mut self,
+        state: &QueueState,
+        from: Duration,
+        to: Duration,
+        busy: bool,
+        pending: &[Process],
+    ) -> usize {
+        let taken = self.samples.len();
+        while self.next < to && !self.period.is_zero() {
+            let time = self.next.max(from);
+            let busy_so_far = self.busy_time + if busy { time - from } else { Duration::ZERO };
+            let arrived = pending.partition_point(|p| p.arrival_time <= self.start + time);
+            let seconds = time.as_secs_f64();
+            self.samples.push(QueueSample {
+                time,
+                ready: state.ready.clone(),
+                running: state.running,
+                blocked: state.blocked,
+                in_system: state.ready.iter().sum::<usize>() + state.running + state.blocked + arrived,
+                busy,
+                utilization: if seconds > 0.0 { busy_so_far.as_secs_f64() / seconds } else { 0.0 },
+                completed: state.completed,
+                throughput: if seconds > 0.0 { state.completed as f64 / seconds } else { 0.0 },
+            });
+            self.next += self.period;
+        }
+        if busy {
+            self.busy_time += to.saturating_sub(from);
+        }
+        self.samples.len() - taken
+    }
+}
+
+pub fn littles_law(samples: &[QueueSample], finished: &[Process], elapsed: Duration) -> Option<LittlesLaw> {
+    if samples.is_empty() || finished.is_empty() || elapsed.is_zero() {
+        return None;
+    }
+    let average_in_system =
+        samples.iter().map(|s| s.in_system as f64).sum::<f64>() / samples.len() as f64;
+    let arrival_rate = finished.len() as f64 / elapsed.as_secs_f64();
+    let average_turnaround = finished
+        .iter()
+        .map(|p| p.metrics.total_time.as_secs_f64())
+        .sum::<f64>()
+        / finished.len() as f64;
+    let expected_in_system = arrival_rate * average_turnaround;
+    Some(LittlesLaw {
+        average_in_system,
+        arrival_rate,
+        average_turnaround_time: average_turnaround * 1000.0,
+        expected_in_system,
+        relative_error: (expected_in_system != 0.0)
+            .then(|| (average_in_system - expected_in_system) / expected_in_system),
+    })
+}
+
+/// One row per sample, ready counts get a column per level.
+pub fn to_csv(samples: &[QueueSample]) -> String {
+    let levels = samples.first().map(|s| s.ready.len()).unwrap_or(1);
+    let mut csv = String::from("time_ms");
+    for level in 1..=levels {
+        csv.push_str(&format!(",ready_{}", level));
+    }
+    csv.push_str(",running,blocked,in_system,busy,utilization,completed,throughput\n");
+    for sample in samples {
+        csv.push_str(&(sample.time.as_secs_f64() * 1000.0).to_string());
+        for ready in &sample.ready {
+            csv.push_str(&format!(",{}", ready));
+        }
+        csv.push_str(&format!(
+            ",{},{},{},{},{},{},{}\n",
+            sample.running,
+            sample.blocked,
+            sample.in_system,
+            sample.busy,
+            sample.utilization,
+            sample.completed,
+            sample.throughput
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_gen::ProcessType;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn state(ready: usize, running: usize) -> QueueState {
+        QueueState {
+            ready: vec![ready],
+            running,
+            blocked: 0,
+            completed: 0,
+        }
+    }
+
+    fn process(arrival: SystemTime, turnaround: u64) -> Process {
+        let mut process = Process::new(ms(5), arrival, Some(ProcessType::BatchProcess));
+        process.metrics.total_time = ms(turnaround);
+        process
+    }
+
+    #[test]
+    fn samples_fall_on_the_period() {
+        let start = SystemTime::UNIX_EPOCH;
+        let mut sampler = Sampler::new(ms(10), start);
+        assert_eq!(sampler.step(&state(1, 1), ms(0), ms(25), true, &[]), 3);
+        assert_eq!(sampler.step(&state(1, 0), ms(25), ms(27), false, &[]), 0);
+        assert_eq!(sampler.step(&state(0, 1), ms(27), ms(45), true, &[]), 2);
+        let times: Vec<Duration> = sampler.samples.iter().map(|s| s.time).collect();
+        assert_eq!(times, [ms(0), ms(10), ms(20), ms(30), ms(40)]);
+    }
+
+    #[test]
+    fn utilization_counts_the_busy_time_so_far() {
+        let start = SystemTime::UNIX_EPOCH;
+        let mut sampler = Sampler::new(ms(10), start);
+        sampler.step(&state(1, 1), ms(0), ms(25), true, &[]);
+        sampler.step(&state(1, 0), ms(25), ms(27), false, &[]);
+        sampler.step(&state(0, 1), ms(27), ms(45), true, &[]);
+        let utilization: Vec<f64> = sampler.samples.iter().map(|s| s.utilization).collect();
+        // idle from 25 to 27 ms, busy the rest
+        let expected = [0.0, 1.0, 1.0, 28.0 / 30.0, 38.0 / 40.0];
+        for (got, expected) in utilization.iter().zip(expected) {
+            assert!((got - expected).abs() < 1e-9, "{:?}", utilization);
+        }
+    }
+
+    #[test]
+    fn processes_not_let_in_yet_are_in_the_system() {
+        let start = SystemTime::UNIX_EPOCH;
+        let mut sampler = Sampler::new(ms(10), start);
+        let pending = [process(start + ms(5), 0), process(start + ms(15), 0)];
+        sampler.step(&state(0, 1), ms(0), ms(20), true, &pending);
+        let in_system: Vec<usize> = sampler.samples.iter().map(|s| s.in_system).collect();
+        assert_eq!(in_system, [1, 2]);
+    }
+
+    #[test]
+    fn littles_law_by_hand() {
+        let start = SystemTime::UNIX_EPOCH;
+        let samples: Vec<QueueSample> = [2, 2, 1, 0]
+            .into_iter()
+            .enumerate()
+            .map(|(i, in_system)| QueueSample {
+                time: ms(10 * i as u64),
+                ready: vec![in_system],
+                running: 0,
+                blocked: 0,
+                in_system,
+                busy: true,
+                utilization: 1.0,
+                completed: 0,
+                throughput: 0.0,
+            })
+            .collect();
+        // L = 5/4, λ = 2 processes / 40 ms = 50/s, W = 25 ms, so λW = 1.25 as well
+        let finished = [process(start, 20), process(start, 30)];
+        let law = littles_law(&samples, &finished, ms(40)).unwrap();
+        assert_eq!(law.average_in_system, 1.25);
+        assert!((law.arrival_rate - 50.0).abs() < 1e-9);
+        assert!((law.average_turnaround_time - 25.0).abs() < 1e-9);
+        assert!((law.expected_in_system - 1.25).abs() < 1e-9);
+        assert!(law.relative_error.unwrap().abs() < 1e-9);
+
+        assert!(littles_law(&[], &finished, ms(40)).is_none());
+        assert!(littles_law(&samples, &[], ms(40)).is_none());
+    }
+}
@@ -0,0 +1,382 @@
+use crate::compare::{self, Comparison};
+use crate::error::{FieldError, SimError};
+use crate::events::Recorder;
+use crate::process_gen::Process;
+use crate::queue_engine::{QueueDiscipline, Queues};
+use crate::session::Session;
+use crate::simulator::{RunOptions, RunResult, Simulator};
+use crate::workload::WorkloadEntry;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+// Utils -------------------------------------------------------------------------------------------
+
+/// Where the processes of a run come from.
+#[derive(Debug, Clone)]
+pub enum ProcessSource {
+    Generated {
+        // exponential interarrival times and bursts, the rates are per second
+        arrival_lambda: f64,
+        burst_lambda: f64,
+        count: usize,
+    },
+    Workload(Vec<WorkloadEntry>),
+}
+
+impl ProcessSource {
+    /// The processes, arriving from `start` on. Anything random about them comes from `rng`, so
+    /// a seeded `rng` gives the same processes every time.
+    pub fn build<R: Rng>(&self, start: SystemTime, rng: &mut R) -> Result<Vec<Process>, SimError> {
+        match self {
+            ProcessSource::Workload(entries) => {
+                Ok(entries.iter().map(|entry| entry.to_process(start, rng)).collect())
+            }
+            ProcessSource::Generated {
+                arrival_lambda,
+                burst_lambda,
+                count,
+            } => Simulator::init(*arrival_lambda, *burst_lambda).generate_processes(*count, start, rng),
+        }
+    }
+
+    /// What's wrong with the processes before any are built: rates that aren't positive, no
+    /// processes at all, or a burst of zero, which no queue can run.
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        match self {
+            ProcessSource::Generated {
+                arrival_lambda,
+                burst_lambda,
+                count,
+            } => {
+                let rates = [("arrival_lambda", arrival_lambda), ("burst_lambda", burst_lambda)];
+                for (field, rate) in rates {
+                    if !rate.is_finite() || *rate <= 0.0 {
+                        errors.push(FieldError::new(
+                            format!("scenario.source.{}", field),
+                            "must be a positive number",
+                        ));
+                    }
+                }
+                if *count == 0 {
+                    errors.push(FieldError::new("scenario.source.count", "must be at least 1"));
+                }
+            }
+            ProcessSource::Workload(entries) => {
+                if entries.is_empty() {
+                    errors.push(FieldError::new(
+                        "scenario.source.workload",
+                        "there are no processes to run",
+                    ));
+                }
+                for (index, entry) in entries.iter().enumerate() {
+                    if entry.cpu_burst_time.is_zero() {
+                        errors.push(FieldError::new(
+                            format!("scenario.source.workload[{}].cpu_burst_time", index),
+                            "must be more than zero",
+                        ));
+                    }
+                }
+            }
+        }
+        errors
+    }
+}
+
+// Scenario ----------------------------------------------------------------------------------------
+
+/// One run, set up. `run` it headless to get its result at once, or `run_in` a session to
+/// follow it through its events.
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub queue: Queues,
+    pub disciplines: Option<[QueueDiscipline; 4]>, // one per level of MLQ and MLFQ, RR if not given
+    pub time_quantum: Duration,
+    pub context_switch: Duration,
+    pub options: RunOptions,
+    pub source: ProcessSource,
+    pub seed: Option<u64>, // the same seed gives the same processes
+}
+
+impl Scenario {
+    /// `queue` running the processes of `source`, with a 10 ms time quantum, a 1 ms context
+    /// switch and none of the options. The fields can be changed for anything else.
+    pub fn new(queue: Queues, source: ProcessSource) -> Self {
+        Scenario {
+            queue,
+            disciplines: None,
+            time_quantum: Duration::from_millis(10),
+            context_switch: Duration::from_millis(1),
+            options: RunOptions::default(),
+            source,
+            seed: None,
+        }
+    }
+
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if let Some(preemptive) = self.queue.needs_quantum(self.disciplines) {
+            if self.time_quantum.is_zero() {
+                errors.push(FieldError::new(
+                    "scenario.time_quantum",
+                    format!("{} needs a time quantum", preemptive),
+                ));
+            }
+        }
+        match (&self.disciplines, self.queue) {
+            (Some(disciplines), Queues::MLQ | Queues::MLFQ) => {
+                for (level, discipline) in disciplines.iter().enumerate() {
+                    if let QueueDiscipline::FIFO = discipline {
+                        errors.push(FieldError::new(
+                            format!("scenario.disciplines[{}]", level),
+                            "FIFO can't be the discipline of a level, use FCFS",
+                        ));
+                    }
+                }
+            }
+            (Some(_), queue) => errors.push(FieldError::new(
+                "scenario.disciplines",
+                format!("{} has a single level, only MLQ and MLFQ take disciplines", queue),
+            )),
+            (None, _) => {}
+        }
+        let uses = |discipline| self.queue.uses(self.disciplines, discipline);
+        if uses(QueueDiscipline::SCRIPT) && self.options.policy.is_none() {
+            errors.push(FieldError::new(
+                "scenario.options.policy",
+                "the SCRIPT discipline needs a script",
+            ));
+        }
+        if uses(QueueDiscipline::PLUGIN) && self.options.plugin.is_none() {
+            errors.push(FieldError::new(
+                "scenario.options.plugin",
+                "the PLUGIN discipline needs a plugin",
+            ));
+        }
+        if self.options.sample_period.is_some_and(|period| period.is_zero()) {
+            errors.push(FieldError::new("scenario.options.sample_period", "must be more than zero"));
+        }
+        if self.options.starvation_threshold.is_some_and(|threshold| threshold.is_zero()) {
+            errors.push(FieldError::new(
+                "scenario.options.starvation_threshold",
+                "must be more than zero",
+            ));
+        }
+        errors.extend(self.source.validate());
+        errors
+    }
+
+    /// Runs the scenario headless, as fast as it goes, and returns what it left behind, its
+    /// event log included. A seeded scenario also starts at the Unix epoch, so the whole result
+    /// is the same every time.
+    pub fn run(&self) -> Result<RunResult, SimError> {
+        self.run_from(self.start())
+    }
+
+    /// `run`, with the processes arriving from `start` on. For targets where the engine can't
+    /// read the system clock itself, like WebAssembly in the browser.
+    pub fn run_from(&self, start: SystemTime) -> Result<RunResult, SimError> {
+        SimError::check(self.validate())?;
+        let processes = self.source.build(start, &mut self.rng())?;
+        self.simulate(processes, Recorder::headless(self.queue.to_string(), start))
+    }
+
+    /// Runs the scenario live in `session`: paced at the session's speed, paused and stepped by
+    /// it, and every event sent to its sink as it happens. The session is finished or failed
+    /// when the run is over. Blocks until then, so callers give it a thread of its own.
+    pub fn run_in(&self, session: Arc<Session>) -> Result<RunResult, SimError> {
+        let result = SimError::check(self.validate()).and_then(|()| {
+            let recorder = Recorder::new(self.queue.to_string(), session.clone());
+            let processes = self.source.build(recorder.clock.start(), &mut self.rng())?;
+            self.simulate(processes, recorder)
+        });
+        match &result {
+            Ok(result) => session.finish(result.clone()),
+            Err(e) => session.fail(e.clone()),
+        }
+        result
+    }
+
+    /// Runs the processes of the scenario through every queue in `queues` instead of its own,
+    /// and ranks the queues by `rank_by`, see `compare::compare`. Seeded, it's the same every
+    /// time, like `run`.
+    pub fn compare(&self, queues: &[Queues], rank_by: &str) -> Result<Comparison, SimError> {
+        compare::compare(self, queues, self.start(), rank_by)
+    }
+
+    fn start(&self) -> SystemTime {
+        match self.seed {
+            Some(_) => SystemTime::UNIX_EPOCH,
+            None => SystemTime::now(),
+        }
+    }
+
+    pub(crate) fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    fn simulate(&self, processes: Vec<Process>, recorder: Recorder) -> Result<RunResult, SimError> {
+        Simulator::simulate(
+            self.queue,
+            self.context_switch,
+            self.time_quantum,
+            self.disciplines,
+            self.options.clone(),
+            processes,
+            recorder,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generated(queue: Queues) -> Scenario {
+        let source = ProcessSource::Generated {
+            arrival_lambda: 50.0,
+            burst_lambda: 100.0,
+            count: 20,
+        };
+        Scenario::new(queue, source)
+    }
+
+    fn invalid_fields(scenario: &Scenario) -> Vec<String> {
+        scenario.validate().into_iter().map(|e| e.field).collect()
+    }
+
+    #[test]
+    fn preemptive_queues_need_a_quantum() {
+        for queue in [Queues::RR, Queues::SRF, Queues::SJF, Queues::MLFQ] {
+            let scenario = Scenario {
+                time_quantum: Duration::ZERO,
+                ..generated(queue)
+            };
+            assert_eq!(invalid_fields(&scenario), ["scenario.time_quantum"], "{}", queue);
+        }
+        let mlfq = Scenario {
+            time_quantum: Duration::ZERO,
+            ..generated(Queues::MLFQ)
+        };
+        assert!(mlfq.validate()[0].message.contains("level 1 of MLFQ (RR)"));
+    }
+
+    #[test]
+    fn queues_that_never_preempt_need_no_quantum() {
+        for queue in [Queues::FCFS, Queues::SPN, Queues::HRRN] {
+            let scenario = Scenario {
+                time_quantum: Duration::ZERO,
+                ..generated(queue)
+            };
+            assert!(scenario.validate().is_empty(), "{}", queue);
+        }
+        let mlq = Scenario {
+            time_quantum: Duration::ZERO,
+            disciplines: Some([QueueDiscipline::FCFS; 4]),
+            ..generated(Queues::MLQ)
+        };
+        assert!(mlq.validate().is_empty());
+    }
+
+    #[test]
+    fn levels_and_policies_are_checked() {
+        let scenario = Scenario {
+            disciplines: Some([
+                QueueDiscipline::RR,
+                QueueDiscipline::FIFO,
+                QueueDiscipline::SCRIPT,
+                QueueDiscipline::FCFS,
+            ]),
+            ..generated(Queues::MLQ)
+        };
+        assert_eq!(
+            invalid_fields(&scenario),
+            ["scenario.disciplines[1]", "scenario.options.policy"]
+        );
+    }
+
+    #[test]
+    fn single_level_queues_take_no_disciplines() {
+        let scenario = Scenario {
+            disciplines: Some([QueueDiscipline::SCRIPT; 4]),
+            ..generated(Queues::FCFS)
+        };
+        assert_eq!(invalid_fields(&scenario), ["scenario.disciplines"]);
+    }
+
+    #[test]
+    fn sources_are_checked() {
+        let scenario = Scenario::new(
+            Queues::RR,
+            ProcessSource::Generated {
+                arrival_lambda: f64::NAN,
+                burst_lambda: -1.0,
+                count: 0,
+            },
+        );
+        assert_eq!(
+            invalid_fields(&scenario),
+            [
+                "scenario.source.arrival_lambda",
+                "scenario.source.burst_lambda",
+                "scenario.source.count"
+            ]
+        );
+
+        let entry = |burst| WorkloadEntry {
+            name: None,
+            arrival_time: Duration::ZERO,
+            cpu_burst_time: Duration::from_millis(burst),
+            process_type: None,
+            priority: None,
+            io_phases: Vec::new(),
+            deadline: None,
+        };
+        for queue in [Queues::FCFS, Queues::SRF, Queues::HRRN] {
+            let scenario = Scenario::new(queue, ProcessSource::Workload(vec![entry(5), entry(0)]));
+            assert_eq!(
+                invalid_fields(&scenario),
+                ["scenario.source.workload[1].cpu_burst_time"]
+            );
+            assert!(scenario.run().is_err());
+        }
+        let empty = Scenario::new(Queues::FCFS, ProcessSource::Workload(Vec::new()));
+        assert_eq!(invalid_fields(&empty), ["scenario.source.workload"]);
+    }
+
+    #[test]
+    fn an_invalid_scenario_doesnt_run() {
+        let scenario = Scenario {
+            time_quantum: Duration::ZERO,
+            ..generated(Queues::SRF)
+        };
+        assert!(matches!(scenario.run(), Err(SimError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn a_seeded_run_is_the_same_every_time() {
+        let scenario = Scenario {
+            seed: Some(11),
+            ..generated(Queues::RR)
+        };
+        let (first, second) = (scenario.run().unwrap(), scenario.run().unwrap());
+        assert_eq!(first.processes.len(), 20);
+        let times = |result: &RunResult| -> Vec<(Duration, Duration)> {
+            result
+                .processes
+                .iter()
+                .map(|p| (p.cpu_burst_time, p.metrics.total_waiting_time))
+                .collect()
+        };
+        assert_eq!(times(&first), times(&second));
+        assert_eq!(
+            first.metrics["average_waiting_time"].as_f64(),
+            second.metrics["average_waiting_time"].as_f64()
+        );
+    }
+}
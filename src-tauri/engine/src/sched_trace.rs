@@ -0,0 +1,549 @@
+use crate::process_gen::IoPhase;
+use crate::queue_engine::MetricValue;
+use crate::simulator::RunResult;
+use crate::workload::{RowIssue, ValidationReport, WorkloadEntry, WorkloadFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+
+// Utils -------------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SchedTraceOptions {
+    /// multiplies every time in the trace, e.g. 10.0 replays a 1ms kernel burst as 10ms.
+    pub time_scale: f64,
+    /// only these pids are imported when given, otherwise every task except the idle task.
+    pub pids: Option<Vec<u32>>,
+}
+
+impl Default for SchedTraceOptions {
+    fn default() -> Self {
+        SchedTraceOptions {
+            time_scale: 1.0,
+            pids: None,
+        }
+    }
+}
+
+/// A stretch of time a task actually spent on a CPU according to the kernel.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineSegment {
+    pub cpu: u32,
+    pub pid: u32,
+    pub name: String,
+    pub start: Duration,
+    pub end: Duration,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SchedTraceImport {
+    pub report: ValidationReport,
+    pub timeline: Vec<TimelineSegment>,
+    pub kernel_metrics: HashMap<String, MetricValue>,
+    pub cpus: usize,
+}
+
+/// What the kernel did with a traced workload, to put next to the simulator's runs of it.
+#[derive(Debug, Clone, Serialize)]
+pub struct KernelRun {
+    pub cpus: usize, // the kernel may have used several, the simulator has one
+    pub metrics: HashMap<String, MetricValue>,
+}
+
+/// A run of a sched trace's workload next to the kernel's own run of it.
+#[derive(Serialize)]
+pub struct Replay<'a> {
+    #[serde(flatten)]
+    pub result: &'a RunResult,
+    pub kernel: &'a KernelRun,
+}
+
+impl SchedTraceImport {
+    pub fn kernel(&self) -> KernelRun {
+        KernelRun {
+            cpus: self.cpus,
+            metrics: self.kernel_metrics.clone(),
+        }
+    }
+}
+
+enum SchedEvent {
+    Switch {
+        prev_pid: u32,
+        prev_comm: String,
+        prev_state: String,
+        next_pid: u32,
+        next_comm: String,
+    },
+    Wakeup {
+        pid: u32,
+        comm: String,
+    },
+}
+
+struct TraceLine {
+    cpu: u32,
+    time: f64,
+    event: SchedEvent,
+}
+
+#[derive(PartialEq)]
+enum TaskState {
+    Running(f64, u32), // on a CPU since, and which CPU
+    Runnable,          // waiting for a CPU
+    Sleeping(f64),     // blocked since
+    Dead,
+}
+
+struct Task {
+    // everything we learn about one pid while walking the trace
+    name: String,
+    arrival: f64,
+    first_run: Option<f64>,
+    last_end: f64,
+    cpu_time: f64,
+    sleeps: Vec<(f64, f64)>, // (cpu time used before the sleep, sleep length)
+    state: TaskState,
+}
+
+// Parsing -----------------------------------------------------------------------------------------
+
+fn key_value<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    // finds `key=value` where value runs until the next space. comm values with spaces are rare
+    // enough in sched traces that we don't try to handle them.
+    let pattern = format!("{}=", key);
+    let mut search = 0;
+    while let Some(found) = text[search..].find(&pattern) {
+        let start = search + found;
+        if start == 0 || text.as_bytes()[start - 1] == b' ' {
+            let value = &text[start + pattern.len()..];
+            return Some(value.split_whitespace().next().unwrap_or(""));
+        }
+        search = start + pattern.len();
+    }
+    None
+}
+
+fn compact_task(text: &str) -> Option<(String, u32)> {
+    // `comm:pid` as printed by `perf sched script` in its compact form
+    let (comm, pid) = text.trim().rsplit_once(':')?;
+    Some((comm.to_string(), pid.parse().ok()?))
+}
+
+fn parse_switch(fields: &str) -> Option<SchedEvent> {
+    if let (Some(prev_pid), Some(next_pid)) = (key_value(fields, "prev_pid"), key_value(fields, "next_pid")) {
+        return Some(SchedEvent::Switch {
+            prev_pid: prev_pid.parse().ok()?,
+            prev_comm: key_value(fields, "prev_comm").unwrap_or("").to_string(),
+            prev_state: key_value(fields, "prev_state").unwrap_or("R").to_string(),
+            next_pid: next_pid.parse().ok()?,
+            next_comm: key_value(fields, "next_comm").unwrap_or("").to_string(),
+        });
+    }
+    // compact form: `prev:1234 [120] S ==> next:0 [120]`
+    let (prev, next) = fields.split_once("==>")?;
+    let mut prev_parts = prev.split_whitespace();
+    let (prev_comm, prev_pid) = compact_task(prev_parts.next()?)?;
+    let prev_state = prev_parts.rfind(|p| !p.starts_with('[')).unwrap_or("R");
+    let (next_comm, next_pid) = compact_task(next.split_whitespace().next()?)?;
+    Some(SchedEvent::Switch {
+        prev_pid,
+        prev_comm,
+        prev_state: prev_state.to_string(),
+        next_pid,
+        next_comm,
+    })
+}
+
+fn parse_wakeup(fields: &str) -> Option<SchedEvent> {
+    if let Some(pid) = key_value(fields, "pid") {
+        return Some(SchedEvent::Wakeup {
+            pid: pid.parse().ok()?,
+            comm: key_value(fields, "comm").unwrap_or("").to_string(),
+        });
+    }
+    let (comm, pid) = compact_task(fields.split_whitespace().next()?)?;
+    Some(SchedEvent::Wakeup { pid, comm })
+}
+
+fn parse_line(line: &str) -> Result<Option<TraceLine>, String> {
+    // Both ftrace and `perf sched script` print `<task> [cpu] <flags> <timestamp>: <event>: <fields>`,
+    // we only rely on the bracketed cpu, the timestamp right before the event name and the fields.
+    let event_name = ["sched_switch", "sched_wakeup_new", "sched_wakeup", "sched_waking"]
+        .iter()
+        .find_map(|name| line.find(&format!("{}:", name)).map(|index| (*name, index)));
+    let (name, index) = match event_name {
+        Some(found) => found,
+        None => return Ok(None),
+    };
+    let fields = &line[index + name.len() + 1..];
+    let head = &line[..index];
+
+    let cpu = head
+        .split_whitespace()
+        .find_map(|t| t.strip_prefix('[')?.strip_suffix(']')?.parse::<u32>().ok())
+        .ok_or("could not find the [cpu] column")?;
+    let time = head
+        .split_whitespace()
+        .rev()
+        .find_map(|t| t.trim_end_matches(':').parse::<f64>().ok())
+        .ok_or("could not find the timestamp")?;
+
+    let event = match name {
+        "sched_switch" => parse_switch(fields).ok_or("could not read the sched_switch fields")?,
+        // sched_waking comes right before the sched_wakeup of the same task, whichever of the
+        // two is in the trace first ends the sleep and the other one changes nothing.
+        _ => parse_wakeup(fields).ok_or("could not read the wakeup fields")?,
+    };
+    Ok(Some(TraceLine { cpu, time, event }))
+}
+
+// Reconstruction ----------------------------------------------------------------------------------
+
+/// `seconds` of the trace, scaled. Every time is within the trace, whose length
+/// `import_sched_trace` checks fits in a duration, so only an average can saturate.
+fn secs(seconds: f64, scale: f64) -> Duration {
+    Duration::try_from_secs_f64((seconds * scale).max(0.0)).unwrap_or(Duration::MAX)
+}
+
+fn failed(report: ValidationReport) -> SchedTraceImport {
+    SchedTraceImport {
+        report,
+        timeline: Vec::new(),
+        kernel_metrics: HashMap::new(),
+        cpus: 0,
+    }
+}
+
+/// Rebuilds a workload out of `perf sched script` or ftrace `sched_switch` / `sched_wakeup`
+/// output. Every task becomes a process whose sleeps are I/O phases, and the switches
+/// themselves become the kernel's timeline, with the same metrics `calculate_metrics` reports
+/// so the two can be put next to each other.
+pub fn import_sched_trace(contents: &str, options: &SchedTraceOptions) -> SchedTraceImport {
+    let mut report = ValidationReport {
+        format: WorkloadFormat::SchedTrace,
+        valid: false,
+        processes: Vec::new(),
+        errors: Vec::new(),
+        warnings: Vec::new(),
+        kernel: None, // it's next to the report, see kernel_metrics
+    };
+    let scale = options.time_scale;
+    if !scale.is_finite() || scale <= 0.0 {
+        report.errors.push(RowIssue {
+            line: 0,
+            column: Some(String::from("time_scale")),
+            message: String::from("time scale must be a positive number"),
+        });
+        return failed(report);
+    }
+    let wanted = |pid: u32| pid != 0 && options.pids.as_ref().is_none_or(|p| p.contains(&pid));
+
+    let mut lines = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        match parse_line(line) {
+            Ok(Some(parsed)) => lines.push(parsed),
+            Ok(None) => {}
+            Err(e) => report.errors.push(RowIssue {
+                line: index + 1,
+                column: None,
+                message: e.to_string(),
+            }),
+        }
+    }
+    // perf can print events of different CPUs slightly out of order
+    lines.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(std::cmp::Ordering::Equal));
+
+    let trace_start = lines.first().map(|l| l.time).unwrap_or(0.0);
+    let trace_end = lines.last().map(|l| l.time - trace_start).unwrap_or(0.0);
+    if Duration::try_from_secs_f64(trace_end * scale).is_err() {
+        report.errors.push(RowIssue {
+            line: 0,
+            column: None,
+            message: String::from("the trace is too long to replay at this time scale"),
+        });
+        return failed(report);
+    }
+    let mut tasks: BTreeMap<u32, Task> = BTreeMap::new();
+    let mut timeline = Vec::new();
+    let mut cpus = HashSet::new();
+    let new_task = |name: &str, time: f64, state: TaskState| Task {
+        name: name.to_string(),
+        arrival: time,
+        first_run: None,
+        last_end: time,
+        cpu_time: 0.0,
+        sleeps: Vec::new(),
+        state,
+    };
+
+    for line in &lines {
+        cpus.insert(line.cpu);
+        let time = line.time - trace_start;
+        match &line.event {
+            SchedEvent::Wakeup { pid, comm } if wanted(*pid) => {
+                let task = tasks
+                    .entry(*pid)
+                    .or_insert_with(|| new_task(comm, time, TaskState::Runnable));
+                if let TaskState::Sleeping(since) = task.state {
+                    task.sleeps.push((task.cpu_time, time - since));
+                    task.state = TaskState::Runnable;
+                }
+            }
+            SchedEvent::Wakeup { .. } => {}
+            SchedEvent::Switch {
+                prev_pid,
+                prev_comm,
+                prev_state,
+                next_pid,
+                next_comm,
+            } => {
+                if wanted(*prev_pid) {
+                    // a task we never saw start was already running when the trace began
+                    let task = tasks
+                        .entry(*prev_pid)
+                        .or_insert_with(|| new_task(prev_comm, 0.0, TaskState::Running(0.0, line.cpu)));
+                    if let TaskState::Running(since, _) = task.state {
+                        task.cpu_time += time - since;
+                        task.last_end = time;
+                        task.first_run.get_or_insert(since);
+                        timeline.push(TimelineSegment {
+                            cpu: line.cpu,
+                            pid: *prev_pid,
+                            name: task.name.clone(),
+                            start: secs(since, scale),
+                            end: secs(time, scale),
+                        });
+                    }
+                    // R (or R+) means it was preempted and is still runnable, X / Z mean it
+                    // exited, anything else (S, D, I, ...) means it blocked.
+                    task.state = match prev_state.trim_end_matches('+') {
+                        "R" => TaskState::Runnable,
+                        "X" | "Z" => TaskState::Dead,
+                        _ => TaskState::Sleeping(time),
+                    };
+                }
+                if wanted(*next_pid) {
+                    let task = tasks
+                        .entry(*next_pid)
+                        .or_insert_with(|| new_task(next_comm, time, TaskState::Runnable));
+                    if let TaskState::Sleeping(since) = task.state {
+                        // the wakeup wasn't traced, we can only tell it slept until now
+                        task.sleeps.push((task.cpu_time, time - since));
+                    }
+                    task.first_run.get_or_insert(time);
+                    task.state = TaskState::Running(time, line.cpu);
+                }
+            }
+        }
+    }
+
+    for (pid, task) in tasks.iter_mut() {
+        // tasks still on a CPU when the trace ends are cut at the last event
+        if let TaskState::Running(since, cpu) = task.state {
+            task.cpu_time += trace_end - since;
+            task.last_end = trace_end;
+            task.first_run.get_or_insert(since);
+            timeline.push(TimelineSegment {
+                cpu,
+                pid: *pid,
+                name: task.name.clone(),
+                start: secs(since, scale),
+                end: secs(trace_end, scale),
+            });
+        }
+    }
+    timeline.sort_by_key(|s| s.start);
+
+    let mut turnaround = Vec::new();
+    let mut bursts = Vec::new();
+    let mut waiting = Vec::new();
+    let mut response = Vec::new();
+    for (pid, task) in &tasks {
+        let cpu_burst_time = secs(task.cpu_time, scale);
+        if cpu_burst_time.is_zero() {
+            report.warnings.push(RowIssue {
+                line: 0,
+                column: Some(format!("pid {}", pid)),
+                message: format!("{} never ran during the trace and was skipped", task.name),
+            });
+            continue;
+        }
+        // a sleep after the last CPU burst isn't I/O the process needs, it just went idle
+        let io_phases: Vec<IoPhase> = task
+            .sleeps
+            .iter()
+            .filter(|(at, _)| *at < task.cpu_time)
+            .map(|(at, length)| IoPhase {
+                at: secs(*at, scale),
+                duration: secs(*length, scale),
+            })
+            .collect();
+        let io_time: f64 = task
+            .sleeps
+            .iter()
+            .filter(|(at, _)| *at < task.cpu_time)
+            .map(|(_, length)| length)
+            .sum();
+        let total = task.last_end - task.arrival;
+        turnaround.push(total);
+        bursts.push(task.cpu_time);
+        waiting.push((total - task.cpu_time - io_time).max(0.0));
+        response.push(task.first_run.unwrap_or(task.arrival) - task.arrival);
+
+        report.processes.push(WorkloadEntry {
+            name: Some(format!("{}-{}", task.name, pid)),
+            arrival_time: secs(task.arrival, scale),
+            cpu_burst_time,
+            process_type: None,
+            priority: None,
+            io_phases,
+            deadline: None,
+        });
+    }
+
+    let mut kernel_metrics = HashMap::new();
+    let average = |values: &Vec<f64>| {
+        secs(values.iter().sum::<f64>() / values.len().max(1) as f64, scale)
+    };
+    kernel_metrics.insert(
+        String::from("queue_discipline"),
+        MetricValue::StringValue(String::from("kernel")),
+    );
+    kernel_metrics.insert(
+        String::from("average_turnaround_time"),
+        MetricValue::DurationValue(average(&turnaround)),
+    );
+    kernel_metrics.insert(
+        String::from("average_waiting_time"),
+        MetricValue::DurationValue(average(&waiting)),
+    );
+    kernel_metrics.insert(
+        String::from("average_response_time"),
+        MetricValue::DurationValue(average(&response)),
+    );
+    // defined like calculate_metrics does, the total burst over the total turnaround
+    let total_turnaround: f64 = turnaround.iter().sum();
+    let utilization = match total_turnaround {
+        total if total > 0.0 => bursts.iter().sum::<f64>() / total * 100.0,
+        _ => 0.0,
+    };
+    kernel_metrics.insert(
+        String::from("cpu_utilization"),
+        MetricValue::PercentageValue(utilization),
+    );
+
+    if report.processes.is_empty() && report.errors.is_empty() {
+        report.errors.push(RowIssue {
+            line: 0,
+            column: None,
+            message: String::from("no sched_switch events of any task were found"),
+        });
+    }
+    report.valid = report.errors.is_empty();
+    SchedTraceImport {
+        report,
+        timeline,
+        kernel_metrics,
+        cpus: cpus.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::millis;
+
+    // `a` runs 4 ms, sleeps 2 ms while `b` runs 4 ms and exits, then runs 2 more ms and exits
+    const TRACE: &str = "\
+# tracer: nop
+  <idle>-0  [000] d..3 100.000000: sched_switch: prev_comm=swapper/0 prev_pid=0 prev_prio=120 \
+prev_state=R ==> next_comm=a next_pid=10 next_prio=120
+       a-10 [000] d..3 100.004000: sched_switch: prev_comm=a prev_pid=10 prev_prio=120 \
+prev_state=S ==> next_comm=b next_pid=20 next_prio=120
+  <idle>-0  [001] d..3 100.006000: sched_wakeup: comm=a pid=10 prio=120 target_cpu=000
+       b-20 [000] d..3 100.008000: sched_switch: prev_comm=b prev_pid=20 prev_prio=120 \
+prev_state=X ==> next_comm=a next_pid=10 next_prio=120
+       a-10 [000] d..3 100.010000: sched_switch: prev_comm=a prev_pid=10 prev_prio=120 \
+prev_state=X ==> next_comm=swapper/0 next_pid=0 next_prio=120
+";
+
+    fn close(duration: Duration, expected: f64) -> bool {
+        (millis(duration) - expected).abs() < 1e-6
+    }
+
+    #[test]
+    fn tasks_and_sleeps_are_rebuilt() {
+        let import = import_sched_trace(TRACE, &SchedTraceOptions::default());
+        let report = &import.report;
+        assert!(report.valid, "{:?}", report.errors);
+        assert_eq!(import.cpus, 2);
+        assert_eq!(import.timeline.len(), 3);
+
+        let a = &report.processes[0];
+        assert_eq!(a.name.as_deref(), Some("a-10"));
+        assert!(close(a.arrival_time, 0.0) && close(a.cpu_burst_time, 6.0));
+        assert_eq!(a.io_phases.len(), 1);
+        assert!(close(a.io_phases[0].at, 4.0) && close(a.io_phases[0].duration, 2.0));
+
+        let b = &report.processes[1];
+        assert_eq!(b.name.as_deref(), Some("b-20"));
+        assert!(close(b.arrival_time, 4.0) && close(b.cpu_burst_time, 4.0));
+        assert!(b.io_phases.is_empty());
+    }
+
+    #[test]
+    fn kernel_metrics_match_calculate_metrics() {
+        let import = import_sched_trace(TRACE, &SchedTraceOptions::default());
+        let metric = |name: &str| import.kernel_metrics.get(name).cloned();
+        // turnarounds of 10 and 4 ms for 6 and 4 ms of bursts
+        match metric("cpu_utilization") {
+            Some(MetricValue::PercentageValue(u)) => assert!((u - 1000.0 / 14.0).abs() < 1e-6),
+            other => panic!("unexpected cpu_utilization {:?}", other),
+        }
+        match metric("average_waiting_time") {
+            Some(MetricValue::DurationValue(w)) => assert!(close(w, 1.0)),
+            other => panic!("unexpected average_waiting_time {:?}", other),
+        }
+    }
+
+    #[test]
+    fn times_are_scaled() {
+        let options = SchedTraceOptions {
+            time_scale: 10.0,
+            pids: Some(vec![20]),
+        };
+        let import = import_sched_trace(TRACE, &options);
+        assert_eq!(import.report.processes.len(), 1);
+        let b = &import.report.processes[0];
+        assert!(close(b.arrival_time, 40.0) && close(b.cpu_burst_time, 40.0));
+    }
+
+    #[test]
+    fn the_compact_perf_form_is_read() {
+        let trace = "\
+    a 10 [000] 5.000000: sched:sched_switch: swapper/0:0 [120] R ==> a:10 [120]
+    a 10 [000] 5.003000: sched:sched_switch: a:10 [120] X ==> swapper/0:0 [120]
+";
+        let import = import_sched_trace(trace, &SchedTraceOptions::default());
+        assert!(import.report.valid, "{:?}", import.report.errors);
+        assert!(close(import.report.processes[0].cpu_burst_time, 3.0));
+    }
+
+    #[test]
+    fn malformed_lines_are_reported() {
+        let trace = "\
+  a-10 [000] d..3 1.000000: sched_switch: nothing to read here
+  a-10 d..3 1.000000: sched_wakeup: comm=a pid=10
+";
+        let report = import_sched_trace(trace, &SchedTraceOptions::default()).report;
+        assert!(!report.valid);
+        let lines: Vec<usize> = report.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, [1, 2]);
+        assert!(report.errors[1].message.contains("[cpu]"));
+    }
+}
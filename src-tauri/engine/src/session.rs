@@ -0,0 +1,432 @@
+use crate::error::{FieldError, SimError};
+use crate::events::{self, EventLog, EventSink};
+use crate::simulator::RunResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+// Utils -------------------------------------------------------------------------------------------
+
+pub type SessionId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionState {
+    Running,
+    Paused,
+    Cancelled,
+    Finished,
+    Failed, // the run stopped on an error, see SimulationFailed
+}
+
+/// How fast a run shown in the UI plays, only the real time it takes changes. In JSON it's
+/// `{"times": 10}` or `"max"`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackSpeed {
+    Times(f64), // simulated time runs this many times faster than real time, 0.1 is slow motion
+    Max,        // no waiting at all, the run goes as fast as it can
+}
+
+impl Default for PlaybackSpeed {
+    fn default() -> Self {
+        PlaybackSpeed::Times(1.0)
+    }
+}
+
+/// Every event a run sends the UI is wrapped in this, so the UI can tell apart the events of
+/// runs going at the same time. A replayed log has no session.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionEvent<S> {
+    pub session: Option<SessionId>,
+    pub data: S,
+}
+
+/// Sent to the UI as `simulation_failed` when a run stops on an error instead of finishing.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationFailed {
+    pub cause: SimError,
+    pub message: String, // the cause as a sentence, ready to show
+}
+
+/// Sent to the UI as `session_status` every time a session changes state, and returned by
+/// every session command.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionStatus {
+    pub id: SessionId,
+    pub queue: String,
+    pub state: SessionState,
+    pub speed: PlaybackSpeed,
+    pub elapsed: Duration, // simulated time so far
+    pub decisions: u64,    // scheduling decisions made so far
+    pub error: Option<SimError>, // why a failed run stopped
+}
+
+#[derive(Debug)]
+struct Control {
+    state: SessionState,
+    steps: u64, // decisions a paused session may still make
+    speed: PlaybackSpeed,
+    elapsed: Duration,
+    decisions: u64,
+    error: Option<SimError>,
+}
+
+pub const MIN_SPEED: f64 = 0.001; // a simulated millisecond takes a real second
+pub const MAX_SPEED: f64 = 1_000_000.0; // a simulated second takes a real microsecond, use Max
+
+/// What's wrong with `speed`, reported as `field`.
+pub fn check_speed(field: &str, speed: PlaybackSpeed) -> Vec<FieldError> {
+    match speed {
+        PlaybackSpeed::Times(factor) if !(MIN_SPEED..=MAX_SPEED).contains(&factor) => {
+            vec![FieldError::new(
+                field,
+                format!("must be from {} to {} times real time, or max", MIN_SPEED, MAX_SPEED),
+            )]
+        }
+        _ => Vec::new(),
+    }
+}
+
+// Session -----------------------------------------------------------------------------------------
+
+/// One simulation shown live. The simulation thread waits on it before every scheduling decision,
+/// and the session commands change it from the UI's side.
+pub struct Session {
+    pub id: SessionId,
+    queue: String,
+    sink: Arc<dyn EventSink>, // the run's events go here too, see Recorder::new
+    control: Mutex<Control>,
+    changed: Condvar,
+    result: Mutex<Option<RunResult>>,
+}
+
+impl Session {
+    fn new(
+        id: SessionId,
+        queue: String,
+        sink: Arc<dyn EventSink>,
+        speed: PlaybackSpeed,
+        paused: bool,
+    ) -> Self {
+        Session {
+            id,
+            queue,
+            sink,
+            control: Mutex::new(Control {
+                state: if paused { SessionState::Paused } else { SessionState::Running },
+                steps: 0,
+                speed,
+                elapsed: Duration::from_secs(0),
+                decisions: 0,
+                error: None,
+            }),
+            changed: Condvar::new(),
+            result: Mutex::new(None),
+        }
+    }
+
+    fn status_of(&self, control: &Control) -> SessionStatus {
+        SessionStatus {
+            id: self.id,
+            queue: self.queue.clone(),
+            state: control.state,
+            speed: control.speed,
+            elapsed: control.elapsed,
+            decisions: control.decisions,
+            error: control.error.clone(),
+        }
+    }
+
+    pub fn status(&self) -> SessionStatus {
+        self.status_of(&self.control.lock().unwrap())
+    }
+
+    pub fn sink(&self) -> Arc<dyn EventSink> {
+        self.sink.clone()
+    }
+
+    fn publish(&self, status: &SessionStatus) {
+        let _ = events::send(self.sink.as_ref(), "session_status", status);
+    }
+
+    /// Waits out `duration` of simulated time in real time, at the session's speed. A new
+    /// speed applies right away, even halfway through a long burst, and a cancelled run stops
+    /// waiting.
+    pub fn pace(&self, duration: Duration) {
+        let mut left = duration;
+        let mut control = self.control.lock().unwrap();
+        while !left.is_zero() && control.state != SessionState::Cancelled {
+            let PlaybackSpeed::Times(factor) = control.speed else {
+                return;
+            };
+            let started = Instant::now();
+            // a burst can be long enough that dividing overflows, it's waited out until a change
+            let real =
+                Duration::try_from_secs_f64(left.as_secs_f64() / factor).unwrap_or(Duration::MAX);
+            let (guard, wait) = self.changed.wait_timeout(control, real).unwrap();
+            if wait.timed_out() {
+                return;
+            }
+            // woken up early, the time that passed still went at the old speed
+            control = guard;
+            let simulated = Duration::try_from_secs_f64(started.elapsed().as_secs_f64() * factor)
+                .unwrap_or(Duration::MAX);
+            left = left.saturating_sub(simulated);
+        }
+    }
+
+    /// Called by the simulation before every scheduling decision. Blocks while the session is
+    /// paused and has no step left, returns false once it's cancelled.
+    pub fn checkpoint(&self, elapsed: Duration) -> bool {
+        let mut control = self.control.lock().unwrap();
+        control.elapsed = elapsed;
+        loop {
+            match control.state {
+                SessionState::Cancelled | SessionState::Finished | SessionState::Failed => {
+                    return false
+                }
+                SessionState::Running => {
+                    control.decisions += 1;
+                    return true;
+                }
+                SessionState::Paused if control.steps > 0 => {
+                    control.steps -= 1;
+                    control.decisions += 1;
+                    let status = self.status_of(&control);
+                    drop(control);
+                    self.publish(&status);
+                    return true;
+                }
+                SessionState::Paused => control = self.changed.wait(control).unwrap(),
+            }
+        }
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Control)) -> Result<SessionStatus, String> {
+        let mut control = self.control.lock().unwrap();
+        if matches!(
+            control.state,
+            SessionState::Cancelled | SessionState::Finished | SessionState::Failed
+        ) {
+            return Err(format!("Simulation {} is no longer running", self.id));
+        }
+        change(&mut control);
+        let status = self.status_of(&control);
+        drop(control);
+        self.changed.notify_all();
+        self.publish(&status);
+        Ok(status)
+    }
+
+    pub fn cancel(&self) -> Result<SessionStatus, String> {
+        self.update(|control| control.state = SessionState::Cancelled)
+    }
+
+    pub fn pause(&self) -> Result<SessionStatus, String> {
+        self.update(|control| control.state = SessionState::Paused)
+    }
+
+    pub fn resume(&self) -> Result<SessionStatus, String> {
+        self.update(|control| {
+            control.state = SessionState::Running;
+            control.steps = 0;
+        })
+    }
+
+    /// Lets a paused session make one more scheduling decision, pausing it first if needed.
+    pub fn step(&self) -> Result<SessionStatus, String> {
+        self.update(|control| {
+            control.state = SessionState::Paused;
+            control.steps += 1;
+        })
+    }
+
+    pub fn set_speed(&self, speed: PlaybackSpeed) -> Result<SessionStatus, SimError> {
+        SimError::check(check_speed("speed", speed))?;
+        self.update(|control| control.speed = speed)
+            .map_err(|message| SimError::Session { message })
+    }
+
+    /// Keeps what the run left behind so it can still be asked for after the run is over.
+    pub fn finish(&self, result: RunResult) {
+        *self.result.lock().unwrap() = Some(result);
+        let mut control = self.control.lock().unwrap();
+        if control.state != SessionState::Cancelled {
+            control.state = SessionState::Finished;
+        }
+        let status = self.status_of(&control);
+        drop(control);
+        self.changed.notify_all();
+        self.publish(&status);
+    }
+
+    /// Ends a run that couldn't go on, the UI gets the cause as `simulation_failed`.
+    pub fn fail(&self, error: SimError) {
+        let mut control = self.control.lock().unwrap();
+        control.state = SessionState::Failed;
+        control.error = Some(error.clone());
+        let status = self.status_of(&control);
+        drop(control);
+        self.changed.notify_all();
+        let failed = SimulationFailed {
+            message: error.to_string(),
+            cause: error,
+        };
+        let _ = events::send(
+            self.sink.as_ref(),
+            "simulation_failed",
+            SessionEvent {
+                session: Some(self.id),
+                data: failed,
+            },
+        );
+        self.publish(&status);
+    }
+
+    pub fn result(&self) -> Option<RunResult> {
+        self.result.lock().unwrap().clone()
+    }
+
+    pub fn log(&self) -> Option<EventLog> {
+        self.result.lock().unwrap().as_ref().map(|result| result.log.clone())
+    }
+}
+
+// Sessions ----------------------------------------------------------------------------------------
+
+/// Every session of the app, finished ones included. The desktop app keeps it in Tauri's
+/// managed state.
+#[derive(Default)]
+pub struct Sessions {
+    next_id: AtomicU64,
+    sessions: Mutex<HashMap<SessionId, Arc<Session>>>,
+}
+
+impl Sessions {
+    /// A new session. Runs already going keep going, their events are told apart by the
+    /// session ID they carry. A session started `paused` makes no decision until it's resumed
+    /// or stepped, so the UI can learn its ID before any of its events arrive.
+    pub fn start(
+        &self,
+        queue: String,
+        sink: Arc<dyn EventSink>,
+        speed: PlaybackSpeed,
+        paused: bool,
+    ) -> Result<Arc<Session>, SimError> {
+        SimError::check(check_speed("speed", speed))?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let session = Arc::new(Session::new(id, queue, sink, speed, paused));
+        self.sessions.lock().unwrap().insert(id, session.clone());
+        session.publish(&session.status());
+        Ok(session)
+    }
+
+    pub fn get(&self, id: SessionId) -> Result<Arc<Session>, String> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .ok_or(format!("There is no simulation {}", id))
+    }
+
+    pub fn list(&self) -> Vec<SessionStatus> {
+        let mut statuses: Vec<SessionStatus> =
+            self.sessions.lock().unwrap().values().map(|s| s.status()).collect();
+        statuses.sort_by_key(|status| status.id);
+        statuses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queue_engine::Queues;
+    use crate::scenario::{ProcessSource, Scenario};
+    use crate::workload::WorkloadEntry;
+    use serde_json::Value;
+    use std::thread::{self, JoinHandle};
+
+    type Run = JoinHandle<Result<RunResult, SimError>>;
+
+    /// Three processes run live at full speed on a thread of their own, paused from the start.
+    fn start_paused() -> (Arc<Session>, Run) {
+        let workload = (0..3)
+            .map(|_| WorkloadEntry {
+                name: None,
+                arrival_time: Duration::ZERO,
+                cpu_burst_time: Duration::from_millis(5),
+                process_type: None,
+                priority: None,
+                io_phases: Vec::new(),
+                deadline: None,
+            })
+            .collect();
+        let scenario = Scenario::new(Queues::FCFS, ProcessSource::Workload(workload));
+        let sink = Arc::new(|_: &str, _: Value| Ok(()));
+        let sessions = Sessions::default();
+        let session = sessions.start(String::from("FCFS"), sink, PlaybackSpeed::Max, true).unwrap();
+        let run = {
+            let session = session.clone();
+            thread::spawn(move || scenario.run_in(session))
+        };
+        (session, run)
+    }
+
+    fn wait_for(session: &Session, done: impl Fn(&SessionStatus) -> bool) -> SessionStatus {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let status = session.status();
+            if done(&status) {
+                return status;
+            }
+            assert!(Instant::now() < deadline, "stuck at {:?}", status);
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn a_step_makes_exactly_one_decision() {
+        let (session, run) = start_paused();
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(session.status().decisions, 0);
+
+        session.step().unwrap();
+        wait_for(&session, |status| status.decisions == 1);
+        thread::sleep(Duration::from_millis(20));
+        let status = session.status();
+        assert_eq!((status.state, status.decisions), (SessionState::Paused, 1));
+
+        // resumed after a step, the run goes on to the end
+        session.resume().unwrap();
+        run.join().unwrap().unwrap();
+        let status = wait_for(&session, |status| status.state == SessionState::Finished);
+        assert!(status.decisions > 1);
+    }
+
+    #[test]
+    fn a_cancelled_run_stays_cancelled() {
+        let (session, run) = start_paused();
+        assert_eq!(session.cancel().unwrap().state, SessionState::Cancelled);
+        // the run still ends through finish, which leaves the state alone
+        run.join().unwrap().unwrap();
+        assert_eq!(session.status().state, SessionState::Cancelled);
+        assert_eq!(session.status().decisions, 0);
+        assert!(session.resume().is_err());
+    }
+
+    #[test]
+    fn a_finished_run_can_still_be_asked_for() {
+        let (session, run) = start_paused();
+        session.resume().unwrap();
+        let result = run.join().unwrap().unwrap();
+        assert_eq!(session.status().state, SessionState::Finished);
+        assert_eq!(session.result().unwrap().processes.len(), result.processes.len());
+        assert!(session.log().is_some_and(|log| !log.events.is_empty()));
+        assert!(session.pause().is_err());
+        assert_eq!(session.status().state, SessionState::Finished);
+    }
+}
@@ -0,0 +1,453 @@
+use crate::analytic::{self, AnalyticReport, Workload};
+use crate::error::SimError;
+use crate::events::{EventLog, Recorder, SimEvent};
+use crate::plugin::PluginPolicy;
+use crate::policy::ScriptPolicy;
+use crate::process_gen::{Process, SerializableProcess};
+use crate::queue_engine::{calculate_metrics, MetricValue, QueueDiscipline, ReadyQueue, MLFQ, MLQ, Queues};
+use crate::sampling::{self, LittlesLaw, QueueSample, QueueState, Sampler, DEFAULT_SAMPLE_PERIOD};
+use crate::starvation::{LevelStarvation, StarvationDetector};
+use rand::Rng;
+use rand_distr::{Distribution, Exp};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+// Utils -------------------------------------------------------------------------------------------
+pub struct ExponentialGenerator {
+    exp: Exp<f64>,
+}
+
+impl ExponentialGenerator {
+    pub fn new(rate: f64) -> Result<Self, SimError> {
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(SimError::InvalidRate { rate });
+        }
+        let exp = Exp::new(rate).map_err(|_| SimError::InvalidRate { rate })?;
+        Ok(Self { exp })
+    }
+
+    pub fn generate<R: Rng>(&self, rng: &mut R) -> Duration {
+        loop {
+            let result = self.exp.sample(rng);
+
+            // Break the result into the integer part (seconds) and the fractional part (nanoseconds)
+            let secs = result.floor() as u64; // Integer part as seconds
+            let nanos = ((result - secs as f64) * 1e9).round() as u32; // Fractional part as nanoseconds
+
+            if secs > 0 || nanos > 0 {
+                return Duration::new(secs, nanos);
+            }
+        }
+    }
+
+    pub fn generate_accumulative<R: Rng>(&self, size: usize, rng: &mut R) -> Vec<Duration> {
+        let mut initial_value = Duration::new(0, 0); // Initialize the first arrival time
+        let mut result = Vec::with_capacity(size);
+
+        for _ in 0..size {
+            let value = self.generate(rng);
+            initial_value += value; // Accumulate values to ensure they are ascending
+            result.push(initial_value);
+        }
+
+        result
+    }
+}
+/// The optional parts of a run, all of them off by default.
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    pub mlfq_boost_period: Option<Duration>,
+    pub sample_period: Option<Duration>,
+    pub starvation_threshold: Option<Duration>, // waiting longer than this flags a process
+    pub policy: Option<Arc<ScriptPolicy>>, // for the SCRIPT algorithm and levels
+    pub plugin: Option<Arc<PluginPolicy>>, // for the PLUGIN algorithm and levels
+}
+
+impl RunOptions {
+    /// What a run in the UI gets: the queues are sampled for the charts.
+    pub fn ui(starvation_threshold: Option<Duration>) -> Self {
+        RunOptions {
+            sample_period: Some(DEFAULT_SAMPLE_PERIOD),
+            starvation_threshold,
+            ..RunOptions::default()
+        }
+    }
+}
+
+/// What a finished run leaves behind, for callers that don't follow it through the events.
+#[derive(Clone, Serialize)]
+pub struct RunResult {
+    pub queue: String,
+    pub metrics: HashMap<String, MetricValue>, // over every finished process
+    pub level_metrics: Vec<HashMap<String, MetricValue>>, // per level, only for MLQ and MLFQ
+    pub processes: Vec<SerializableProcess>,
+    pub analytic: Option<AnalyticReport>, // the queueing model of the queue, if it has one
+    pub samples: Vec<QueueSample>, // empty unless the run was sampled
+    pub littles_law: Option<LittlesLaw>,
+    pub starvation: Vec<LevelStarvation>, // one per level
+    #[serde(skip)]
+    pub log: EventLog,
+}
+
+enum Queue {
+    // since there are multiple queue types rust prevent us to use a single value to store
+    // ready queue, mlq, mlfq in single variable since they are different structs.
+    // therefore using a multi type Queue enum is necessary
+    ReadyQueue(ReadyQueue),
+    MultiLevel(MLQ),
+    MultiLevelFeedBack(MLFQ),
+}
+
+// Simulation: -------------------------------------------------------------------------------------
+pub struct Simulator {
+    lambda_rate_arrival: f64,
+    lambda_rate_cbt: f64,
+}
+
+impl Simulator {
+    fn generate_queue(
+        queue_discipline: Queues,
+        context_switch: Duration,
+        time_quantum: Duration,
+        list_of_disciplines: Option<[QueueDiscipline; 4]>,
+        options: &RunOptions,
+    ) -> Result<Queue, SimError> {
+        //generating The Ready queue based on single or multi level, levels are RR unless told
+        let selected_disciplines = list_of_disciplines.unwrap_or([QueueDiscipline::RR; 4]);
+
+        let queue: Queue;
+        if let Queues::MLFQ = queue_discipline {
+            let mut mlfq = MLFQ::init(
+                selected_disciplines[0],
+                selected_disciplines[1],
+                selected_disciplines[2],
+                selected_disciplines[3],
+                context_switch,
+                time_quantum,
+            );
+            mlfq.boost_period = options.mlfq_boost_period;
+            if let Some(policy) = &options.policy {
+                mlfq.set_policy(policy.clone());
+            }
+            if let Some(plugin) = &options.plugin {
+                mlfq.set_plugin(plugin)?;
+            }
+            queue = Queue::MultiLevelFeedBack(mlfq);
+        } else if let Queues::MLQ = queue_discipline {
+            let mut mlq = MLQ::init(
+                selected_disciplines[0],
+                selected_disciplines[1],
+                selected_disciplines[2],
+                selected_disciplines[3],
+                context_switch,
+                time_quantum,
+            );
+            if let Some(policy) = &options.policy {
+                mlq.set_policy(policy.clone());
+            }
+            if let Some(plugin) = &options.plugin {
+                mlq.set_plugin(plugin)?;
+            }
+            queue = Queue::MultiLevel(mlq);
+        } else {
+            //convert queue type to queues_discipline
+            let queue_discipline = match queue_discipline {
+                Queues::FIFO => QueueDiscipline::FIFO,
+                Queues::FCFS => QueueDiscipline::FCFS,
+                Queues::SPN => QueueDiscipline::SPN,
+                Queues::SJF => QueueDiscipline::SJF,
+                Queues::HRRN => QueueDiscipline::HRRN,
+                Queues::RR => QueueDiscipline::RR,
+                Queues::SRF => QueueDiscipline::SRF,
+                Queues::SCRIPT => QueueDiscipline::SCRIPT,
+                Queues::PLUGIN => QueueDiscipline::PLUGIN,
+                _ => QueueDiscipline::FCFS,
+            };
+            let mut ready_queue = ReadyQueue::new(queue_discipline, context_switch, time_quantum);
+            if let Some(policy) = &options.policy {
+                ready_queue.set_policy(policy.clone());
+            }
+            if let Some(plugin) = &options.plugin {
+                ready_queue.set_plugin(plugin)?;
+            }
+            queue = Queue::ReadyQueue(ready_queue);
+        }
+        Ok(queue)
+    }
+
+    fn enqueue_queue(q: &mut Queue, process: Process) {
+        match q {
+            Queue::ReadyQueue(ref mut ready_queue) => ready_queue.enqueue(process),
+            Queue::MultiLevel(ref mut mlq) => mlq.enqueue(process),
+            Queue::MultiLevelFeedBack(ref mut mlfq) => mlfq.enqueue(process),
+        };
+    }
+
+    fn execute_queue(q: &mut Queue, recorder: &mut Recorder) -> Result<(), SimError> {
+        match q {
+            Queue::ReadyQueue(ref mut ready_queue) => {
+                ready_queue.execute_next(recorder, false)?;
+            }
+            Queue::MultiLevel(ref mut mlq) => {
+                mlq.execute_next(recorder)?;
+            }
+            Queue::MultiLevelFeedBack(ref mut mlfq) => {
+                mlfq.execute_next(recorder)?;
+            }
+        };
+        Ok(())
+    }
+
+    fn is_queue_empty(q: &Queue) -> bool {
+        match q {
+            Queue::ReadyQueue(ready_queue) => ready_queue.is_queue_empty(),
+            Queue::MultiLevel(mlq) => mlq.is_queue_empty(),
+            Queue::MultiLevelFeedBack(mlfq) => mlfq.is_queue_empty(),
+        }
+    }
+
+    fn has_ready(q: &Queue) -> bool {
+        match q {
+            Queue::ReadyQueue(ready_queue) => ready_queue.has_ready(),
+            Queue::MultiLevel(mlq) => mlq.has_ready(),
+            Queue::MultiLevelFeedBack(mlfq) => mlfq.has_ready(),
+        }
+    }
+
+    fn wake_queue(q: &mut Queue, recorder: &mut Recorder) {
+        // processes whose I/O is over go back to the ready queue they blocked in
+        match q {
+            Queue::ReadyQueue(ref mut ready_queue) => ready_queue.wake_blocked(recorder),
+            Queue::MultiLevel(ref mut mlq) => mlq.wake_blocked(recorder),
+            Queue::MultiLevelFeedBack(ref mut mlfq) => mlfq.wake_blocked(recorder),
+        }
+    }
+
+    fn update_data(q: &mut Queue, recorder: &mut Recorder) {
+        if let Queue::ReadyQueue(ref mut ready_queue) = q {
+            let data = ready_queue.get_data();
+            recorder.emit("update_process", data);
+        }
+    }
+
+    fn calc_q_metrics(q: &mut Queue, recorder: &mut Recorder) {
+        match q {
+            Queue::ReadyQueue(ref mut ready_queue) => {
+                let data = ready_queue.calculate_metrics();
+                recorder.emit("send_metrics", data);
+            }
+            Queue::MultiLevel(ref mut mlq) => {
+                let data = mlq.calculate_metric();
+                recorder.emit("send_metrics_mlq", data);
+            }
+            Queue::MultiLevelFeedBack(ref mut mlfq) => {
+                let data = mlfq.calculate_metric();
+                recorder.emit("send_metrics_mlfq", data)
+            }
+        }
+    }
+
+    fn arrive(q: &mut Queue, process: Process, recorder: &mut Recorder) {
+        recorder.record(SimEvent::Arrival {
+            process: process.to_serializable(),
+        });
+        Simulator::enqueue_queue(q, process); // enqueue process at this point.
+    }
+
+    fn next_wake(q: &Queue) -> Option<SystemTime> {
+        match q {
+            Queue::ReadyQueue(ready_queue) => ready_queue.next_wake(),
+            Queue::MultiLevel(mlq) => mlq.next_wake(),
+            Queue::MultiLevelFeedBack(mlfq) => mlfq.next_wake(),
+        }
+    }
+
+    fn levels(q: &Queue) -> Vec<&ReadyQueue> {
+        match q {
+            Queue::ReadyQueue(ready_queue) => vec![ready_queue],
+            Queue::MultiLevel(mlq) => vec![&mlq.queue_1, &mlq.queue_2, &mlq.queue_3, &mlq.queue_4],
+            Queue::MultiLevelFeedBack(mlfq) => {
+                vec![&mlfq.queue_1, &mlfq.queue_2, &mlfq.queue_3, &mlfq.queue_4]
+            }
+        }
+    }
+
+    fn queue_state(q: &Queue) -> QueueState {
+        let levels = Simulator::levels(q);
+        let mut ready: Vec<usize> = levels.iter().map(|level| level.ready_len()).collect();
+        // the next step runs a process of the highest level that has one
+        let running = match ready.iter_mut().find(|n| **n > 0) {
+            Some(n) => {
+                *n -= 1;
+                1
+            }
+            None => 0,
+        };
+        QueueState {
+            ready,
+            running,
+            blocked: levels.iter().map(|level| level.blocked_len()).sum(),
+            completed: levels.iter().map(|level| level.finished().len()).sum(),
+        }
+    }
+
+    fn collect_result(
+        q: &Queue,
+        queue_discipline: Queues,
+        workload: Option<Workload>,
+        sampler: Option<Sampler>,
+        detector: StarvationDetector,
+        mut recorder: Recorder,
+    ) -> Result<RunResult, SimError> {
+        let queue_name = queue_discipline.to_string();
+        let (metrics, level_metrics, finished) = match q {
+            Queue::ReadyQueue(ready_queue) => (ready_queue.calculate_metrics(), Vec::new(), ready_queue.finished().to_vec()),
+            Queue::MultiLevel(mlq) => {
+                let finished = mlq.finished();
+                let metrics = calculate_metrics(&finished, queue_name.clone());
+                (metrics, mlq.calculate_metric().to_vec(), finished)
+            }
+            Queue::MultiLevelFeedBack(mlfq) => {
+                let finished = mlfq.finished();
+                let metrics = calculate_metrics(&finished, queue_name.clone());
+                (metrics, mlfq.calculate_metric().to_vec(), finished)
+            }
+        };
+        let analytic = workload
+            .and_then(|w| analytic::predict(queue_discipline, &w))
+            .map(|prediction| analytic::report(prediction, &metrics));
+        if let Some(ref report) = analytic {
+            recorder.emit("send_analytic", report.clone());
+        }
+        let samples = sampler.map(|sampler| sampler.samples).unwrap_or_default();
+        let littles_law = sampling::littles_law(&samples, &finished, recorder.clock.elapsed());
+        if let Some(ref littles_law) = littles_law {
+            recorder.emit("littles_law", littles_law.clone());
+        }
+        let levels: Vec<&[Process]> = Simulator::levels(q).iter().map(|l| l.finished()).collect();
+        let starvation = detector.finish(&levels);
+        recorder.emit("starvation_summary", starvation.clone());
+        recorder.check()?;
+        Ok(RunResult {
+            queue: queue_name,
+            metrics,
+            level_metrics,
+            processes: finished.iter().map(|p| p.to_serializable()).collect(),
+            analytic,
+            samples,
+            littles_law,
+            starvation,
+            log: recorder.finish(),
+        })
+    }
+
+    /// Generates `num_of_processes` processes with exponential inter-arrival and burst times,
+    /// arriving from `start` on.
+    pub fn generate_processes<R: Rng>(
+        &self,
+        num_of_processes: usize,
+        start: SystemTime,
+        rng: &mut R,
+    ) -> Result<Vec<Process>, SimError> {
+        let exp_for_arrival = ExponentialGenerator::new(self.lambda_rate_arrival)?;
+        let exp_for_cbt = ExponentialGenerator::new(self.lambda_rate_cbt)?;
+        let arrival_randoms = exp_for_arrival.generate_accumulative(num_of_processes, rng);
+        let mut processes = Vec::with_capacity(num_of_processes);
+        for element in arrival_randoms {
+            let cpu_burst_time = exp_for_cbt.generate(rng);
+            processes.push(Process::with_rng(cpu_burst_time, start + element, None, rng));
+        }
+        Ok(processes)
+    }
+
+    /// Runs `processes` to completion on the recorder's clock. Nothing here waits for real time
+    /// unless the clock is paced, so the same run works for the UI and headless. An error stops
+    /// the run where it is, nothing of it is collected.
+    pub fn simulate(
+        queue_discipline: Queues,
+        context_switch: Duration,
+        time_quantum: Duration,
+        list_of_disciplines: Option<[QueueDiscipline; 4]>,
+        options: RunOptions,
+        mut processes: Vec<Process>,
+        mut recorder: Recorder,
+    ) -> Result<RunResult, SimError> {
+        // the model is fitted to the processes before the run takes them
+        let workload = Workload::from_processes(&processes);
+        //generating queue: ------------------------------------------------------------------------
+        let mut queue = Simulator::generate_queue(
+            queue_discipline,
+            context_switch,
+            time_quantum,
+            list_of_disciplines,
+            &options,
+        )?;
+        processes.sort_by_key(|p| p.arrival_time);
+        let start = recorder.clock.start();
+        let mut sampler = options.sample_period.map(|period| Sampler::new(period, start));
+        let levels = Simulator::levels(&queue).len();
+        let mut detector = StarvationDetector::new(options.starvation_threshold, levels);
+
+        // running the simulation ------------------------------------------------------------------
+        loop {
+            recorder.check()?;
+            if !recorder.clock.checkpoint() {
+                break; // cancelled, what finished so far is still collected
+            }
+            if processes.is_empty() && Simulator::is_queue_empty(&queue) {
+                Simulator::calc_q_metrics(&mut queue, &mut recorder);
+                break;
+            }
+
+            //check if the current time passed the first arrival time
+            let right_now = recorder.clock.now();
+            while !processes.is_empty() && right_now >= processes[0].arrival_time {
+                let process = processes.remove(0);
+                Simulator::arrive(&mut queue, process, &mut recorder);
+            }
+
+            //check if queue isn't empty execute the next process:
+            Simulator::wake_queue(&mut queue, &mut recorder);
+            let ready: Vec<&[Process]> = Simulator::levels(&queue).iter().map(|l| l.ready()).collect();
+            detector.check(&ready, recorder.clock.now(), &mut recorder);
+            let step_start = recorder.clock.elapsed();
+            let state = sampler.as_ref().map(|_| Simulator::queue_state(&queue));
+            let busy = Simulator::has_ready(&queue);
+            if busy {
+                Simulator::update_data(&mut queue, &mut recorder);
+                Simulator::execute_queue(&mut queue, &mut recorder)?;
+                Simulator::update_data(&mut queue, &mut recorder);
+            } else {
+                // the cpu is idle until the next arrival or the next process done with its I/O
+                let next_arrival = processes.first().map(|p| p.arrival_time);
+                let next_event = [next_arrival, Simulator::next_wake(&queue)]
+                    .into_iter()
+                    .flatten()
+                    .min();
+                if let Some(next_event) = next_event {
+                    recorder.clock.advance_to(next_event);
+                }
+            }
+
+            // sampling the queues over the step ---------------------------------------------------
+            if let (Some(sampler), Some(state)) = (sampler.as_mut(), state) {
+                let step_end = recorder.clock.elapsed();
+                let taken = sampler.step(&state, step_start, step_end, busy, &processes);
+                for sample in &sampler.samples[sampler.samples.len() - taken..] {
+                    recorder.emit("queue_sample", sample.clone());
+                }
+            }
+        }
+        Simulator::collect_result(&queue, queue_discipline, workload, sampler, detector, recorder)
+    }
+
+    /// A generator of processes with these exponential arrival and burst rates, per second.
+    pub fn init(lambda_rate_arrival: f64, lambda_rate_cbt: f64) -> Self {
+        Simulator {
+            lambda_rate_arrival,
+            lambda_rate_cbt,
+        }
+    }
+}
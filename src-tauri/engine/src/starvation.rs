@@ -0,0 +1,177 @@
+use crate::events::Recorder;
+use crate::process_gen::Process;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+// Utils -------------------------------------------------------------------------------------------
+
+/// Sent to the UI as `process_starving` the first time a ready process has waited longer than
+/// the threshold.
+#[derive(Debug, Clone, Serialize)]
+pub struct StarvingProcess {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub queue: usize, // the level it waits in, from 1
+    pub waiting_time: Duration,
+    pub threshold: Duration,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LevelStarvation {
+    /// How one level of the queue fared. `longest_unserved` is the longest stretch the level
+    /// had ready processes while the CPU was serving other levels, so it stays zero for a
+    /// single queue.
+    pub queue: usize,
+    pub starved_processes: usize,
+    pub longest_unserved: Duration,
+    pub max_waiting_time: Duration, // of the processes that finished in this level
+}
+
+// Detector ----------------------------------------------------------------------------------------
+
+pub struct StarvationDetector {
+    threshold: Option<Duration>,
+    flagged: HashSet<Uuid>,
+    unserved_since: Vec<Option<SystemTime>>,
+    levels: Vec<LevelStarvation>,
+}
+
+impl StarvationDetector {
+    pub fn new(threshold: Option<Duration>, levels: usize) -> Self {
+        StarvationDetector {
+            threshold,
+            flagged: HashSet::new(),
+            unserved_since: vec![None; levels],
+            levels: (1..=levels)
+                .map(|queue| LevelStarvation {
+                    queue,
+                    ..LevelStarvation::default()
+                })
+                .collect(),
+        }
+    }
+
+    /// Looks at the ready processes of every level right before a step, which serves the
+    /// highest level that has any.
+    pub fn check(&mut self, ready: &[&[Process]], now: SystemTime, recorder: &mut Recorder) {
+        let served = ready.iter().position(|processes| !processes.is_empty());
+        for (index, processes) in ready.iter().enumerate() {
+            let level = &mut self.levels[index];
+            let since = if processes.is_empty() || served == Some(index) {
+                self.unserved_since[index].take()
+            } else {
+                Some(*self.unserved_since[index].get_or_insert(now))
+            };
+            if let Some(since) = since {
+                let unserved = now.duration_since(since).unwrap_or_default();
+                level.longest_unserved = level.longest_unserved.max(unserved);
+            }
+
+            let Some(threshold) = self.threshold else {
+                continue;
+            };
+            for process in processes.iter() {
+                let waiting_time = process.waiting_at(now);
+                if waiting_time > threshold && self.flagged.insert(process.id) {
+                    level.starved_processes += 1;
+                    recorder.emit(
+                        "process_starving",
+                        StarvingProcess {
+                            id: process.id,
+                            name: process.name.clone(),
+                            queue: index + 1,
+                            waiting_time,
+                            threshold,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    /// The summary per level, `finished` are the processes that finished in each level.
+    pub fn finish(mut self, finished: &[&[Process]]) -> Vec<LevelStarvation> {
+        for (level, processes) in self.levels.iter_mut().zip(finished) {
+            level.max_waiting_time = processes
+                .iter()
+                .map(|p| p.metrics.total_waiting_time)
+                .max()
+                .unwrap_or_default();
+        }
+        self.levels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::SimEvent;
+    use crate::process_gen::ProcessType;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn process(arrival: SystemTime) -> Process {
+        Process::new(ms(50), arrival, Some(ProcessType::BatchProcess))
+    }
+
+    fn starving(recorder: Recorder) -> usize {
+        let log = recorder.finish();
+        let emitted = log.events.iter().filter(|e| {
+            matches!(&e.event, SimEvent::Emit { event, .. } if event == "process_starving")
+        });
+        emitted.count()
+    }
+
+    #[test]
+    fn a_process_is_flagged_once_past_the_threshold() {
+        let start = SystemTime::UNIX_EPOCH;
+        let mut recorder = Recorder::headless(String::from("FCFS"), start);
+        let mut detector = StarvationDetector::new(Some(ms(10)), 1);
+        let ready = [process(start), process(start + ms(5))];
+        for now in [0, 10, 11, 14, 20, 30] {
+            detector.check(&[&ready], start + ms(now), &mut recorder);
+        }
+        // the first is past 10 ms at 11, the second at 20
+        let levels = detector.finish(&[&[]]);
+        assert_eq!(levels[0].starved_processes, 2);
+        assert_eq!(starving(recorder), 2);
+    }
+
+    #[test]
+    fn without_a_threshold_nothing_is_flagged() {
+        let start = SystemTime::UNIX_EPOCH;
+        let mut recorder = Recorder::headless(String::from("FCFS"), start);
+        let mut detector = StarvationDetector::new(None, 1);
+        detector.check(&[&[process(start)]], start + ms(1000), &mut recorder);
+        assert_eq!(detector.finish(&[&[]])[0].starved_processes, 0);
+        assert_eq!(starving(recorder), 0);
+    }
+
+    #[test]
+    fn levels_are_summed_up_on_their_own() {
+        let start = SystemTime::UNIX_EPOCH;
+        let mut recorder = Recorder::headless(String::from("MLFQ"), start);
+        let mut detector = StarvationDetector::new(Some(ms(25)), 2);
+        let (high, low) = ([process(start)], [process(start)]);
+        // level 1 is served while level 2 waits, then level 2 gets the CPU at 40 ms
+        detector.check(&[&high, &low], start, &mut recorder);
+        detector.check(&[&high, &low], start + ms(30), &mut recorder);
+        detector.check(&[&[], &low], start + ms(40), &mut recorder);
+
+        let mut finished = process(start);
+        finished.metrics.total_waiting_time = ms(40);
+        let levels = detector.finish(&[&[], &[finished]]);
+        assert_eq!(levels[0].queue, 1);
+        assert_eq!(levels[0].longest_unserved, Duration::ZERO);
+        assert_eq!(levels[0].starved_processes, 1);
+        assert_eq!(levels[0].max_waiting_time, Duration::ZERO);
+        assert_eq!(levels[1].queue, 2);
+        assert_eq!(levels[1].longest_unserved, ms(40));
+        assert_eq!(levels[1].starved_processes, 1);
+        assert_eq!(levels[1].max_waiting_time, ms(40));
+    }
+}
@@ -0,0 +1,294 @@
+use serde::Serialize;
+
+// Utils -------------------------------------------------------------------------------------------
+
+/// A seed for replication `replication` of a run seeded with `seed`. Neighbouring seeds of a
+/// PRNG can give correlated streams, so the pair is mixed first (splitmix64).
+pub fn replication_seed(seed: u64, replication: u64) -> u64 {
+    let mut z = seed
+        .wrapping_add(replication.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+pub fn mean(samples: &[f64]) -> f64 {
+    samples.iter().sum::<f64>() / samples.len() as f64
+}
+
+/// Sample standard deviation (n - 1 in the denominator).
+pub fn std_dev(samples: &[f64]) -> f64 {
+    if samples.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(samples);
+    let squares: f64 = samples.iter().map(|x| (x - mean).powi(2)).sum();
+    (squares / (samples.len() - 1) as f64).sqrt()
+}
+
+/// Jain's fairness index, (Σx)² / (n Σx²). It is 1 when every sample is the same and 1/n
+/// when a single sample has everything.
+pub fn jain_index(samples: &[f64]) -> f64 {
+    let squares: f64 = samples.iter().map(|x| x * x).sum();
+    if samples.is_empty() || squares == 0.0 {
+        return 1.0;
+    }
+    samples.iter().sum::<f64>().powi(2) / (samples.len() as f64 * squares)
+}
+
+// Student's t distribution ------------------------------------------------------------------------
+
+fn ln_gamma(x: f64) -> f64 {
+    // Lanczos approximation, good to about 15 digits for x > 0
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+    let mut y = x;
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000_000_000_190_015;
+    for c in COEFFICIENTS {
+        y += 1.0;
+        series += c / y;
+    }
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    // modified Lentz's method for the continued fraction of the incomplete beta function
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+    for m in 1..300 {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 + even * d;
+        d = if d.abs() < TINY { 1.0 / TINY } else { 1.0 / d };
+        c = 1.0 + even / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        h *= d * c;
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 + odd * d;
+        d = if d.abs() < TINY { 1.0 / TINY } else { 1.0 / d };
+        c = 1.0 + odd / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        let step = d * c;
+        h *= step;
+        if (step - 1.0).abs() < 1e-14 {
+            break;
+        }
+    }
+    h
+}
+
+fn regularized_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// P(T <= t) for Student's t with `df` degrees of freedom.
+pub fn t_cdf(t: f64, df: f64) -> f64 {
+    let tail = 0.5 * regularized_beta(df / 2.0, 0.5, df / (df + t * t));
+    if t >= 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// The t such that P(T <= t) = p, found by bisection on the cdf.
+pub fn t_quantile(p: f64, df: f64) -> f64 {
+    let (mut low, mut high) = (-1e3, 1e3);
+    for _ in 0..200 {
+        let middle = (low + high) / 2.0;
+        if t_cdf(middle, df) < p {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    (low + high) / 2.0
+}
+
+// Confidence intervals ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfidenceInterval {
+    /// Student-t interval for the mean of independent samples. With fewer than two samples
+    /// there is no spread to estimate, so the bounds are left empty.
+    pub n: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub confidence: f64,
+    pub half_width: Option<f64>,
+    pub low: Option<f64>,
+    pub high: Option<f64>,
+}
+
+pub fn confidence_interval(samples: &[f64], confidence: f64) -> ConfidenceInterval {
+    let n = samples.len();
+    let mean = if n == 0 { f64::NAN } else { mean(samples) };
+    let std_dev = std_dev(samples);
+    let half_width = if n >= 2 {
+        let t = t_quantile(1.0 - (1.0 - confidence) / 2.0, (n - 1) as f64);
+        Some(t * std_dev / (n as f64).sqrt())
+    } else {
+        None
+    };
+    ConfidenceInterval {
+        n,
+        mean,
+        std_dev,
+        confidence,
+        half_width,
+        low: half_width.map(|h| mean - h),
+        high: half_width.map(|h| mean + h),
+    }
+}
+
+// Output analysis ---------------------------------------------------------------------------------
+
+/// Welch's procedure for the warm-up period: the replications are averaged point by point and
+/// smoothed with a moving average of `window` points on each side. The warm-up ends where the
+/// next `window + 1` smoothed points all lie within `tolerance` (relative) of the mean of the
+/// rest of the curve. Never more than half of the points are deleted.
+pub fn welch_warm_up(replications: &[Vec<f64>], window: usize, tolerance: f64) -> usize {
+    let length = replications.iter().map(|r| r.len()).min().unwrap_or(0);
+    if length < 2 * window + 2 {
+        return 0;
+    }
+    let averaged: Vec<f64> = (0..length)
+        .map(|i| replications.iter().map(|r| r[i]).sum::<f64>() / replications.len() as f64)
+        .collect();
+    // the first points have fewer neighbours, so their window shrinks to what's available
+    let smoothed: Vec<f64> = (0..length - window)
+        .map(|i| {
+            let reach = i.min(window);
+            mean(&averaged[i - reach..=i + reach])
+        })
+        .collect();
+
+    let limit = length / 2;
+    let mut rest_sum: f64 = smoothed.iter().sum();
+    for start in 0..limit.min(smoothed.len()) {
+        let rest = rest_sum / (smoothed.len() - start) as f64;
+        rest_sum -= smoothed[start];
+        let end = (start + window + 1).min(smoothed.len());
+        if smoothed[start..end]
+            .iter()
+            .all(|value| (value - rest).abs() <= tolerance * rest.abs())
+        {
+            return start;
+        }
+    }
+    limit
+}
+
+/// Splits one long run's observations into `batches` equal batches and returns their means.
+/// Batches long enough are nearly independent, so their means can go into a t interval. The
+/// observations that don't fill a whole batch at the end are dropped.
+pub fn batch_means(observations: &[f64], batches: usize) -> Vec<f64> {
+    if batches == 0 || observations.len() < batches {
+        return Vec::new();
+    }
+    let size = observations.len() / batches;
+    observations
+        .chunks_exact(size)
+        .take(batches)
+        .map(mean)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(value: f64, expected: f64, tolerance: f64) -> bool {
+        (value - expected).abs() < tolerance
+    }
+
+    #[test]
+    fn t_quantiles_match_the_tables() {
+        assert!(close(t_quantile(0.975, 9.0), 2.262_157, 1e-5));
+        assert!(close(t_quantile(0.975, 1.0), 12.706_205, 1e-4));
+        assert!(close(t_quantile(0.95, 30.0), 1.697_261, 1e-5));
+        assert!(close(t_quantile(0.025, 9.0), -2.262_157, 1e-5));
+        assert!(close(t_cdf(0.0, 5.0), 0.5, 1e-12));
+    }
+
+    #[test]
+    fn a_known_confidence_interval() {
+        let samples: Vec<f64> = (1..=10).map(f64::from).collect();
+        let interval = confidence_interval(&samples, 0.95);
+        assert_eq!(interval.n, 10);
+        assert!(close(interval.mean, 5.5, 1e-12));
+        assert!(close(interval.std_dev, 3.027_650, 1e-6));
+        // t(0.975, 9) * s / sqrt(10)
+        assert!(close(interval.half_width.unwrap(), 2.165_858, 1e-5));
+        assert!(close(interval.low.unwrap(), 3.334_142, 1e-5));
+        assert!(close(interval.high.unwrap(), 7.665_858, 1e-5));
+    }
+
+    #[test]
+    fn one_sample_has_no_interval() {
+        let interval = confidence_interval(&[4.0], 0.95);
+        assert_eq!((interval.mean, interval.std_dev), (4.0, 0.0));
+        assert!(interval.half_width.is_none() && interval.low.is_none());
+    }
+
+    #[test]
+    fn jain_index_bounds() {
+        assert_eq!(jain_index(&[3.0, 3.0, 3.0]), 1.0);
+        assert!(close(jain_index(&[1.0, 0.0, 0.0, 0.0]), 0.25, 1e-12));
+        assert_eq!(jain_index(&[]), 1.0);
+    }
+
+    #[test]
+    fn batch_means_drop_the_partial_batch() {
+        let observations = [1.0, 3.0, 5.0, 7.0, 100.0];
+        assert_eq!(batch_means(&observations, 2), [2.0, 6.0]);
+        assert!(batch_means(&observations, 6).is_empty());
+    }
+
+    #[test]
+    fn welch_deletes_the_transient() {
+        // ten points climbing to a level of 10, then the level
+        let run: Vec<f64> = (0..100).map(|i| if i < 10 { i as f64 } else { 10.0 }).collect();
+        let deleted = welch_warm_up(&[run.clone(), run], 2, 0.05);
+        // the moving average spreads the climb over `window` more points
+        assert!((10..=12).contains(&deleted), "deleted {}", deleted);
+        assert_eq!(welch_warm_up(&[vec![5.0; 50]], 2, 0.05), 0);
+    }
+
+    #[test]
+    fn replication_seeds_differ() {
+        assert_ne!(replication_seed(0, 0), replication_seed(0, 1));
+        assert_ne!(replication_seed(0, 1), replication_seed(1, 0));
+        assert_eq!(replication_seed(3, 4), replication_seed(3, 4));
+    }
+}
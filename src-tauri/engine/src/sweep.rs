@@ -0,0 +1,611 @@
+use crate::events::Recorder;
+use crate::plugin::PluginPolicy;
+use crate::policy::ScriptPolicy;
+use crate::process_gen::{Process, SerializableProcess};
+use crate::queue_engine::{QueueDiscipline, Queues};
+use crate::simulator::{RunOptions, RunResult, Simulator};
+use crate::stats::{self, ConfidenceInterval};
+use crate::workload::WorkloadEntry;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+// Utils -------------------------------------------------------------------------------------------
+
+// a sweep this big would keep the machine busy for hours, it's most likely a typo in a range
+const MAX_RUNS: usize = 200_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SweepParameter {
+    TimeQuantum,     // milliseconds
+    ContextSwitch,   // milliseconds
+    ArrivalLambda,   // per second
+    BurstLambda,     // per second
+    MlfqBoostPeriod, // milliseconds, 0 turns the boost off
+    /// The simulator has a single CPU, see `check_cpus`. The axis is kept so a sweep over it
+    /// is refused with the reason rather than as an unknown parameter.
+    Cpus,
+}
+
+impl SweepParameter {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "time_quantum" | "quantum" => Some(SweepParameter::TimeQuantum),
+            "context_switch" => Some(SweepParameter::ContextSwitch),
+            "arrival_lambda" => Some(SweepParameter::ArrivalLambda),
+            "burst_lambda" => Some(SweepParameter::BurstLambda),
+            "mlfq_boost_period" | "boost_period" => Some(SweepParameter::MlfqBoostPeriod),
+            "cpus" | "cpu_count" => Some(SweepParameter::Cpus),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SweepParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SweepParameter::TimeQuantum => write!(f, "time_quantum"),
+            SweepParameter::ContextSwitch => write!(f, "context_switch"),
+            SweepParameter::ArrivalLambda => write!(f, "arrival_lambda"),
+            SweepParameter::BurstLambda => write!(f, "burst_lambda"),
+            SweepParameter::MlfqBoostPeriod => write!(f, "mlfq_boost_period"),
+            SweepParameter::Cpus => write!(f, "cpus"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepRange {
+    pub start: f64,
+    pub end: f64, // included when the steps land on it
+    pub step: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepAxis {
+    /// One swept parameter, its points are `values` followed by the points of `range`.
+    pub parameter: SweepParameter,
+    #[serde(default)]
+    pub values: Vec<f64>,
+    pub range: Option<SweepRange>,
+}
+
+impl SweepAxis {
+    pub fn points(&self) -> Result<Vec<f64>, String> {
+        let mut points = self.values.clone();
+        if let Some(range) = &self.range {
+            if range.step.is_nan() || range.step <= 0.0 || !range.start.is_finite() || !range.end.is_finite() {
+                return Err(format!("the range of {} needs a positive step", self.parameter));
+            }
+            let count = ((range.end - range.start) / range.step + 1e-9).floor();
+            if count < 0.0 || count >= MAX_RUNS as f64 {
+                return Err(format!("the range of {} has no usable points", self.parameter));
+            }
+            for index in 0..=(count as usize) {
+                points.push(range.start + index as f64 * range.step);
+            }
+        }
+        if points.is_empty() {
+            return Err(format!("{} has no values to sweep", self.parameter));
+        }
+        Ok(points)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum WarmUp {
+    /// How many of the first processes to finish are left out of the metrics, since a run
+    /// starts with an empty system and its first processes wait less than in steady state.
+    None,
+    Fixed { count: usize },
+    /// picked with Welch's moving average procedure on the first metric of the spec
+    Welch { window: usize, tolerance: f64 },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Estimator {
+    /// One sample per replication.
+    Replications,
+    /// One sample per batch of a long run, the batches of every replication together.
+    BatchMeans { batches: usize },
+}
+
+// the metrics that are an average over processes, and the per process value they average
+const PER_PROCESS_METRICS: [&str; 3] = [
+    "average_waiting_time",
+    "average_turnaround_time",
+    "average_response_time",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SweepSpec {
+    /// The base scenario, every point of the sweep is this scenario with the swept parameters
+    /// replaced. Each point runs `replications` times for every algorithm, replication r uses
+    /// the same seed at every point so the points are compared on the same workloads.
+    pub algorithms: Vec<Queues>,
+    pub disciplines: Option<[QueueDiscipline; 4]>,
+    pub time_quantum: f64,   // milliseconds
+    pub context_switch: f64, // milliseconds
+    pub arrival_lambda: f64,
+    pub burst_lambda: f64,
+    pub processes: usize,
+    pub mlfq_boost_period: Option<f64>, // milliseconds
+    pub script: Option<String>, // the Rhai source of the SCRIPT algorithm and levels
+    pub plugin: Option<PathBuf>, // the WebAssembly policy of the PLUGIN algorithm and levels
+    pub workload: Option<Vec<WorkloadEntry>>, // replaces the generated processes when given
+    pub axes: Vec<SweepAxis>,
+    pub replications: usize,
+    pub seed: u64,
+    pub confidence: f64,
+    pub metrics: Vec<String>,
+    pub warm_up: WarmUp,
+    pub estimator: Estimator,
+}
+
+impl Default for SweepSpec {
+    fn default() -> Self {
+        SweepSpec {
+            algorithms: vec![Queues::RR],
+            disciplines: None,
+            time_quantum: 10.0,
+            context_switch: 1.0,
+            arrival_lambda: 1.0,
+            burst_lambda: 2.0,
+            processes: 100,
+            mlfq_boost_period: None,
+            script: None,
+            plugin: None,
+            workload: None,
+            axes: Vec::new(),
+            replications: 10,
+            seed: 0,
+            confidence: 0.95,
+            metrics: vec![
+                String::from("average_waiting_time"),
+                String::from("average_turnaround_time"),
+                String::from("average_response_time"),
+                String::from("cpu_utilization"),
+            ],
+            warm_up: WarmUp::None,
+            estimator: Estimator::Replications,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepRow {
+    pub algorithm: String,
+    pub parameters: BTreeMap<String, f64>, // the swept parameters at this point
+    pub metric: String,
+    pub warm_up_deleted: usize, // processes left out at the start of every replication
+    #[serde(flatten)]
+    pub interval: ConfidenceInterval,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepResult {
+    pub parameters: Vec<SweepParameter>,
+    pub rows: Vec<SweepRow>,
+}
+
+impl SweepResult {
+    /// One row per algorithm, point and metric, with a column per swept parameter.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("algorithm");
+        for parameter in &self.parameters {
+            csv.push_str(&format!(",{}", parameter));
+        }
+        csv.push_str(",metric,warm_up_deleted,n,mean,std_dev,ci_low,ci_high,half_width\n");
+        let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+        for row in &self.rows {
+            csv.push_str(&row.algorithm);
+            for parameter in &self.parameters {
+                csv.push_str(&format!(",{}", row.parameters[&parameter.to_string()]));
+            }
+            csv.push_str(&format!(
+                ",{},{},{},{},{},{},{},{}\n",
+                row.metric,
+                row.warm_up_deleted,
+                row.interval.n,
+                row.interval.mean,
+                row.interval.std_dev,
+                optional(row.interval.low),
+                optional(row.interval.high),
+                optional(row.interval.half_width),
+            ));
+        }
+        csv
+    }
+}
+
+// Sweep -------------------------------------------------------------------------------------------
+
+struct Point {
+    time_quantum: Duration,
+    context_switch: Duration,
+    arrival_lambda: f64,
+    burst_lambda: f64,
+    boost_period: Option<Duration>,
+}
+
+fn milliseconds(parameter: SweepParameter, value: f64) -> Result<Duration, String> {
+    // negative, infinite or too long for a duration
+    Duration::try_from_secs_f64(value / 1000.0)
+        .map_err(|_| format!("{} can't be {}", parameter, value))
+}
+
+fn point(spec: &SweepSpec, values: &[(SweepParameter, f64)]) -> Result<Point, String> {
+    let (mut time_quantum, mut context_switch) = (spec.time_quantum, spec.context_switch);
+    let (mut arrival_lambda, mut burst_lambda) = (spec.arrival_lambda, spec.burst_lambda);
+    let mut boost_period = spec.mlfq_boost_period;
+    for (parameter, value) in values {
+        match parameter {
+            SweepParameter::TimeQuantum => time_quantum = *value,
+            SweepParameter::ContextSwitch => context_switch = *value,
+            SweepParameter::ArrivalLambda => arrival_lambda = *value,
+            SweepParameter::BurstLambda => burst_lambda = *value,
+            SweepParameter::MlfqBoostPeriod => boost_period = Some(*value).filter(|v| *v > 0.0),
+            SweepParameter::Cpus => {} // always 1, see check_axis
+        }
+    }
+    let time_quantum = milliseconds(SweepParameter::TimeQuantum, time_quantum)?;
+    if time_quantum.is_zero() {
+        return Err(String::from("the time quantum must be greater than 0"));
+    }
+    Ok(Point {
+        time_quantum,
+        context_switch: milliseconds(SweepParameter::ContextSwitch, context_switch)?,
+        arrival_lambda,
+        burst_lambda,
+        boost_period: boost_period
+            .map(|b| milliseconds(SweepParameter::MlfqBoostPeriod, b))
+            .transpose()?,
+    })
+}
+
+/// Refuses axes whose points would all give the same runs, so the rows don't look like a
+/// sweep that isn't one.
+fn check_axis(spec: &SweepSpec, parameter: SweepParameter, points: &[f64]) -> Result<(), String> {
+    match parameter {
+        SweepParameter::ArrivalLambda | SweepParameter::BurstLambda if spec.workload.is_some() => {
+            Err(format!(
+                "{} only changes generated processes, the sweep replays a workload",
+                parameter
+            ))
+        }
+        // every run is scheduled on one CPU, spreading it over several would take a
+        // multiprocessor simulator, which the engine isn't
+        SweepParameter::Cpus => match points.iter().find(|cpus| **cpus != 1.0) {
+            Some(cpus) => Err(format!(
+                "the simulator has a single CPU, cpus = {} can't be simulated",
+                cpus
+            )),
+            None => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+fn grid(axes: &[SweepAxis], points: &[Vec<f64>]) -> Vec<Vec<(SweepParameter, f64)>> {
+    // every combination of the axes' points, the last axis changing fastest
+    let mut grid: Vec<Vec<(SweepParameter, f64)>> = vec![Vec::new()];
+    for (axis, points) in axes.iter().zip(points) {
+        grid = grid
+            .into_iter()
+            .flat_map(|combination| {
+                points.iter().map(move |value| {
+                    let mut combination = combination.clone();
+                    combination.push((axis.parameter, *value));
+                    combination
+                })
+            })
+            .collect();
+    }
+    grid
+}
+
+fn processes(spec: &SweepSpec, point: &Point, rng: &mut StdRng) -> Result<Vec<Process>, String> {
+    match &spec.workload {
+        Some(workload) => Ok(workload
+            .iter()
+            .map(|entry| entry.to_process(SystemTime::UNIX_EPOCH, rng))
+            .collect()),
+        None => Ok(Simulator::init(point.arrival_lambda, point.burst_lambda).generate_processes(
+            spec.processes,
+            SystemTime::UNIX_EPOCH,
+            rng,
+        )?),
+    }
+}
+
+fn observations(result: &RunResult, metric: &str) -> Vec<f64> {
+    // per process values in the order the processes finished, in milliseconds
+    let mut finished: Vec<&SerializableProcess> = result.processes.iter().collect();
+    finished.sort_by_key(|p| p.arrival_time + chrono::Duration::from_std(p.metrics.total_time).unwrap_or_default());
+    finished
+        .iter()
+        .map(|p| match metric {
+            "average_waiting_time" => p.metrics.total_waiting_time,
+            "average_turnaround_time" => p.metrics.total_time,
+            _ => p.metrics.response_time,
+        })
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .collect()
+}
+
+/// The samples of every metric for one algorithm at one point, and how many processes the
+/// warm-up deletion left out of each replication.
+fn summarize(spec: &SweepSpec, results: &[RunResult]) -> Result<(usize, Vec<Vec<f64>>), String> {
+    if let (WarmUp::None, Estimator::Replications) = (spec.warm_up, spec.estimator) {
+        // the run's own metrics, so the ones that aren't per process averages work here too
+        let mut samples = Vec::with_capacity(spec.metrics.len());
+        for metric in &spec.metrics {
+            let values = results
+                .iter()
+                .map(|result| result.metrics.get(metric).and_then(|m| m.as_f64()))
+                .collect::<Option<Vec<f64>>>()
+                .ok_or(format!("\"{}\" is not a numeric metric", metric))?;
+            samples.push(values);
+        }
+        return Ok((0, samples));
+    }
+
+    if let Some(metric) = spec.metrics.iter().find(|m| !PER_PROCESS_METRICS.contains(&m.as_str())) {
+        return Err(format!(
+            "\"{}\" isn't an average over processes, so it can't be used with warm-up deletion or batch means",
+            metric
+        ));
+    }
+    let observations: Vec<Vec<Vec<f64>>> = spec
+        .metrics
+        .iter()
+        .map(|metric| results.iter().map(|r| observations(r, metric)).collect())
+        .collect();
+    let deleted = match spec.warm_up {
+        WarmUp::None => 0,
+        WarmUp::Fixed { count } => count,
+        WarmUp::Welch { window, tolerance } => {
+            stats::welch_warm_up(&observations[0], window, tolerance)
+        }
+    };
+
+    let mut samples = Vec::with_capacity(spec.metrics.len());
+    for per_replication in &observations {
+        let kept: Vec<&[f64]> = per_replication
+            .iter()
+            .map(|o| &o[deleted.min(o.len())..])
+            .collect();
+        if kept.iter().any(|o| o.is_empty()) {
+            return Err(format!(
+                "the warm-up deletion left no processes, the runs only have {} processes",
+                per_replication.iter().map(|o| o.len()).min().unwrap_or(0)
+            ));
+        }
+        let values: Vec<f64> = match spec.estimator {
+            Estimator::Replications => kept.iter().map(|o| stats::mean(o)).collect(),
+            Estimator::BatchMeans { batches } => {
+                let means: Vec<f64> = kept
+                    .iter()
+                    .flat_map(|o| stats::batch_means(o, batches))
+                    .collect();
+                if means.is_empty() {
+                    return Err(format!(
+                        "{} batches need at least {} processes after the warm-up",
+                        batches, batches
+                    ));
+                }
+                means
+            }
+        };
+        samples.push(values);
+    }
+    Ok((deleted, samples))
+}
+
+/// Runs every point of the grid spanned by `spec.axes` and summarizes each metric with a
+/// Student-t confidence interval over the replications.
+pub fn run_sweep(spec: &SweepSpec) -> Result<SweepResult, String> {
+    if spec.replications == 0 {
+        return Err(String::from("a sweep needs at least one replication"));
+    }
+    if spec.confidence.is_nan() || spec.confidence <= 0.0 || spec.confidence >= 1.0 {
+        return Err(String::from("the confidence level must be between 0 and 1"));
+    }
+    if spec.metrics.is_empty() {
+        return Err(String::from("choose at least one metric"));
+    }
+    if let Estimator::BatchMeans { batches } = spec.estimator {
+        if batches < 2 {
+            return Err(String::from("batch means need at least 2 batches"));
+        }
+    }
+    let algorithms = &spec.algorithms;
+    if algorithms.is_empty() {
+        return Err(String::from("choose at least one algorithm"));
+    }
+    for (index, axis) in spec.axes.iter().enumerate() {
+        if spec.axes[..index].iter().any(|a| a.parameter == axis.parameter) {
+            return Err(format!("{} is swept more than once", axis.parameter));
+        }
+    }
+    let policy = match &spec.script {
+        Some(source) => Some(Arc::new(ScriptPolicy::compile("script", source)?)),
+        None => None,
+    };
+    let plugin = match &spec.plugin {
+        Some(path) => Some(Arc::new(
+            PluginPolicy::load(path).map_err(|e| format!("{}: {}", path.display(), e))?,
+        )),
+        None => None,
+    };
+    let points = spec.axes.iter().map(|axis| axis.points()).collect::<Result<Vec<_>, _>>()?;
+    for (axis, points) in spec.axes.iter().zip(&points) {
+        check_axis(spec, axis.parameter, points)?;
+    }
+    // counted before the grid is built, which could take more memory than there is otherwise
+    let runs = points
+        .iter()
+        .map(|p| p.len())
+        .chain([algorithms.len(), spec.replications])
+        .try_fold(1usize, |runs, count| runs.checked_mul(count));
+    if runs.is_none_or(|runs| runs > MAX_RUNS) {
+        return Err(format!(
+            "the sweep would need more than {} simulations, use fewer points or replications",
+            MAX_RUNS
+        ));
+    }
+    let grid = grid(&spec.axes, &points);
+
+    let mut rows = Vec::new();
+    for values in &grid {
+        let point = point(spec, values)?;
+        let parameters: BTreeMap<String, f64> =
+            values.iter().map(|(p, v)| (p.to_string(), *v)).collect();
+        for queue in algorithms {
+            let mut results = Vec::with_capacity(spec.replications);
+            for replication in 0..spec.replications {
+                let seed = stats::replication_seed(spec.seed, replication as u64);
+                let mut rng = StdRng::seed_from_u64(seed);
+                results.push(Simulator::simulate(
+                    *queue,
+                    point.context_switch,
+                    point.time_quantum,
+                    spec.disciplines,
+                    RunOptions {
+                        mlfq_boost_period: point.boost_period,
+                        policy: policy.clone(),
+                        plugin: plugin.clone(),
+                        ..RunOptions::default()
+                    },
+                    processes(spec, &point, &mut rng)?,
+                    Recorder::batch(queue.to_string(), SystemTime::UNIX_EPOCH),
+                )?);
+            }
+            let (deleted, samples) = summarize(spec, &results)?;
+            for (metric, samples) in spec.metrics.iter().zip(samples) {
+                rows.push(SweepRow {
+                    algorithm: queue.to_string(),
+                    parameters: parameters.clone(),
+                    metric: metric.clone(),
+                    warm_up_deleted: deleted,
+                    interval: stats::confidence_interval(&samples, spec.confidence),
+                });
+            }
+        }
+    }
+
+    Ok(SweepResult {
+        parameters: spec.axes.iter().map(|axis| axis.parameter).collect(),
+        rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn axis(parameter: SweepParameter, values: Vec<f64>, range: Option<SweepRange>) -> SweepAxis {
+        SweepAxis {
+            parameter,
+            values,
+            range,
+        }
+    }
+
+    fn range(start: f64, end: f64, step: f64) -> Option<SweepRange> {
+        Some(SweepRange { start, end, step })
+    }
+
+    #[test]
+    fn points_are_the_values_then_the_range() {
+        let quantum = axis(SweepParameter::TimeQuantum, vec![1.0], range(10.0, 30.0, 10.0));
+        assert_eq!(quantum.points(), Ok(vec![1.0, 10.0, 20.0, 30.0]));
+        // an end the steps don't land on isn't a point
+        let quantum = axis(SweepParameter::TimeQuantum, Vec::new(), range(0.5, 1.0, 0.2));
+        assert_eq!(quantum.points().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn bad_ranges_are_rejected() {
+        for range in [range(1.0, 5.0, 0.0), range(5.0, 1.0, 1.0), range(0.0, f64::INFINITY, 1.0)] {
+            assert!(axis(SweepParameter::TimeQuantum, Vec::new(), range).points().is_err());
+        }
+        assert!(axis(SweepParameter::TimeQuantum, Vec::new(), None).points().is_err());
+    }
+
+    #[test]
+    fn the_grid_changes_the_last_axis_fastest() {
+        let axes = [
+            axis(SweepParameter::TimeQuantum, vec![1.0, 2.0], None),
+            axis(SweepParameter::ContextSwitch, vec![0.0, 0.5], None),
+        ];
+        let points: Vec<Vec<f64>> = axes.iter().map(|a| a.points().unwrap()).collect();
+        let switches: Vec<f64> = grid(&axes, &points).iter().map(|c| c[1].1).collect();
+        assert_eq!(switches, [0.0, 0.5, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn times_must_fit_a_duration() {
+        let quantum = SweepParameter::TimeQuantum;
+        assert_eq!(milliseconds(quantum, 1.5), Ok(Duration::from_micros(1500)));
+        assert!(milliseconds(quantum, -1.0).is_err());
+        assert!(milliseconds(quantum, 1e300).is_err());
+        assert!(milliseconds(quantum, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn too_many_runs_are_rejected_before_the_grid() {
+        let spec = SweepSpec {
+            axes: vec![
+                axis(SweepParameter::TimeQuantum, Vec::new(), range(1.0, 100_000.0, 1.0)),
+                axis(SweepParameter::ContextSwitch, Vec::new(), range(0.0, 100_000.0, 1.0)),
+            ],
+            ..SweepSpec::default()
+        };
+        assert!(run_sweep(&spec).unwrap_err().contains("more than"));
+    }
+
+    #[test]
+    fn axes_that_change_nothing_are_rejected() {
+        let spec = SweepSpec {
+            workload: Some(Vec::new()),
+            axes: vec![axis(SweepParameter::ArrivalLambda, vec![1.0, 2.0], None)],
+            ..SweepSpec::default()
+        };
+        assert!(run_sweep(&spec).unwrap_err().contains("replays a workload"));
+        let spec = SweepSpec {
+            axes: vec![axis(SweepParameter::Cpus, vec![1.0, 2.0], None)],
+            ..SweepSpec::default()
+        };
+        assert!(run_sweep(&spec).unwrap_err().contains("single CPU"));
+    }
+
+    #[test]
+    fn a_sweep_has_a_row_per_point_and_metric() {
+        let spec = SweepSpec {
+            processes: 20,
+            replications: 3,
+            seed: 42,
+            axes: vec![axis(SweepParameter::TimeQuantum, vec![5.0, 20.0], None)],
+            metrics: vec![String::from("average_waiting_time")],
+            ..SweepSpec::default()
+        };
+        let result = run_sweep(&spec).unwrap();
+        assert_eq!(result.rows.len(), 2);
+        assert_eq!(result.rows[1].parameters["time_quantum"], 20.0);
+        assert_eq!(result.rows[0].interval.n, 3);
+        // the same seed gives the same workloads, so the same intervals
+        let again = run_sweep(&spec).unwrap();
+        assert_eq!(result.rows[0].interval.mean, again.rows[0].interval.mean);
+        assert!(result.to_csv().starts_with("algorithm,time_quantum,metric,"));
+    }
+}
@@ -0,0 +1,271 @@
+use crate::workload::{RowIssue, ValidationReport, WorkloadEntry, WorkloadFormat};
+use serde::Deserialize;
+use std::time::Duration;
+
+// Utils -------------------------------------------------------------------------------------------
+
+// Field positions of the Standard Workload Format (Parallel Workloads Archive), zero based.
+// Every job is one line of 18 whitespace separated numbers and -1 marks a missing value.
+const JOB_NUMBER: usize = 0;
+const SUBMIT_TIME: usize = 1;
+const RUN_TIME: usize = 3;
+const ALLOCATED_PROCESSORS: usize = 4;
+const REQUESTED_PROCESSORS: usize = 7;
+const STATUS: usize = 10;
+const FIELD_COUNT: usize = 18;
+const STATUS_CANCELLED: i64 = 5;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SwfOptions {
+    /// multiplies every submit and run time, SWF times are in seconds so 0.001 replays one
+    /// trace second as one millisecond.
+    pub time_scale: f64,
+    /// only the first `limit` usable jobs of the trace are imported.
+    pub limit: Option<usize>,
+    /// our simulator has a single CPU, so a job that ran on N processors needs N times its run
+    /// time of CPU work. When false the run time is used as is.
+    pub scale_by_processors: bool,
+}
+
+impl Default for SwfOptions {
+    fn default() -> Self {
+        SwfOptions {
+            time_scale: 1.0,
+            limit: None,
+            scale_by_processors: false,
+        }
+    }
+}
+
+/// `seconds` times `scale`, none when that's too long for a duration.
+fn scaled(seconds: f64, scale: f64) -> Option<Duration> {
+    Duration::try_from_secs_f64(seconds * scale).ok()
+}
+
+// Parsing -----------------------------------------------------------------------------------------
+
+/// Turns an SWF trace into a workload. Arrivals are relative to the first job's submit time, so
+/// the replay starts right away no matter when the trace was recorded.
+pub fn import_swf(contents: &str, options: &SwfOptions) -> ValidationReport {
+    let mut report = ValidationReport {
+        format: WorkloadFormat::Swf,
+        valid: false,
+        processes: Vec::new(),
+        errors: Vec::new(),
+        warnings: Vec::new(),
+        kernel: None,
+    };
+    if !options.time_scale.is_finite() || options.time_scale <= 0.0 {
+        report.errors.push(RowIssue {
+            line: 0,
+            column: Some(String::from("time_scale")),
+            message: String::from("time scale must be a positive number"),
+        });
+        return report;
+    }
+
+    let mut first_submit: Option<f64> = None;
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        // ';' starts the header comments of an SWF file
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+        if options.limit.is_some_and(|limit| report.processes.len() >= limit) {
+            break;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < FIELD_COUNT {
+            report.errors.push(RowIssue {
+                line: line_number,
+                column: None,
+                message: format!("expected {} fields, found {}", FIELD_COUNT, fields.len()),
+            });
+            continue;
+        }
+        let numbers: Result<Vec<f64>, usize> = fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| f.parse::<f64>().map_err(|_| i))
+            .collect();
+        let numbers = match numbers {
+            Ok(numbers) => numbers,
+            Err(field) => {
+                report.errors.push(RowIssue {
+                    line: line_number,
+                    column: Some(format!("field {}", field + 1)),
+                    message: format!("\"{}\" is not a number", fields[field]),
+                });
+                continue;
+            }
+        };
+
+        let job = numbers[JOB_NUMBER] as i64;
+        let (submit, run_time) = (numbers[SUBMIT_TIME], numbers[RUN_TIME]);
+        if submit < 0.0 {
+            report.warnings.push(RowIssue {
+                line: line_number,
+                column: Some(String::from("submit time")),
+                message: format!("job {} has no submit time and was skipped", job),
+            });
+            continue;
+        }
+        if run_time <= 0.0 || numbers[STATUS] as i64 == STATUS_CANCELLED {
+            // cancelled jobs and jobs that never ran don't need any CPU time
+            report.warnings.push(RowIssue {
+                line: line_number,
+                column: Some(String::from("run time")),
+                message: format!("job {} never ran and was skipped", job),
+            });
+            continue;
+        }
+
+        let first = *first_submit.get_or_insert(submit);
+        if submit < first {
+            report.warnings.push(RowIssue {
+                line: line_number,
+                column: Some(String::from("submit time")),
+                message: format!("job {} was submitted before the first job", job),
+            });
+        }
+        let processors = if numbers[REQUESTED_PROCESSORS] > 0.0 {
+            numbers[REQUESTED_PROCESSORS]
+        } else {
+            numbers[ALLOCATED_PROCESSORS].max(1.0)
+        };
+        let cpu_seconds = if options.scale_by_processors {
+            run_time * processors
+        } else {
+            run_time
+        };
+        let (Some(arrival_time), Some(cpu_burst_time)) = (
+            scaled((submit - first).max(0.0), options.time_scale),
+            scaled(cpu_seconds, options.time_scale),
+        ) else {
+            report.errors.push(RowIssue {
+                line: line_number,
+                column: None,
+                message: format!("job {} is too long to replay after scaling", job),
+            });
+            continue;
+        };
+        if cpu_burst_time.is_zero() {
+            report.warnings.push(RowIssue {
+                line: line_number,
+                column: Some(String::from("run time")),
+                message: format!("job {} is shorter than a nanosecond after scaling and was skipped", job),
+            });
+            continue;
+        }
+
+        report.processes.push(WorkloadEntry {
+            name: Some(format!("J{}", job)),
+            arrival_time,
+            cpu_burst_time,
+            process_type: None,
+            priority: None,
+            io_phases: Vec::new(),
+            deadline: None,
+        });
+    }
+
+    if report.processes.is_empty() && report.errors.is_empty() {
+        report.errors.push(RowIssue {
+            line: 0,
+            column: None,
+            message: String::from("the trace has no jobs that can be replayed"),
+        });
+    }
+    report.valid = report.errors.is_empty();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An SWF line: job, submit, run time, allocated and requested processors, status.
+    fn job(number: i64, submit: f64, run: f64, processors: i64, status: i64) -> String {
+        format!(
+            "{} {} 0 {} {} -1 -1 {} -1 -1 {} 1 1 1 1 1 -1 -1",
+            number, submit, run, processors, processors, status
+        )
+    }
+
+    fn trace(jobs: &[String]) -> String {
+        format!("; Version: 2.2\n{}\n", jobs.join("\n"))
+    }
+
+    #[test]
+    fn arrivals_start_at_the_first_job() {
+        let contents = trace(&[job(1, 100.0, 5.0, 1, 1), job(2, 102.5, 3.0, 1, 1)]);
+        let report = import_swf(&contents, &SwfOptions::default());
+        assert!(report.valid, "{:?}", report.errors);
+        let arrivals: Vec<Duration> = report.processes.iter().map(|p| p.arrival_time).collect();
+        assert_eq!(arrivals, [Duration::ZERO, Duration::from_millis(2500)]);
+        assert_eq!(report.processes[1].name.as_deref(), Some("J2"));
+        assert_eq!(report.processes[1].cpu_burst_time, Duration::from_secs(3));
+    }
+
+    #[test]
+    fn times_are_scaled() {
+        let contents = trace(&[job(1, 0.0, 2.0, 4, 1), job(2, 10.0, 1.0, 1, 1)]);
+        let options = SwfOptions {
+            time_scale: 0.001,
+            limit: None,
+            scale_by_processors: true,
+        };
+        let report = import_swf(&contents, &options);
+        assert!(report.valid, "{:?}", report.errors);
+        assert_eq!(report.processes[0].cpu_burst_time, Duration::from_millis(8));
+        assert_eq!(report.processes[1].arrival_time, Duration::from_millis(10));
+        assert_eq!(report.processes[1].cpu_burst_time, Duration::from_millis(1));
+    }
+
+    #[test]
+    fn cancelled_jobs_are_skipped_and_the_limit_kept() {
+        let contents = trace(&[
+            job(1, 0.0, 5.0, 1, STATUS_CANCELLED),
+            job(2, 1.0, 5.0, 1, 1),
+            job(3, 2.0, 5.0, 1, 1),
+            job(4, 3.0, 5.0, 1, 1),
+        ]);
+        let options = SwfOptions {
+            limit: Some(2),
+            ..SwfOptions::default()
+        };
+        let report = import_swf(&contents, &options);
+        let names: Vec<&str> = report.processes.iter().filter_map(|p| p.name.as_deref()).collect();
+        assert_eq!(names, ["J2", "J3"]);
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].line, 2);
+    }
+
+    #[test]
+    fn malformed_lines_are_reported() {
+        let not_a_number = job(2, 0.0, 5.0, 1, 1).replace(" 5 ", " x ");
+        let contents = trace(&[String::from("1 0 0 5"), not_a_number]);
+        let report = import_swf(&contents, &SwfOptions::default());
+        assert!(!report.valid);
+        assert_eq!(report.errors.len(), 2);
+        assert_eq!(report.errors[0].line, 2);
+        assert!(report.errors[0].message.contains("expected 18 fields"));
+        assert_eq!(report.errors[1].line, 3);
+        assert_eq!(report.errors[1].column.as_deref(), Some("field 4"));
+    }
+
+    #[test]
+    fn jobs_too_long_after_scaling_are_an_error() {
+        let contents = trace(&[job(1, 0.0, 1e300, 1, 1)]);
+        let options = SwfOptions {
+            time_scale: 1e10,
+            ..SwfOptions::default()
+        };
+        let report = import_swf(&contents, &options);
+        assert!(!report.valid);
+        assert!(report.errors[0].message.contains("too long to replay"));
+    }
+}
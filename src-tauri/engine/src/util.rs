@@ -0,0 +1,63 @@
+use crate::queue_engine::Queues;
+use std::time::{Duration, SystemTime};
+
+// Times -------------------------------------------------------------------------------------------
+
+pub(crate) fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+pub(crate) fn millis_since(time: SystemTime, start: SystemTime) -> f64 {
+    millis(time.duration_since(start).unwrap_or_default())
+}
+
+// Text --------------------------------------------------------------------------------------------
+
+/// A time or key rounded the way a student writes it down: `2`, `2.5`, `0.333`.
+pub(crate) fn rounded(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+/// `value` rounded and written out, see `rounded`.
+pub(crate) fn number(value: f64) -> String {
+    rounded(value).to_string()
+}
+
+/// Names joined the way a sentence lists them: "A", "A and B", "A, B and C".
+pub(crate) fn listed(names: &[String]) -> String {
+    match names {
+        [] => String::new(),
+        [name] => name.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+/// The algorithm the way a textbook names it.
+pub(crate) fn algorithm_name(queue: Queues) -> String {
+    match queue {
+        Queues::SRF => String::from("SRTF"),
+        queue => queue.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_written_to_three_decimals() {
+        assert_eq!(number(2.0), "2");
+        assert_eq!(number(2.5), "2.5");
+        assert_eq!(number(1.0 / 3.0), "0.333");
+        assert_eq!(rounded(3.6666), 3.667);
+    }
+
+    #[test]
+    fn names_are_listed_like_a_sentence() {
+        let names = |n: &[&str]| n.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(listed(&names(&[])), "");
+        assert_eq!(listed(&names(&["P1"])), "P1");
+        assert_eq!(listed(&names(&["P1", "P2"])), "P1 and P2");
+        assert_eq!(listed(&names(&["P1", "P2", "P3"])), "P1, P2 and P3");
+    }
+}
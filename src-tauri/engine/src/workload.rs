@@ -0,0 +1,658 @@
+use crate::process_gen::{IoPhase, Process, ProcessType};
+use crate::sched_trace::{self, KernelRun, SchedTraceOptions};
+use crate::swf::{self, SwfOptions};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+// Utils -------------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WorkloadFormat {
+    Csv,
+    Json,
+    Swf,        // Standard Workload Format traces, see swf.rs
+    SchedTrace, // perf sched / ftrace text output, see sched_trace.rs
+}
+
+impl WorkloadFormat {
+    /// Accepts "csv" / "json" / "swf" / "sched" (case insensitive). When no format is given we
+    /// guess it from the contents of the file.
+    pub fn detect(format: Option<&str>, contents: &str) -> Result<Self, String> {
+        match format.map(|f| f.trim().to_lowercase()) {
+            Some(f) if f == "csv" => Ok(WorkloadFormat::Csv),
+            Some(f) if f == "json" => Ok(WorkloadFormat::Json),
+            Some(f) if f == "swf" => Ok(WorkloadFormat::Swf),
+            Some(f) if f == "sched" => Ok(WorkloadFormat::SchedTrace),
+            Some(f) => Err(format!(
+                "Unknown workload format \"{}\", expected csv, json, swf or sched",
+                f
+            )),
+            None if contents.contains("sched_switch") => Ok(WorkloadFormat::SchedTrace),
+            None => match contents.trim_start().chars().next() {
+                Some('[') | Some('{') => Ok(WorkloadFormat::Json),
+                Some(';') => Ok(WorkloadFormat::Swf),
+                _ => Ok(WorkloadFormat::Csv),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TimeUnit {
+    Milliseconds,
+    Seconds,
+}
+
+impl TimeUnit {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "ms" => Some(TimeUnit::Milliseconds),
+            "s" => Some(TimeUnit::Seconds),
+            _ => None,
+        }
+    }
+
+    /// `value` in this unit as a duration, none when it's negative or too long for one.
+    fn to_duration(self, value: f64) -> Option<Duration> {
+        let seconds = match self {
+            TimeUnit::Milliseconds => value / 1000.0,
+            TimeUnit::Seconds => value,
+        };
+        Duration::try_from_secs_f64(seconds).ok()
+    }
+}
+
+/// One row of an imported workload. Times are relative to the start of the simulation, the
+/// same way `run_with_parameters` treats its arrival times.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkloadEntry {
+    pub name: Option<String>,
+    pub arrival_time: Duration,
+    pub cpu_burst_time: Duration,
+    pub process_type: Option<ProcessType>,
+    pub priority: Option<u8>,
+    pub io_phases: Vec<IoPhase>,
+    pub deadline: Option<Duration>,
+}
+
+impl WorkloadEntry {
+    pub fn to_process<R: Rng>(&self, start: SystemTime, rng: &mut R) -> Process {
+        // rows without a type get a random one, from `rng` so seeded runs pick the same types
+        let mut process = Process::with_rng(
+            self.cpu_burst_time,
+            start + self.arrival_time,
+            self.process_type,
+            rng,
+        );
+        process.name = self.name.clone();
+        process.priority = self.priority;
+        process.io_phases = self.io_phases.clone();
+        process.deadline = self.deadline.map(|d| start + d);
+        process
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RowIssue {
+    pub line: usize,
+    pub column: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for RowIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "line {}, {}: {}", self.line, column, self.message),
+            None => write!(f, "line {}: {}", self.line, self.message),
+        }
+    }
+}
+
+/// What the UI gets back before running an imported workload: the rows we could read, and
+/// every problem we found with a line number so it can be fixed in the spreadsheet.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    pub format: WorkloadFormat,
+    pub valid: bool,
+    pub processes: Vec<WorkloadEntry>,
+    pub errors: Vec<RowIssue>,
+    pub warnings: Vec<RowIssue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kernel: Option<KernelRun>, // what the kernel did with a sched trace, see import_workload
+}
+
+// Parsing -----------------------------------------------------------------------------------------
+
+const NAME_COLUMNS: [&str; 3] = ["pid", "name", "id"];
+const ARRIVAL_COLUMNS: [&str; 3] = ["arrival", "arrival_time", "at"];
+const BURST_COLUMNS: [&str; 4] = ["burst", "burst_time", "cbt", "cpu_burst"];
+const TYPE_COLUMNS: [&str; 2] = ["type", "process_type"];
+const PRIORITY_COLUMNS: [&str; 1] = ["priority"];
+const IO_COLUMNS: [&str; 3] = ["io", "io_bursts", "io_burst"];
+const DEADLINE_COLUMNS: [&str; 1] = ["deadline"];
+
+struct Row {
+    // a single record with its cells already looked up by column, before validation
+    line: usize,
+    name: Option<String>,
+    arrival: Option<String>,
+    burst: Option<String>,
+    process_type: Option<String>,
+    priority: Option<String>,
+    io: Option<String>,
+    deadline: Option<String>,
+}
+
+fn find_column(headers: &[String], aliases: &[&str]) -> Option<usize> {
+    headers
+        .iter()
+        .position(|h| aliases.contains(&h.trim().to_lowercase().as_str()))
+}
+
+/// Parses a duration such as `12`, `12ms`, `1.5s` or `1.5 s`. Numbers without a suffix are read
+/// in `default_unit`.
+pub fn parse_duration(text: &str, default_unit: TimeUnit) -> Result<Duration, String> {
+    let text = text.trim();
+    let (number, unit) = if let Some(number) = text.strip_suffix("ms") {
+        (number, TimeUnit::Milliseconds)
+    } else if let Some(number) = text.strip_suffix('s') {
+        (number, TimeUnit::Seconds)
+    } else {
+        (text, default_unit)
+    };
+    let value: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("\"{}\" is not a time (examples: 20, 20ms, 1.5s)", text))?;
+    if !value.is_finite() || value < 0.0 {
+        return Err(format!("\"{}\" must be a non-negative time", text));
+    }
+    unit.to_duration(value).ok_or_else(|| format!("\"{}\" is too long a time", text))
+}
+
+fn parse_duration_list(text: &str, default_unit: TimeUnit) -> Result<Vec<Duration>, String> {
+    text.split([';', '|'])
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .map(|part| parse_duration(part, default_unit))
+        .collect()
+}
+
+fn validate_row(row: Row, unit: TimeUnit, report: &mut ValidationReport) {
+    let line = row.line;
+    let errors_before = report.errors.len();
+    let mut push_error = |column: &str, message: String| {
+        report.errors.push(RowIssue {
+            line,
+            column: Some(column.to_string()),
+            message,
+        })
+    };
+
+    let arrival_time = match row.arrival.as_deref().map(|a| parse_duration(a, unit)) {
+        Some(Ok(at)) => Some(at),
+        Some(Err(e)) => {
+            push_error("arrival", e);
+            None
+        }
+        None => {
+            push_error("arrival", String::from("arrival time is missing"));
+            None
+        }
+    };
+
+    // a burst is either the total CPU time or one CPU burst per phase ("5;3;2") when the
+    // process does I/O in between.
+    let cpu_bursts = match row.burst.as_deref().map(|b| parse_duration_list(b, unit)) {
+        Some(Ok(bursts)) if bursts.is_empty() => {
+            push_error("burst", String::from("burst time is missing"));
+            None
+        }
+        Some(Ok(bursts)) if bursts.iter().any(|b| b.is_zero()) => {
+            push_error("burst", String::from("burst time must be greater than zero"));
+            None
+        }
+        Some(Ok(bursts)) => Some(bursts),
+        Some(Err(e)) => {
+            push_error("burst", e);
+            None
+        }
+        None => {
+            push_error("burst", String::from("burst time is missing"));
+            None
+        }
+    };
+
+    let process_type = match row.process_type.as_deref() {
+        Some(pt) => match ProcessType::from_name(pt) {
+            Some(pt) => Some(pt),
+            None => {
+                push_error(
+                    "type",
+                    format!(
+                        "unknown process type \"{}\", expected system, interactive, batch or student",
+                        pt
+                    ),
+                );
+                None
+            }
+        },
+        None => None,
+    };
+
+    let priority = match row.priority.as_deref() {
+        Some(p) => match p.parse::<u8>() {
+            Ok(p) => Some(p),
+            Err(_) => {
+                push_error(
+                    "priority",
+                    format!("priority \"{}\" must be a whole number from 0 to 255", p),
+                );
+                None
+            }
+        },
+        None => None,
+    };
+
+    let io_bursts = match row.io.as_deref().map(|io| parse_duration_list(io, unit)) {
+        Some(Ok(io)) => io,
+        Some(Err(e)) => {
+            push_error("io", e);
+            Vec::new()
+        }
+        None => Vec::new(),
+    };
+
+    let deadline = match row.deadline.as_deref().map(|d| parse_duration(d, unit)) {
+        Some(Ok(d)) => Some(d),
+        Some(Err(e)) => {
+            push_error("deadline", e);
+            None
+        }
+        None => None,
+    };
+
+    let mut io_phases = Vec::new();
+    let mut cpu_burst_time = Duration::from_secs(0);
+    if let Some(cpu_bursts) = &cpu_bursts {
+        let total = cpu_bursts.iter().try_fold(Duration::ZERO, |sum, b| sum.checked_add(*b));
+        cpu_burst_time = total.unwrap_or(Duration::MAX);
+        if total.is_none() {
+            push_error("burst", String::from("the CPU bursts add up to too long a time"));
+        } else if cpu_bursts.len() > 1 && cpu_bursts.len() != io_bursts.len() + 1 {
+            push_error(
+                "io",
+                format!(
+                    "{} CPU bursts need {} I/O bursts between them, found {}",
+                    cpu_bursts.len(),
+                    cpu_bursts.len() - 1,
+                    io_bursts.len()
+                ),
+            );
+        } else {
+            // with a single total burst the I/O requests are spread evenly over it
+            let segments = io_bursts.len() as u32 + 1;
+            let mut at = Duration::from_secs(0);
+            for (index, io) in io_bursts.iter().enumerate() {
+                at += if cpu_bursts.len() > 1 {
+                    cpu_bursts[index]
+                } else {
+                    cpu_burst_time / segments
+                };
+                io_phases.push(IoPhase {
+                    at,
+                    duration: *io,
+                });
+            }
+        }
+    }
+
+    if let (Some(at), Some(deadline)) = (arrival_time, deadline) {
+        if at.checked_add(cpu_burst_time).is_none_or(|end| deadline < end) {
+            report.warnings.push(RowIssue {
+                line,
+                column: Some(String::from("deadline")),
+                message: String::from("deadline is earlier than arrival plus burst, it can never be met"),
+            });
+        }
+    }
+
+    if let Some(name) = &row.name {
+        if report.processes.iter().any(|p| p.name.as_ref() == Some(name)) {
+            report.warnings.push(RowIssue {
+                line,
+                column: Some(String::from("pid")),
+                message: format!("\"{}\" is used by more than one process", name),
+            });
+        }
+    }
+
+    if report.errors.len() == errors_before {
+        report.processes.push(WorkloadEntry {
+            name: row.name,
+            arrival_time: arrival_time.unwrap(),
+            cpu_burst_time,
+            process_type,
+            priority,
+            io_phases,
+            deadline,
+        });
+    }
+}
+
+fn parse_csv(contents: &str, unit: TimeUnit, report: &mut ValidationReport) {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_reader(contents.as_bytes());
+
+    let headers: Vec<String> = match reader.headers() {
+        Ok(headers) => headers.iter().map(|h| h.to_string()).collect(),
+        Err(e) => {
+            report.errors.push(RowIssue {
+                line: 1,
+                column: None,
+                message: format!("could not read the header row: {}", e),
+            });
+            return;
+        }
+    };
+    let column = |aliases: &[&str]| find_column(&headers, aliases);
+    let (name, arrival, burst) = (
+        column(&NAME_COLUMNS),
+        column(&ARRIVAL_COLUMNS),
+        column(&BURST_COLUMNS),
+    );
+    let (process_type, priority, io, deadline) = (
+        column(&TYPE_COLUMNS),
+        column(&PRIORITY_COLUMNS),
+        column(&IO_COLUMNS),
+        column(&DEADLINE_COLUMNS),
+    );
+    if arrival.is_none() || burst.is_none() {
+        report.errors.push(RowIssue {
+            line: 1,
+            column: None,
+            message: String::from("the header row needs an \"arrival\" and a \"burst\" column"),
+        });
+        return;
+    }
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|p| p.line() as usize).unwrap_or(0);
+                report.errors.push(RowIssue {
+                    line,
+                    column: None,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+        let cell = |index: Option<usize>| {
+            index
+                .and_then(|i| record.get(i))
+                .filter(|c| !c.is_empty())
+                .map(|c| c.to_string())
+        };
+        let row = Row {
+            line,
+            name: cell(name),
+            arrival: cell(arrival),
+            burst: cell(burst),
+            process_type: cell(process_type),
+            priority: cell(priority),
+            io: cell(io),
+            deadline: cell(deadline),
+        };
+        validate_row(row, unit, report);
+    }
+}
+
+fn array_element_lines(contents: &str) -> Vec<usize> {
+    // serde_json doesn't keep positions, so we find the line every element of the top level
+    // array starts at ourselves in order to report errors by line.
+    let mut lines = Vec::new();
+    let (mut line, mut depth, mut in_string, mut escaped) = (1, 0, false, false);
+    for c in contents.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                '\n' => line += 1,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '\n' => line += 1,
+            '"' => in_string = true,
+            '{' | '[' => {
+                if depth == 1 {
+                    lines.push(line);
+                }
+                depth += 1;
+            }
+            '}' | ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    lines
+}
+
+fn json_cell(object: &serde_json::Map<String, Value>, aliases: &[&str]) -> Option<String> {
+    let value = object
+        .iter()
+        .find(|(key, _)| aliases.contains(&key.trim().to_lowercase().as_str()))
+        .map(|(_, value)| value)?;
+    match value {
+        Value::Null => None,
+        Value::String(s) if s.trim().is_empty() => None,
+        Value::String(s) => Some(s.trim().to_string()),
+        Value::Array(items) => Some(
+            items
+                .iter()
+                .map(|i| match i {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<String>>()
+                .join(";"),
+        ),
+        other => Some(other.to_string()),
+    }
+}
+
+fn parse_json(contents: &str, unit: TimeUnit, report: &mut ValidationReport) {
+    let value: Value = match serde_json::from_str(contents) {
+        Ok(value) => value,
+        Err(e) => {
+            report.errors.push(RowIssue {
+                line: e.line(),
+                column: None,
+                message: format!("invalid JSON: {}", e),
+            });
+            return;
+        }
+    };
+    // both a bare array and {"processes": [...]} are accepted
+    let (elements, lines) = match value {
+        Value::Array(elements) => (elements, array_element_lines(contents)),
+        Value::Object(mut object) => match object.remove("processes") {
+            Some(Value::Array(elements)) => {
+                let start = contents.find("\"processes\"").unwrap_or(0);
+                let offset = contents[..start].matches('\n').count();
+                let lines = array_element_lines(&contents[start..])
+                    .iter()
+                    .map(|l| l + offset)
+                    .collect();
+                (elements, lines)
+            }
+            _ => {
+                report.errors.push(RowIssue {
+                    line: 1,
+                    column: None,
+                    message: String::from("expected an array of processes"),
+                });
+                return;
+            }
+        },
+        _ => {
+            report.errors.push(RowIssue {
+                line: 1,
+                column: None,
+                message: String::from("expected an array of processes"),
+            });
+            return;
+        }
+    };
+
+    for (index, element) in elements.iter().enumerate() {
+        let line = lines.get(index).copied().unwrap_or(0);
+        let object = match element {
+            Value::Object(object) => object,
+            _ => {
+                report.errors.push(RowIssue {
+                    line,
+                    column: None,
+                    message: String::from("each process must be an object"),
+                });
+                continue;
+            }
+        };
+        let row = Row {
+            line,
+            name: json_cell(object, &NAME_COLUMNS),
+            arrival: json_cell(object, &ARRIVAL_COLUMNS),
+            burst: json_cell(object, &BURST_COLUMNS),
+            process_type: json_cell(object, &TYPE_COLUMNS),
+            priority: json_cell(object, &PRIORITY_COLUMNS),
+            io: json_cell(object, &IO_COLUMNS),
+            deadline: json_cell(object, &DEADLINE_COLUMNS),
+        };
+        validate_row(row, unit, report);
+    }
+}
+
+/// Reads a workload. Nothing is simulated here; the report tells the caller whether the workload
+/// can be run and what is wrong with it otherwise. `swf` is only used by SWF traces.
+pub fn import_workload(
+    contents: &str,
+    format: WorkloadFormat,
+    unit: TimeUnit,
+    swf: &SwfOptions,
+) -> ValidationReport {
+    let mut report = ValidationReport {
+        format,
+        valid: false,
+        processes: Vec::new(),
+        errors: Vec::new(),
+        warnings: Vec::new(),
+        kernel: None,
+    };
+    match format {
+        WorkloadFormat::Csv => parse_csv(contents, unit, &mut report),
+        WorkloadFormat::Json => parse_json(contents, unit, &mut report),
+        WorkloadFormat::Swf => return swf::import_swf(contents, swf),
+        WorkloadFormat::SchedTrace => {
+            // the kernel's metrics come along, so the run of the workload can be shown next to them
+            let import = sched_trace::import_sched_trace(contents, &SchedTraceOptions::default());
+            let kernel = Some(import.kernel());
+            return ValidationReport { kernel, ..import.report };
+        }
+    }
+    if report.processes.is_empty() && report.errors.is_empty() {
+        report.errors.push(RowIssue {
+            line: 1,
+            column: None,
+            message: String::from("the workload has no processes"),
+        });
+    }
+    report.valid = report.errors.is_empty();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv(contents: &str) -> ValidationReport {
+        let swf = SwfOptions::default();
+        import_workload(contents, WorkloadFormat::Csv, TimeUnit::Milliseconds, &swf)
+    }
+
+    #[test]
+    fn durations_are_read_in_their_unit() {
+        let ms = TimeUnit::Milliseconds;
+        assert_eq!(parse_duration("12", ms), Ok(Duration::from_millis(12)));
+        assert_eq!(parse_duration("12ms", TimeUnit::Seconds), Ok(Duration::from_millis(12)));
+        assert_eq!(parse_duration("1.5s", ms), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration(" 1.5 s ", ms), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("2", TimeUnit::Seconds), Ok(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn bad_durations_are_rejected() {
+        let ms = TimeUnit::Milliseconds;
+        assert!(parse_duration("soon", ms).unwrap_err().contains("is not a time"));
+        assert!(parse_duration("-3", ms).unwrap_err().contains("non-negative"));
+        assert!(parse_duration("inf", ms).unwrap_err().contains("non-negative"));
+        assert!(parse_duration("1e30s", ms).unwrap_err().contains("too long"));
+    }
+
+    #[test]
+    fn rows_are_imported() {
+        let report = csv("pid,arrival,burst,io\nP1,0,10,\nP2,1.5s,5;3,2\n");
+        assert!(report.valid, "{:?}", report.errors);
+        assert_eq!(report.processes.len(), 2);
+        let p2 = &report.processes[1];
+        assert_eq!(p2.name.as_deref(), Some("P2"));
+        assert_eq!(p2.arrival_time, Duration::from_millis(1500));
+        assert_eq!(p2.cpu_burst_time, Duration::from_millis(8));
+        assert_eq!(p2.io_phases.len(), 1);
+        assert_eq!(p2.io_phases[0].at, Duration::from_millis(5));
+    }
+
+    #[test]
+    fn malformed_rows_are_reported_by_line() {
+        let report = csv("pid,arrival,burst,priority\nP1,0,10,1\nP2,x,0,\nP3,2,,300\n");
+        assert!(!report.valid);
+        assert_eq!(report.processes.len(), 1);
+        let issues: Vec<(usize, Option<&str>)> = report
+            .errors
+            .iter()
+            .map(|e| (e.line, e.column.as_deref()))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (3, Some("arrival")),
+                (3, Some("burst")),
+                (4, Some("burst")),
+                (4, Some("priority"))
+            ]
+        );
+    }
+
+    #[test]
+    fn a_header_without_burst_is_an_error() {
+        let report = csv("pid,arrival\nP1,0\n");
+        assert!(!report.valid);
+        assert_eq!(report.errors[0].line, 1);
+    }
+
+    #[test]
+    fn bursts_that_overflow_are_an_error() {
+        let report = csv("arrival,burst\n0,1e19s;1e19s\n");
+        assert!(!report.valid);
+        assert!(report.errors[0].message.contains("too long"));
+    }
+}
@@ -0,0 +1,41 @@
+;; Shortest remaining time first as a PLUGIN policy, see the README for the ABI.
+;; Build it with `wat2wasm srtf.wat`, or hand the .wat to mehrmux-cli as it is.
+(module
+  ;; the queue, 16 bytes per process: its number and its remaining time
+  (memory 1)
+  (global $count (mut i32) (i32.const 0))
+
+  (func $slot (param $i i32) (result i32)
+    (i32.mul (local.get $i) (i32.const 16)))
+
+  (func (export "on_arrival")
+    (param $process i32) (param $arrival f64) (param $burst f64) (param $remaining f64)
+    (param $priority i32) (param $process_type i32)
+    (local $at i32)
+    (local.set $at (call $slot (global.get $count)))
+    (i32.store (local.get $at) (local.get $process))
+    (f64.store offset=8 (local.get $at) (local.get $remaining))
+    (global.set $count (i32.add (global.get $count) (i32.const 1))))
+
+  (func (export "pick_next") (param $now f64) (result i32)
+    (local $i i32) (local $best i32) (local $picked i32) (local $last i32)
+    (local.set $i (i32.const 1))
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (global.get $count)))
+        (if (f64.lt (f64.load offset=8 (call $slot (local.get $i)))
+                    (f64.load offset=8 (call $slot (local.get $best))))
+          (then (local.set $best (local.get $i))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))
+    (local.set $picked (i32.load (call $slot (local.get $best))))
+    ;; the picked process leaves the queue, the last one takes its slot
+    (global.set $count (i32.sub (global.get $count) (i32.const 1)))
+    (local.set $last (call $slot (global.get $count)))
+    (i32.store (call $slot (local.get $best)) (i32.load (local.get $last)))
+    (f64.store offset=8 (call $slot (local.get $best)) (f64.load offset=8 (local.get $last)))
+    (local.get $picked))
+
+  ;; every process is preempted after a quantum, so a shorter arrival gets its turn
+  (func (export "on_tick") (param $process i32) (param $now f64) (result i32)
+    (i32.const 1)))
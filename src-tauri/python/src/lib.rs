@@ -0,0 +1,431 @@
+//! Python bindings of the engine, built into a wheel with `maturin build --release`.
+//!
+//! ```python
+//! import mehrmux
+//!
+//! scenario = mehrmux.Scenario("RR", quantum=20, processes=100, seed=7)
+//! result = mehrmux.run(scenario)
+//! result["metrics"]["average_waiting_time"]
+//! mehrmux.compare(scenario, ["FCFS", "SJF", "RR"])["ranking"]
+//! ```
+//!
+//! Results are plain dicts and lists, the same JSON the app's commands return: a run has its
+//! metrics and one record per process, shaped like `SerializableProcess`. The differences are
+//! that durations are floats in milliseconds instead of `{"secs": ..., "nanos": ...}`, and a
+//! metric is its value, `203.97` rather than `{"DurationValue": 203.97}`.
+
+use mehrmux_engine::error::SimError;
+use mehrmux_engine::exercise::{self, ExerciseSpec};
+use mehrmux_engine::grading::{self, Homework};
+use mehrmux_engine::policy::ScriptPolicy;
+use mehrmux_engine::queue_engine::{QueueDiscipline, Queues};
+use mehrmux_engine::scenario::{ProcessSource, Scenario};
+use mehrmux_engine::sched_trace::{KernelRun, Replay};
+use mehrmux_engine::simulator::RunOptions;
+use mehrmux_engine::sweep::{run_sweep, SweepSpec};
+use mehrmux_engine::swf::SwfOptions;
+use mehrmux_engine::workload::{self, TimeUnit, WorkloadEntry, WorkloadFormat};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+// Utils -------------------------------------------------------------------------------------------
+
+// the fields of a workload entry and of its I/O phases that are durations
+const DURATION_FIELDS: [&str; 5] = ["arrival_time", "cpu_burst_time", "deadline", "at", "duration"];
+
+// the variants of `MetricValue`, serde writes a metric as `{"DurationValue": ...}`
+const METRIC_VARIANTS: [&str; 5] =
+    ["DurationValue", "PercentageValue", "IntegerValue", "StringValue", "FloatValue"];
+
+create_exception!(mehrmux, SimulationError, PyException, "A run that didn't make it to the end.");
+
+fn sim_error(error: SimError) -> PyErr {
+    match error {
+        // the run never started, something it was given is wrong
+        SimError::InvalidConfig { .. } | SimError::InvalidRate { .. } => {
+            PyValueError::new_err(error.to_string())
+        }
+        _ => SimulationError::new_err(error.to_string()),
+    }
+}
+
+fn from_py<T: DeserializeOwned>(value: &Bound<'_, PyAny>) -> PyResult<T> {
+    let json: String = value.py().import("json")?.call_method1("dumps", (value,))?.extract()?;
+    serde_json::from_str(&json).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Turns every `{"secs": ..., "nanos": ...}` serde writes a duration as into milliseconds.
+fn durations_as_millis(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let secs = map.get("secs").and_then(Value::as_f64);
+            let nanos = map.get("nanos").and_then(Value::as_f64);
+            match (secs, nanos) {
+                (Some(secs), Some(nanos)) if map.len() == 2 => {
+                    *value = Value::from(secs * 1000.0 + nanos / 1_000_000.0)
+                }
+                _ => map.values_mut().for_each(durations_as_millis),
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(durations_as_millis),
+        _ => {}
+    }
+}
+
+/// The other way around, every duration field of a workload given in ms becomes the
+/// `{"secs": ..., "nanos": ...}` serde reads. A negative time is left for serde to refuse.
+fn millis_as_durations(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                let ms = field.as_f64().filter(|_| DURATION_FIELDS.contains(&key.as_str()));
+                match ms.and_then(|ms| Duration::try_from_secs_f64(ms / 1000.0).ok()) {
+                    Some(d) => *field = json!({ "secs": d.as_secs(), "nanos": d.subsec_nanos() }),
+                    None => millis_as_durations(field),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(millis_as_durations),
+        _ => {}
+    }
+}
+
+/// Takes every metric out of its `MetricValue` variant.
+fn metrics_as_values(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let variant = match map.keys().next() {
+                Some(key) if map.len() == 1 && METRIC_VARIANTS.contains(&key.as_str()) => key.clone(),
+                _ => return map.values_mut().for_each(metrics_as_values),
+            };
+            *value = map.remove(&variant).unwrap_or_default();
+        }
+        Value::Array(items) => items.iter_mut().for_each(metrics_as_values),
+        _ => {}
+    }
+}
+
+/// A workload given as a list of entries, with its times in ms or as `{"secs": ..., "nanos": ...}`.
+fn workload_from_py(value: &Bound<'_, PyAny>) -> PyResult<Vec<WorkloadEntry>> {
+    let mut entries: Value = from_py(value)?;
+    millis_as_durations(&mut entries);
+    serde_json::from_value(entries).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+fn to_py<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let mut value = serde_json::to_value(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    durations_as_millis(&mut value);
+    metrics_as_values(&mut value);
+    Ok(py.import("json")?.call_method1("loads", (value.to_string(),))?.unbind())
+}
+
+fn parse_queue(name: &str) -> PyResult<Queues> {
+    Queues::from_name(name).ok_or(PyValueError::new_err(format!("Unknown algorithm \"{}\"", name)))
+}
+
+fn parse_disciplines(names: Vec<String>) -> PyResult<[QueueDiscipline; 4]> {
+    let disciplines = names
+        .iter()
+        .map(|name| {
+            QueueDiscipline::from_name(name)
+                .ok_or(PyValueError::new_err(format!("Unknown discipline \"{}\"", name)))
+        })
+        .collect::<PyResult<Vec<QueueDiscipline>>>()?;
+    disciplines
+        .try_into()
+        .map_err(|_| PyValueError::new_err("disciplines needs one discipline for each of the 4 levels"))
+}
+
+/// A workload file, in any format `mehrmux-cli --workload` reads.
+fn load_workload(
+    path: &str,
+    unit: TimeUnit,
+    swf: &SwfOptions,
+) -> PyResult<(Vec<WorkloadEntry>, Option<KernelRun>)> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| PyValueError::new_err(format!("{}: {}", path, e)))?;
+    let format = WorkloadFormat::detect(None, &contents).map_err(PyValueError::new_err)?;
+    let report = workload::import_workload(&contents, format, unit, swf);
+    if !report.valid {
+        let errors: Vec<String> = report.errors.iter().map(|e| e.to_string()).collect();
+        return Err(PyValueError::new_err(format!(
+            "{} is not a valid workload:\n{}",
+            path,
+            errors.join("\n")
+        )));
+    }
+    Ok((report.processes, report.kernel))
+}
+
+/// A script for the SCRIPT discipline, its source or the path of a `.rhai` file.
+fn load_script(script: &str) -> PyResult<Arc<ScriptPolicy>> {
+    let (name, source) = match script.ends_with(".rhai") {
+        true => {
+            let source = std::fs::read_to_string(script)
+                .map_err(|e| PyValueError::new_err(format!("{}: {}", script, e)))?;
+            (script, source)
+        }
+        false => ("script", script.to_string()),
+    };
+    let policy = ScriptPolicy::compile(name, &source)
+        .map_err(|e| PyValueError::new_err(format!("{}: {}", name, e)))?;
+    Ok(Arc::new(policy))
+}
+
+fn millis(ms: Option<u64>) -> Option<Duration> {
+    ms.map(Duration::from_millis)
+}
+
+// Scenario ----------------------------------------------------------------------------------------
+
+/// One run, set up. Times are in milliseconds. The processes are generated from the arrival and
+/// burst rates unless a `workload` is given, either the path of a workload file or a list of
+/// entries in the app's JSON, with times in ms. `swf` takes the options of an SWF trace as a
+/// dict, `time_scale`, `limit` and `scale_by_processors`. `script` is the Rhai policy of the
+/// SCRIPT discipline, its source or the path of a `.rhai` file.
+#[pyclass(name = "Scenario", module = "mehrmux")]
+#[derive(Clone)]
+pub struct PyScenario {
+    scenario: Scenario,
+    kernel: Option<KernelRun>, // what the kernel did when the workload is a sched trace
+}
+
+#[pymethods]
+impl PyScenario {
+    #[new]
+    #[pyo3(signature = (
+        algorithm,
+        *,
+        quantum = 10,
+        context_switch = 1,
+        disciplines = None,
+        arrival_lambda = 1.0,
+        burst_lambda = 2.0,
+        processes = 20,
+        workload = None,
+        unit = "ms",
+        swf = None,
+        seed = None,
+        mlfq_boost_period = None,
+        sample_period = None,
+        starvation_threshold = None,
+        script = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        algorithm: &str,
+        quantum: u64,
+        context_switch: u64,
+        disciplines: Option<Vec<String>>,
+        arrival_lambda: f64,
+        burst_lambda: f64,
+        processes: usize,
+        workload: Option<&Bound<'_, PyAny>>,
+        unit: &str,
+        swf: Option<&Bound<'_, PyAny>>,
+        seed: Option<u64>,
+        mlfq_boost_period: Option<u64>,
+        sample_period: Option<u64>,
+        starvation_threshold: Option<u64>,
+        script: Option<&str>,
+    ) -> PyResult<Self> {
+        let mut kernel = None;
+        let source = match workload {
+            Some(workload) => match workload.extract::<String>() {
+                Ok(path) => {
+                    let unit = TimeUnit::from_name(unit).ok_or(PyValueError::new_err(format!(
+                        "unknown time unit \"{}\", expected ms or s",
+                        unit
+                    )))?;
+                    let swf: SwfOptions = swf.map(from_py).transpose()?.unwrap_or_default();
+                    let (entries, traced) = load_workload(&path, unit, &swf)?;
+                    kernel = traced;
+                    ProcessSource::Workload(entries)
+                }
+                Err(_) => ProcessSource::Workload(workload_from_py(workload)?),
+            },
+            None => ProcessSource::Generated {
+                arrival_lambda,
+                burst_lambda,
+                count: processes,
+            },
+        };
+        let mut scenario = Scenario::new(parse_queue(algorithm)?, source);
+        scenario.time_quantum = Duration::from_millis(quantum);
+        scenario.context_switch = Duration::from_millis(context_switch);
+        scenario.disciplines = disciplines.map(parse_disciplines).transpose()?;
+        scenario.seed = seed;
+        scenario.options = RunOptions {
+            mlfq_boost_period: millis(mlfq_boost_period),
+            sample_period: millis(sample_period),
+            starvation_threshold: millis(starvation_threshold),
+            policy: script.map(load_script).transpose()?,
+            plugin: None, // the wheel is built without the `plugins` feature
+        };
+        Ok(PyScenario { scenario, kernel })
+    }
+
+    #[getter]
+    fn algorithm(&self) -> String {
+        self.scenario.queue.to_string()
+    }
+
+    #[getter]
+    fn seed(&self) -> Option<u64> {
+        self.scenario.seed
+    }
+
+    fn run(&self, py: Python<'_>) -> PyResult<PyObject> {
+        run(py, self)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Scenario({:?}, quantum={}, context_switch={}, seed={})",
+            self.scenario.queue.to_string(),
+            self.scenario.time_quantum.as_millis(),
+            self.scenario.context_switch.as_millis(),
+            self.scenario.seed.map_or(String::from("None"), |seed| seed.to_string())
+        )
+    }
+}
+
+// Functions ---------------------------------------------------------------------------------------
+
+/// Runs `scenario` and returns its result: `metrics`, `processes`, `level_metrics`, `analytic`,
+/// `samples`, `littles_law` and `starvation`, and `kernel` when the workload is a sched trace.
+#[pyfunction]
+fn run(py: Python<'_>, scenario: &PyScenario) -> PyResult<PyObject> {
+    let result = py.allow_threads(|| scenario.scenario.run()).map_err(sim_error)?;
+    match &scenario.kernel {
+        Some(kernel) => to_py(py, &Replay { result: &result, kernel }),
+        None => to_py(py, &result),
+    }
+}
+
+/// Runs the processes of `scenario` through every algorithm in `algorithms` and ranks them by
+/// `rank_by`, with every process's times under each of them, and the kernel's metrics as
+/// `kernel` when the workload is a sched trace.
+#[pyfunction]
+#[pyo3(signature = (scenario, algorithms, rank_by = "average_waiting_time"))]
+fn compare(
+    py: Python<'_>,
+    scenario: &PyScenario,
+    algorithms: Vec<String>,
+    rank_by: &str,
+) -> PyResult<PyObject> {
+    let queues = algorithms.iter().map(|name| parse_queue(name)).collect::<PyResult<Vec<_>>>()?;
+    let mut comparison = py
+        .allow_threads(|| scenario.scenario.compare(&queues, rank_by))
+        .map_err(sim_error)?;
+    comparison.kernel = scenario.kernel.clone();
+    to_py(py, &comparison)
+}
+
+/// Runs a parameter sweep. `spec` is a dict with the fields of the app's `run_sweep` spec, the
+/// missing ones get their defaults. Returns the swept `parameters` and one row per algorithm,
+/// point and metric with its mean and confidence interval.
+#[pyfunction]
+fn sweep(py: Python<'_>, spec: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+    let spec: SweepSpec = from_py(spec)?;
+    let result = py.allow_threads(|| run_sweep(&spec)).map_err(PyValueError::new_err)?;
+    to_py(py, &result)
+}
+
+/// Checks a student's Gantt chart and metrics. `homework` is a dict with the `config` and
+/// `array_of_processes` of a manual run, the `schedule` as a list of `{"process": "P1",
+/// "start": 0, "end": 3}` and the `metrics` the student computed. Returns the expected
+/// schedule, a verdict per segment, the first mistake explained and the metric errors.
+#[pyfunction]
+fn grade(py: Python<'_>, homework: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+    let homework: Homework = from_py(homework)?;
+    let grade = py.allow_threads(|| grading::grade(&homework)).map_err(sim_error)?;
+    to_py(py, &grade)
+}
+
+/// Writes a scheduling exercise. `spec` is a dict with any of `seed`, `difficulty` ("easy",
+/// "medium" or "hard"), `algorithms`, `time_quantum`, `context_switch` and `properties`
+/// ("tie", "preemption", "idle"). Returns the problem text, the processes and an answer key per
+/// algorithm, or the whole exercise as Markdown with `markdown=True`.
+#[pyfunction]
+#[pyo3(signature = (spec, markdown = false))]
+fn generate_exercise(py: Python<'_>, spec: &Bound<'_, PyAny>, markdown: bool) -> PyResult<PyObject> {
+    let spec: ExerciseSpec = from_py(spec)?;
+    let exercise = py.allow_threads(|| exercise::generate(&spec)).map_err(PyValueError::new_err)?;
+    match markdown {
+        true => Ok(exercise.to_markdown().into_pyobject(py)?.into_any().unbind()),
+        false => to_py(py, &exercise),
+    }
+}
+
+#[pymodule]
+fn mehrmux(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyScenario>()?;
+    m.add_function(wrap_pyfunction!(run, m)?)?;
+    m.add_function(wrap_pyfunction!(compare, m)?)?;
+    m.add_function(wrap_pyfunction!(sweep, m)?)?;
+    m.add_function(wrap_pyfunction!(grade, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_exercise, m)?)?;
+    m.add("SimulationError", m.py().get_type::<SimulationError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_become_milliseconds() {
+        let mut value = json!({
+            "burst": { "secs": 1, "nanos": 500_000_000 },
+            "phases": [{ "secs": 0, "nanos": 250_000 }],
+            "not_a_duration": { "secs": 1, "nanos": 0, "more": 2 },
+        });
+        durations_as_millis(&mut value);
+        assert_eq!(value["burst"], 1500.0);
+        assert_eq!(value["phases"][0], 0.25);
+        assert_eq!(value["not_a_duration"]["secs"], 1);
+    }
+
+    #[test]
+    fn workload_times_in_milliseconds_are_read() {
+        let mut value = json!([{
+            "name": "P1",
+            "arrival_time": 1500,
+            "cpu_burst_time": 2.5,
+            "process_type": null,
+            "priority": 3,
+            "io_phases": [{ "at": 1, "duration": { "secs": 0, "nanos": 4_000_000 } }],
+            "deadline": null,
+        }]);
+        millis_as_durations(&mut value);
+        let entries: Vec<WorkloadEntry> = serde_json::from_value(value).unwrap();
+        assert_eq!(entries[0].arrival_time, Duration::from_millis(1500));
+        assert_eq!(entries[0].cpu_burst_time, Duration::from_micros(2500));
+        assert_eq!(entries[0].priority, Some(3));
+        assert_eq!(entries[0].io_phases[0].at, Duration::from_millis(1));
+        assert_eq!(entries[0].io_phases[0].duration, Duration::from_millis(4));
+    }
+
+    #[test]
+    fn metrics_are_their_values() {
+        let mut value = json!({
+            "metrics": {
+                "average_waiting_time": { "DurationValue": { "secs": 0, "nanos": 203_970_000 } },
+                "cpu_utilization": { "PercentageValue": 52.5 },
+                "queue_discipline": { "StringValue": "RR" },
+            },
+        });
+        durations_as_millis(&mut value);
+        metrics_as_values(&mut value);
+        assert_eq!(value["metrics"]["average_waiting_time"], 203.97);
+        assert_eq!(value["metrics"]["cpu_utilization"], 52.5);
+        assert_eq!(value["metrics"]["queue_discipline"], "RR");
+    }
+}
@@ -1,11 +1,88 @@
-use std::time::Duration;
-use crate::cli::Queues;
-use tauri::Window;
+use mehrmux_engine::analytic::{self, Prediction};
+use mehrmux_engine::compare::{CompareArgs, Comparison};
+use mehrmux_engine::config::{GeneratorConfig, RunConfig};
+use mehrmux_engine::error::SimError;
+use mehrmux_engine::events::{self, EventLog, EventSink, SimEvent};
+use mehrmux_engine::exercise::{self, Exercise, ExerciseSpec};
+use mehrmux_engine::grading::{self, Grade, Homework};
+use mehrmux_engine::queue_engine::Queues;
+use mehrmux_engine::report;
+use mehrmux_engine::scenario::{ProcessSource, Scenario};
+use mehrmux_engine::sched_trace::{self, SchedTraceImport, SchedTraceOptions};
+use mehrmux_engine::session::{PlaybackSpeed, Session, SessionEvent, SessionId, SessionStatus, Sessions};
+use mehrmux_engine::simulator::RunResult;
+use mehrmux_engine::sweep::{self, SweepResult, SweepSpec};
+use mehrmux_engine::swf::{self, SwfOptions};
+use mehrmux_engine::workload::{self, TimeUnit, ValidationReport, WorkloadEntry, WorkloadFormat};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, sleep};
+use std::time::{Duration, SystemTime};
+use tauri::{Emitter, Manager, State, Window};
 
-mod cli;
-mod process_gen;
-mod queue_engine;
-mod simulator;
+// Utils -------------------------------------------------------------------------------------------
+
+/// The log of the last finished run, kept in Tauri's managed state so it can be exported.
+#[derive(Default)]
+struct LastEventLog(Mutex<Option<EventLog>>);
+
+/// Sends a run's events to the window that started it.
+struct WindowSink(Window);
+
+impl EventSink for WindowSink {
+    fn emit(&self, event: &str, payload: Value) -> Result<(), String> {
+        self.0.emit(event, payload).map_err(|e| e.to_string())
+    }
+}
+
+fn start_session(
+    config: &RunConfig,
+    window: &Window,
+    sessions: &Sessions,
+) -> Result<Arc<Session>, SimError> {
+    let sink = Arc::new(WindowSink(window.clone()));
+    sessions.start(config.queue.to_string(), sink, config.speed, config.paused)
+}
+
+/// Runs `scenario` in `session` on a thread of its own, the UI follows it through its events.
+fn spawn_run(window: Window, scenario: Scenario, session: Arc<Session>) {
+    thread::spawn(move || {
+        if let Ok(result) = scenario.run_in(session) {
+            // keeps a copy of the log around for export_event_log
+            let logs = window.state::<LastEventLog>();
+            *logs.0.lock().unwrap() = Some(result.log);
+        }
+    });
+}
+
+/// Sends the UI the same events, with the same timing, it got while the run was simulated.
+/// `replay_started` carries the original start time since the payloads' timestamps are the
+/// original ones too. The events carry no session, the replay isn't one.
+fn replay(window: Window, log: EventLog) {
+    thread::spawn(move || {
+        let mut elapsed = Duration::from_secs(0);
+        for logged in log.events {
+            if logged.time > elapsed {
+                sleep(logged.time - elapsed);
+                elapsed = logged.time;
+            }
+            let sent = match logged.event {
+                SimEvent::RunStarted { started_at, .. } => {
+                    window.emit("replay_started", started_at)
+                }
+                SimEvent::Emit { event, payload } => {
+                    window.emit(&event, SessionEvent { session: None, data: payload })
+                }
+                _ => Ok(()),
+            };
+            if sent.is_err() {
+                return; // the window is gone, there's nobody left to replay to or tell
+            }
+        }
+    });
+}
+
+// Commands ----------------------------------------------------------------------------------------
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -15,58 +92,255 @@ fn greet(name: &str) -> String {
 #[tauri::command]
 fn run_simulation(
     window: Window,
-    at_lambda: f64,
-    cbt_lambda: f64,
-    num_of_prcss: i32,
-    queue: &str,
-    context_switch: u64,
-    time_quantum: u64,
-    list_of_discipline: Option<[String; 4]>
-) {
-    let time_quantum = Duration::from_millis(time_quantum);
-    let context_switch = Duration::from_millis(context_switch);
-    let mut sim = simulator::Simulator::init(at_lambda, cbt_lambda);
-    let queue = match queue {
-        "FCFS" => Queues::FCFS,
-        "FIFO" => Queues::FIFO,
-        "SPN" => Queues::SPN,
-        "SJF" => Queues::SJF,
-        "HRRN" => Queues::HRRN,
-        "RR" => Queues::RR,
-        "SRTF" => Queues::SRF,
-        "MLQ" => Queues::MLQ,
-        "MLFQ" => Queues::MLFQ,
-        _ => Queues::FIFO
-    };
-    sim.run_simulate(num_of_prcss, queue, window, context_switch, time_quantum, list_of_discipline);
+    config: RunConfig,
+    generator: GeneratorConfig,
+    sessions: State<Sessions>,
+) -> Result<SessionId, SimError> {
+    let scenario = config.validate(generator.source())?;
+    let session = start_session(&config, &window, &sessions)?;
+    let id = session.id;
+    spawn_run(window, scenario, session);
+    Ok(id)
 }
 
 #[tauri::command]
 fn run_with_parameters(
     window: Window,
+    config: RunConfig,
     array_of_processes: Vec<(u64, u64, Option<String>)>,
-    queue: &str,
-    context_switch: u64,
-    time_quantum: u64,
-    list_of_discipline: Option<[String; 4]>,
-) {
-    let time_quantum = Duration::from_millis(time_quantum);
-    let context_switch = Duration::from_millis(context_switch);
-    println!("time_quantum: {:?}, contextsiwthc: {:?}", time_quantum, context_switch);
-    let mut sim = simulator::Simulator::init(1.0,1.0);
-    let queue = match queue {
-        "FCFS" => Queues::FCFS,
-        "FIFO" => Queues::FIFO,
-        "SPN" => Queues::SPN,
-        "SJF" => Queues::SJF,
-        "HRRN" => Queues::HRRN,
-        "RR" => Queues::RR,
-        "SRTF" => Queues::SRF,
-        "MLQ" => Queues::MLQ,
-        "MLFQ" => Queues::MLFQ,
-        _ => Queues::FIFO
+    sessions: State<Sessions>,
+) -> Result<SessionId, SimError> {
+    let scenario = config.validate_manual(&array_of_processes)?;
+    let session = start_session(&config, &window, &sessions)?;
+    let id = session.id;
+    spawn_run(window, scenario, session);
+    Ok(id)
+}
+
+#[tauri::command]
+fn cancel_simulation(id: SessionId, sessions: State<Sessions>) -> Result<SessionStatus, String> {
+    sessions.get(id)?.cancel()
+}
+
+#[tauri::command]
+fn pause_simulation(id: SessionId, sessions: State<Sessions>) -> Result<SessionStatus, String> {
+    sessions.get(id)?.pause()
+}
+
+#[tauri::command]
+fn resume_simulation(id: SessionId, sessions: State<Sessions>) -> Result<SessionStatus, String> {
+    sessions.get(id)?.resume()
+}
+
+#[tauri::command]
+fn step_simulation(id: SessionId, sessions: State<Sessions>) -> Result<SessionStatus, String> {
+    // runs the paused simulation up to its next scheduling decision
+    sessions.get(id)?.step()
+}
+
+#[tauri::command]
+fn set_simulation_speed(
+    id: SessionId,
+    speed: PlaybackSpeed,
+    sessions: State<Sessions>,
+) -> Result<SessionStatus, SimError> {
+    // a speed out of range is an invalid_config error, like a run config's
+    let session = sessions.get(id).map_err(|message| SimError::Session { message })?;
+    session.set_speed(speed)
+}
+
+#[tauri::command]
+fn simulation_status(id: SessionId, sessions: State<Sessions>) -> Result<SessionStatus, String> {
+    Ok(sessions.get(id)?.status())
+}
+
+#[tauri::command]
+fn list_simulations(sessions: State<Sessions>) -> Vec<SessionStatus> {
+    sessions.list()
+}
+
+#[tauri::command]
+fn simulation_result(id: SessionId, sessions: State<Sessions>) -> Result<Option<RunResult>, String> {
+    // None until the run is over
+    Ok(sessions.get(id)?.result())
+}
+
+#[tauri::command]
+fn validate_workload(
+    contents: String,
+    format: Option<String>,
+    unit: Option<String>,
+    swf: Option<SwfOptions>, // for SWF traces, like import_swf_trace's options
+) -> Result<ValidationReport, String> {
+    // numbers without a unit are milliseconds unless told otherwise, like in run_with_parameters
+    let format = WorkloadFormat::detect(format.as_deref(), &contents)?;
+    let unit = match unit {
+        Some(unit) => TimeUnit::from_name(&unit)
+            .ok_or(format!("Unknown time unit \"{}\", expected ms or s", unit))?,
+        None => TimeUnit::Milliseconds,
     };
-    sim.run_with_predefined_processes(queue, window, context_switch, time_quantum, array_of_processes, list_of_discipline)
+    Ok(workload::import_workload(&contents, format, unit, &swf.unwrap_or_default()))
+}
+
+#[tauri::command]
+fn import_swf_trace(contents: String, options: Option<SwfOptions>) -> ValidationReport {
+    // the report's processes can be passed to run_workload just like an imported CSV file
+    swf::import_swf(&contents, &options.unwrap_or_default())
+}
+
+#[tauri::command]
+fn import_sched_trace(contents: String, options: Option<SchedTraceOptions>) -> SchedTraceImport {
+    // the kernel's own timeline and metrics come back with the workload, running the workload
+    // through run_workload gives the numbers to compare them with
+    sched_trace::import_sched_trace(&contents, &options.unwrap_or_default())
+}
+
+#[tauri::command]
+fn run_workload(
+    window: Window,
+    config: RunConfig,
+    workload: Vec<WorkloadEntry>,
+    sessions: State<Sessions>,
+) -> Result<SessionId, SimError> {
+    let scenario = config.validate(ProcessSource::Workload(workload))?;
+    let session = start_session(&config, &window, &sessions)?;
+    let id = session.id;
+    spawn_run(window, scenario, session);
+    Ok(id)
+}
+
+#[tauri::command]
+async fn compare_algorithms(
+    config: RunConfig,
+    generator: Option<GeneratorConfig>,
+    workload: Option<Vec<WorkloadEntry>>,
+    queues: Vec<Queues>,
+    rank_by: Option<String>,
+) -> Result<Comparison, SimError> {
+    // one workload, generated or imported, goes through every queue in `queues`. The runs are
+    // headless so the result comes back at once instead of through events.
+    let args = CompareArgs {
+        config,
+        generator,
+        workload,
+        queues,
+        rank_by,
+    };
+    args.compare(SystemTime::now())
+}
+
+#[tauri::command]
+async fn run_sweep(spec: SweepSpec, csv_path: Option<String>) -> Result<SweepResult, String> {
+    // the grid comes back as JSON, and is also written as CSV when a path is given
+    let result = sweep::run_sweep(&spec)?;
+    if let Some(path) = csv_path {
+        std::fs::write(&path, result.to_csv()).map_err(|e| e.to_string())?;
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+async fn grade_homework(homework: Homework) -> Result<Grade, SimError> {
+    // the student's Gantt chart and metrics against a seeded run of the same processes
+    grading::grade(&homework)
+}
+
+#[tauri::command]
+async fn generate_exercise(spec: ExerciseSpec, markdown_path: Option<String>) -> Result<Exercise, String> {
+    // the exercise comes back as JSON, and is also written as Markdown when a path is given
+    let exercise = exercise::generate(&spec)?;
+    if let Some(path) = markdown_path {
+        std::fs::write(&path, exercise.to_markdown()).map_err(|e| e.to_string())?;
+    }
+    Ok(exercise)
+}
+
+#[tauri::command]
+fn analytic_baselines(
+    at_lambda: f64,
+    cbt_lambda: f64,
+    servers: Option<u32>,
+) -> Result<Vec<Prediction>, String> {
+    // the closed form models for the rates run_simulation generates processes with
+    if at_lambda <= 0.0 || cbt_lambda <= 0.0 {
+        return Err(String::from("Rate parameter must be positive"));
+    }
+    Ok(analytic::baselines(at_lambda, cbt_lambda, servers.unwrap_or(1)))
+}
+
+/// The log of the given session, or of the last finished run when no session is given.
+fn chosen_log(
+    session: Option<SessionId>,
+    sessions: &Sessions,
+    last_log: &LastEventLog,
+) -> Result<EventLog, String> {
+    match session {
+        Some(id) => sessions
+            .get(id)?
+            .log()
+            .ok_or(format!("Simulation {} hasn't finished yet", id)),
+        None => last_log
+            .0
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or(String::from("No simulation has finished yet")),
+    }
+}
+
+#[tauri::command]
+fn export_event_log(
+    path: String,
+    session: Option<SessionId>,
+    sessions: State<Sessions>,
+    last_log: State<LastEventLog>,
+) -> Result<usize, String> {
+    // writes the log as JSON Lines and returns how many events it had
+    let log = chosen_log(session, &sessions, &last_log)?;
+    events::export_json_lines(&log, &path)
+}
+
+#[tauri::command]
+fn export_gantt_svg(
+    path: String,
+    session: Option<SessionId>,
+    sessions: State<Sessions>,
+    last_log: State<LastEventLog>,
+) -> Result<(), String> {
+    let log = chosen_log(session, &sessions, &last_log)?;
+    let svg = report::render_svg(&report::bars_from_log(&log));
+    std::fs::write(&path, svg).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn export_html_report(
+    path: String,
+    session: Option<SessionId>,
+    sessions: State<Sessions>,
+    last_log: State<LastEventLog>,
+) -> Result<(), String> {
+    let log = chosen_log(session, &sessions, &last_log)?;
+    let html = report::render_html("Mehrmux Simulation Report", &log);
+    std::fs::write(&path, html).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn export_kernel_gantt_svg(
+    contents: String,
+    options: Option<SchedTraceOptions>,
+    path: String,
+) -> Result<(), String> {
+    // the same chart for the kernel's schedule of an imported sched trace, one lane per core
+    let import = sched_trace::import_sched_trace(&contents, &options.unwrap_or_default());
+    let svg = report::render_svg(&report::bars_from_timeline(&import.timeline));
+    std::fs::write(&path, svg).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn replay_event_log(window: Window, path: String) -> Result<(), String> {
+    let log = events::read_json_lines(&path)?;
+    replay(window, log);
+    Ok(())
 }
 
 #[tauri::command]
@@ -92,10 +366,34 @@ fn on_min(window: Window) {
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .manage(LastEventLog::default())
+        .manage(Sessions::default())
         .invoke_handler(tauri::generate_handler![
             greet,
             run_simulation,
             run_with_parameters,
+            cancel_simulation,
+            pause_simulation,
+            resume_simulation,
+            step_simulation,
+            set_simulation_speed,
+            simulation_status,
+            list_simulations,
+            simulation_result,
+            validate_workload,
+            import_swf_trace,
+            import_sched_trace,
+            run_workload,
+            compare_algorithms,
+            run_sweep,
+            grade_homework,
+            generate_exercise,
+            analytic_baselines,
+            export_event_log,
+            export_gantt_svg,
+            export_html_report,
+            export_kernel_gantt_svg,
+            replay_event_log,
             on_exit,
             on_max,
             on_min
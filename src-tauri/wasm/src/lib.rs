@@ -0,0 +1,125 @@
+//! The Mehrmux engine for the browser, built with `wasm-pack build --target web`.
+//!
+//! The functions are named after the app's Tauri commands and take the same arguments, as plain
+//! JS objects, so the React components can call them instead of `invoke`. Values go in and out
+//! through JSON, they look exactly like what the commands send and return.
+//!
+//! There are no threads or real sleeps in the browser, so runs are headless: a run function
+//! returns once the run is over, with its metrics, its processes and `events`, the event log of
+//! the run. Its `emit` events are the events the app gets from a live run, with the same
+//! payloads, and their `time` is when they were sent, so the UI can play them back the way it
+//! replays a saved event log.
+
+use mehrmux_engine::compare::CompareArgs;
+use mehrmux_engine::config::{GeneratorConfig, RunConfig};
+use mehrmux_engine::error::SimError;
+use mehrmux_engine::events::LoggedEvent;
+use mehrmux_engine::exercise::{self, ExerciseSpec};
+use mehrmux_engine::grading::{self, Homework};
+use mehrmux_engine::scenario::{ProcessSource, Scenario};
+use mehrmux_engine::simulator::RunResult;
+use mehrmux_engine::workload::WorkloadEntry;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::{Duration, SystemTime};
+use wasm_bindgen::prelude::*;
+
+// Utils -------------------------------------------------------------------------------------------
+
+/// A finished run, with the log the UI plays back.
+#[derive(Serialize)]
+pub struct BrowserRun {
+    #[serde(flatten)]
+    pub result: RunResult,
+    pub events: Vec<LoggedEvent>,
+}
+
+impl From<RunResult> for BrowserRun {
+    fn from(mut result: RunResult) -> Self {
+        let events = std::mem::take(&mut result.log.events);
+        BrowserRun { result, events }
+    }
+}
+
+fn from_js<T: DeserializeOwned>(value: JsValue) -> Result<T, JsValue> {
+    let json: String = js_sys::JSON::stringify(&value)?.into();
+    serde_json::from_str(&json).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    let json = serde_json::to_string(value).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    js_sys::JSON::parse(&json)
+}
+
+fn sim_error(error: SimError) -> JsValue {
+    // the same object the app's run commands reject with
+    to_js(&error).unwrap_or_else(|e| e)
+}
+
+/// `SystemTime::now` isn't there on `wasm32-unknown-unknown`, the browser's clock is.
+fn now() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+}
+
+fn run(scenario: Scenario) -> Result<JsValue, JsValue> {
+    let result = scenario.run_from(now()).map_err(sim_error)?;
+    to_js(&BrowserRun::from(result))
+}
+
+// Runs --------------------------------------------------------------------------------------------
+
+#[wasm_bindgen]
+pub fn run_simulation(config: JsValue, generator: JsValue) -> Result<JsValue, JsValue> {
+    let config: RunConfig = from_js(config)?;
+    let generator: GeneratorConfig = from_js(generator)?;
+    run(config.validate(generator.source()).map_err(|e| sim_error(e.into()))?)
+}
+
+#[wasm_bindgen]
+pub fn run_with_parameters(config: JsValue, array_of_processes: JsValue) -> Result<JsValue, JsValue> {
+    let config: RunConfig = from_js(config)?;
+    let array_of_processes: Vec<(u64, u64, Option<String>)> = from_js(array_of_processes)?;
+    run(config.validate_manual(&array_of_processes).map_err(|e| sim_error(e.into()))?)
+}
+
+#[wasm_bindgen]
+pub fn run_workload(config: JsValue, workload: JsValue) -> Result<JsValue, JsValue> {
+    let config: RunConfig = from_js(config)?;
+    let workload: Vec<WorkloadEntry> = from_js(workload)?;
+    run(config.validate(ProcessSource::Workload(workload)).map_err(|e| sim_error(e.into()))?)
+}
+
+// Comparison --------------------------------------------------------------------------------------
+
+/// One workload through every queue in `args.queues`, like the app's `compare_algorithms`.
+#[wasm_bindgen]
+pub fn compare_algorithms(args: JsValue) -> Result<JsValue, JsValue> {
+    let args: CompareArgs = from_js(args)?;
+    let comparison = args.compare(now()).map_err(sim_error)?;
+    to_js(&comparison)
+}
+
+// Grading -----------------------------------------------------------------------------------------
+
+#[wasm_bindgen]
+pub fn grade_homework(homework: JsValue) -> Result<JsValue, JsValue> {
+    let homework: Homework = from_js(homework)?;
+    let grade = grading::grade(&homework).map_err(sim_error)?;
+    to_js(&grade)
+}
+
+// Exercises ---------------------------------------------------------------------------------------
+
+#[wasm_bindgen]
+pub fn generate_exercise(spec: JsValue) -> Result<JsValue, JsValue> {
+    let spec: ExerciseSpec = from_js(spec)?;
+    let exercise = exercise::generate(&spec).map_err(|e| JsValue::from_str(&e))?;
+    to_js(&exercise)
+}
+
+#[wasm_bindgen]
+pub fn exercise_markdown(spec: JsValue) -> Result<String, JsValue> {
+    let spec: ExerciseSpec = from_js(spec)?;
+    let exercise = exercise::generate(&spec).map_err(|e| JsValue::from_str(&e))?;
+    Ok(exercise.to_markdown())
+}
@@ -0,0 +1,48 @@
+//! Runs in Node.js with `cargo test --target wasm32-unknown-unknown`, see the README.
+
+#![cfg(target_arch = "wasm32")]
+
+use js_sys::{Array, Reflect, JSON};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+fn js(json: &str) -> JsValue {
+    JSON::parse(json).unwrap()
+}
+
+fn get(value: &JsValue, key: &str) -> JsValue {
+    Reflect::get(value, &JsValue::from_str(key)).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn runs_a_generated_workload() {
+    let config = js(r#"{"queue": "RR", "time_quantum": 10, "context_switch": 1}"#);
+    let generator = js(r#"{"arrival_lambda": 1.0, "burst_lambda": 2.0, "processes": 20}"#);
+    let run = mehrmux_wasm::run_simulation(config, generator).unwrap();
+
+    assert_eq!(Array::from(&get(&run, "processes")).length(), 20);
+    let events = Array::from(&get(&run, "events"));
+    let first = events.get(0);
+    assert_eq!(get(&first, "kind").as_string().as_deref(), Some("run_started"));
+    assert!(events.iter().any(|event| get(&event, "kind").as_string().as_deref() == Some("emit")));
+}
+
+#[wasm_bindgen_test]
+fn rejects_an_invalid_config_like_the_app() {
+    let config = js(r#"{"queue": "RR"}"#);
+    let generator = js(r#"{"arrival_lambda": -1.0, "burst_lambda": 2.0, "processes": 20}"#);
+    let error = mehrmux_wasm::run_simulation(config, generator).unwrap_err();
+    assert_eq!(get(&error, "kind").as_string().as_deref(), Some("invalid_config"));
+}
+
+#[wasm_bindgen_test]
+fn compares_algorithms_on_one_workload() {
+    let args = js(r#"{
+        "config": { "queue": "RR", "time_quantum": 10 },
+        "generator": { "arrival_lambda": 1.0, "burst_lambda": 2.0, "processes": 15 },
+        "queues": ["FCFS", "SJF", "RR"], "rankBy": null
+    }"#);
+    let comparison = mehrmux_wasm::compare_algorithms(args).unwrap();
+    assert_eq!(Array::from(&get(&comparison, "ranking")).length(), 3);
+    assert_eq!(Array::from(&get(&comparison, "processes")).length(), 15);
+}
@@ -5,20 +5,32 @@ import AlgoChoose from "./Components/algoChoose.tsx";
 import GanttCont from "./Components/ganttCont.tsx";
 import IoCont from "./Components/ioCont.jsx";
 import { listen } from "@tauri-apps/api/event";
-import useStore from "./ui_storage.tsx";
+import useStore, { isCurrentSession } from "./ui_storage.tsx";
 
 function App() {
   const setProcesses = useStore((state) => state.setProcesses);
+  const setStartingDate = useStore((state) => state.setStartingDate);
+  const setRestartChart = useStore((state) => state.setRestartChart);
+  const setSessionId = useStore((state) => state.setSessionId);
 
   useEffect(() => {
     const unlisten = listen("update_process", (event) => {
+      if (!isCurrentSession(event.payload)) return;
       setProcesses(() => [
-        ...event.payload, // Assuming event.payload is the Process object
+        ...event.payload.data, // Assuming event.payload.data is the Process object
       ]);
     });
 
+    // a replayed run carries its original timestamps, so the chart starts from the original date
+    const unlistenReplay = listen("replay_started", (event) => {
+      setRestartChart(!useStore.getState().restartChart);
+      setSessionId(null); // replayed events carry no session
+      setStartingDate(new Date(event.payload));
+    });
+
     return () => {
       unlisten.then((fn) => fn());
+      unlistenReplay.then((fn) => fn());
     };
   }, []);
 
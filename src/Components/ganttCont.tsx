@@ -2,7 +2,7 @@ import { useEffect, useState, useRef } from "react";
 import styles from "./../styles/ganttchart.module.css";
 import ChromeDinoGame from "react-chrome-dino";
 import { listen } from "@tauri-apps/api/event";
-import useStore from "../ui_storage.tsx";
+import useStore, { isCurrentSession, SessionEvent } from "../ui_storage.tsx";
 import * as d3 from "d3";
 import { Process, Time } from "../ui_storage.tsx";
 
@@ -10,16 +10,18 @@ import { Process, Time } from "../ui_storage.tsx";
 
 interface ProcessStoppedEvent {
   event: String; // type of event
-  payload: [
-    number, // self.queue_number (It's the number of  queue)
-    Process
-  ];
+  payload: SessionEvent<
+    [
+      number, // self.queue_number (It's the number of  queue)
+      Process
+    ]
+  >;
   id: number;
 }
 
 interface FinishedProcessEvent {
   event: String; // type of event
-  payload: [Process];
+  payload: SessionEvent<[Process]>;
   id: number;
 }
 
@@ -63,17 +65,19 @@ export default function GanttCont() {
     const unlistenPS = listen(
       "process_stopped",
       (event: ProcessStoppedEvent) => {
-        setProcessEvent(event.payload);
+        if (!isCurrentSession(event.payload)) return;
+        setProcessEvent(event.payload.data);
       }
     );
 
     const unlistenPF = listen(
       "finished_process",
       (event: FinishedProcessEvent) => {
+        if (!isCurrentSession(event.payload)) return;
         setFinishedP((prev) =>
           selectedAlgo === "MLFQ" || selectedAlgo === "MLQ"
-            ? [...prev, ...event.payload]
-            : [...event.payload]
+            ? [...prev, ...event.payload.data]
+            : [...event.payload.data]
         );
       }
     );
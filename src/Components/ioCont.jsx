@@ -16,7 +16,7 @@ import {
 
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import useStore from "../ui_storage.tsx";
+import useStore, { isCurrentSession } from "../ui_storage.tsx";
 
 export default function IoCont() {
   const selectedAlgo = useStore((state) => state.selectedAlgo);
@@ -42,6 +42,9 @@ export default function IoCont() {
   const refMQ_CS = useRef(null);
   const refMQ_TS = useRef(null);
   const [isRunning, setRunning] = React.useState(false);
+  const sessionId = useStore((state) => state.sessionId);
+  const setSessionId = useStore((state) => state.setSessionId);
+  const [speed, setSpeed] = React.useState("1");
   const [whichTab, setTab] = React.useState(0);
   const [nextForm, setNextForm] = React.useState(true);
   const [to_be_generated_processes, setToBeGeneratedProcesses] = useState([]);
@@ -72,13 +75,15 @@ export default function IoCont() {
 
   useEffect(() => {
     const unlistenMetrics = listen("send_metrics", (event) => {
+      if (!isCurrentSession(event.payload)) return;
+      const payload = event.payload.data;
       let tt = { subject: "TT" };
       let wt = { subject: "WT" };
       let rt = { subject: "RT" };
       let cu = { subject: "CU" };
       console.log("data is sent (metrics is sent)");
-      console.log(event.payload);
-      let metrics = event.payload;
+      console.log(payload);
+      let metrics = payload;
       let copyOfMC = multiChart.map((e) => e);
       for (let queue of copyOfMC) {
         if (queue.name == metrics.queue_discipline.StringValue) {
@@ -111,6 +116,8 @@ export default function IoCont() {
       setRunning(false);
     });
     const unlistenMetrics2 = listen("send_metrics_mlq", (event) => {
+      if (!isCurrentSession(event.payload)) return;
+      const payload = event.payload.data;
       let tt = { subject: "TT", TT: 0 };
       let wt = { subject: "WT", WT: 0 };
       let rt = { subject: "RT", RT: 0 };
@@ -119,7 +126,7 @@ export default function IoCont() {
       for (let queue of copyOfMC) {
         if (queue.name == "MLQ") {
           try {
-            for (let data of event.payload) {
+            for (let data of payload) {
               tt["TT"] += parseFloat(
                 turnToNormalDuration(data.average_turnaround_time.DurationValue)
               );
@@ -133,10 +140,10 @@ export default function IoCont() {
                 data.cpu_utilization.PercentageValue.toFixed(2)
               );
             }
-            tt["TT"] /= event.payload.length;
-            wt["WT"] /= event.payload.length;
-            rt["RT"] /= event.payload.length;
-            cu["CU"] /= event.payload.length;
+            tt["TT"] /= payload.length;
+            wt["WT"] /= payload.length;
+            rt["RT"] /= payload.length;
+            cu["CU"] /= payload.length;
             queue["TT"] = tt["TT"];
             queue["WT"] = wt["WT"];
             queue["RT"] = rt["RT"];
@@ -154,6 +161,8 @@ export default function IoCont() {
       setRunning(false);
     });
     const unlistenMetrics3 = listen("send_metrics_mlfq", (event) => {
+      if (!isCurrentSession(event.payload)) return;
+      const payload = event.payload.data;
       let tt = { subject: "TT", TT: 0 };
       let wt = { subject: "WT", WT: 0 };
       let rt = { subject: "RT", RT: 0 };
@@ -162,7 +171,7 @@ export default function IoCont() {
       for (let queue of copyOfMC) {
         if (queue.name == "MLFQ") {
           try {
-            for (let data of event.payload) {
+            for (let data of payload) {
               tt["TT"] += parseFloat(
                 turnToNormalDuration(data.average_turnaround_time.DurationValue)
               );
@@ -176,10 +185,10 @@ export default function IoCont() {
                 data.cpu_utilization.PercentageValue.toFixed(2)
               );
             }
-            tt["TT"] /= event.payload.length;
-            wt["WT"] /= event.payload.length;
-            rt["RT"] /= event.payload.length;
-            cu["CU"] /= event.payload.length;
+            tt["TT"] /= payload.length;
+            wt["WT"] /= payload.length;
+            rt["RT"] /= payload.length;
+            cu["CU"] /= payload.length;
             queue["TT"] = tt["TT"];
             queue["WT"] = wt["WT"];
             queue["RT"] = rt["RT"];
@@ -197,10 +206,17 @@ export default function IoCont() {
       setRunning(false);
     });
 
+    // a run that stops on an error sends why instead of its metrics
+    const unlistenFailed = listen("simulation_failed", (event) => {
+      if (!isCurrentSession(event.payload)) return;
+      notify_this(event.payload.data.message, true);
+      setRunning(false);
+    });
     return () => {
       unlistenMetrics.then((fn) => fn());
       unlistenMetrics2.then((fn) => fn());
       unlistenMetrics3.then((fn) => fn());
+      unlistenFailed.then((fn) => fn());
     };
   }, []);
   useEffect(() => {
@@ -245,6 +261,41 @@ export default function IoCont() {
         return true;
     }
   };
+  // "max" plays the run as fast as it can, any other value is a multiple of real time
+  const playbackSpeed = (value) =>
+    value == "max" ? "max" : { times: parseFloat(value) };
+
+  // a rejected run comes back with every field that's wrong, or a plain message
+  const describeError = (error) =>
+    error.kind == "invalid_config"
+      ? error.fields.map((e) => `${e.field}: ${e.message}`).join("\n")
+      : error.message ?? String(error);
+
+  // the run starts paused so this pane knows its session before any of its events arrive
+  const startSession = async (command, config, args) => {
+    try {
+      const id = await invoke(command, {
+        ...args,
+        config: { ...config, speed: playbackSpeed(speed), paused: true },
+      });
+      setSessionId(id);
+      await invoke("resume_simulation", { id });
+    } catch (error) {
+      notify_this(describeError(error), true);
+      setRunning(false);
+    }
+  };
+
+  const on_speed_change = async (e) => {
+    setSpeed(e.target.value);
+    if (isRunning && sessionId != null) {
+      await invoke("set_simulation_speed", {
+        id: sessionId,
+        speed: playbackSpeed(e.target.value),
+      }).catch((error) => notify_this(error, true));
+    }
+  };
+
   const on_start_click = async (e) => {
     e.preventDefault();
     if (isRunning) {
@@ -300,27 +351,23 @@ export default function IoCont() {
     restartChart(!restart);
     handleDate(new Date());
     console.log({ at, cbt, np, selectedAlgo, cs, ts });
-    if (selectedAlgo == "MLQ" || selectedAlgo == "MLFQ") {
-      await invoke("run_simulation", {
-        atLambda: parseFloat(at),
-        cbtLambda: parseFloat(cbt),
-        numOfPrcss: parseInt(np),
-        contextSwitch: parseInt(cs),
-        queue: selectedAlgo,
-        timeQuantum: parseInt(ts),
-        listOfDiscipline: ["RR", "RR", "RR", "FCFS"],
-      });
-    } else {
-      await invoke("run_simulation", {
-        atLambda: parseFloat(at),
-        cbtLambda: parseFloat(cbt),
-        numOfPrcss: parseInt(np),
-        contextSwitch: parseInt(cs),
+    const multilevel = selectedAlgo == "MLQ" || selectedAlgo == "MLFQ";
+    await startSession(
+      "run_simulation",
+      {
         queue: selectedAlgo,
-        timeQuantum: parseInt(ts),
-        listOfDiscipline: null,
-      });
-    }
+        context_switch: parseInt(cs),
+        time_quantum: parseInt(ts) || 0,
+        disciplines: multilevel ? ["RR", "RR", "RR", "FCFS"] : null,
+      },
+      {
+        generator: {
+          arrival_lambda: parseFloat(at),
+          burst_lambda: parseFloat(cbt),
+          processes: parseInt(np),
+        },
+      }
+    );
   };
 
   const on_manual_start_click = async (e) => {
@@ -388,25 +435,19 @@ export default function IoCont() {
     setRunning(true);
     restartChart(!restart);
     handleDate(new Date());
-    if (selectedAlgo == "MLQ" || selectedAlgo == "MLFQ") {
-      await invoke("run_with_parameters", {
-        arrayOfProcesses: to_be_generated_processes,
-        queue: selectedAlgo,
-        contextSwitch: parseFloat(cs),
-        timeQuantum: parseFloat(ts),
-        listOfDiscipline: [selectedQ1, selectedQ2, selectedQ3, selectedQ4],
-        selectedProcessType: pt,
-      });
-    } else {
-      await invoke("run_with_parameters", {
-        arrayOfProcesses: to_be_generated_processes,
+    const multilevel = selectedAlgo == "MLQ" || selectedAlgo == "MLFQ";
+    await startSession(
+      "run_with_parameters",
+      {
         queue: selectedAlgo,
-        contextSwitch: parseFloat(cs),
-        timeQuantum: parseFloat(ts),
-        listOfDiscipline: null,
-        selectedProcessType: pt,
-      });
-    }
+        context_switch: parseInt(cs),
+        time_quantum: parseInt(ts),
+        disciplines: multilevel
+          ? [selectedQ1, selectedQ2, selectedQ3, selectedQ4]
+          : null,
+      },
+      { arrayOfProcesses: to_be_generated_processes }
+    );
   };
   const on_manual_add_click = (e) => {
     e.preventDefault();
@@ -579,6 +620,16 @@ export default function IoCont() {
                 <label htmlFor="ts">Time Slice (ms):</label>
                 <input id="ts" ref={ref_TS} type="number" defaultValue={1000} />
               </div>
+              <div>
+                <label htmlFor="speed">Playback Speed:</label>
+                <select id="speed" value={speed} onChange={on_speed_change}>
+                  <option value="0.1">0.1×</option>
+                  <option value="1">1×</option>
+                  <option value="10">10×</option>
+                  <option value="100">100×</option>
+                  <option value="max">As Fast As Possible</option>
+                </select>
+              </div>
               <div>
                 <input
                   id="start"
@@ -8,7 +8,7 @@ function ProcessMonitor() {
     useEffect(() => {
         const unlisten = listen('update_process', (event) => {
             setProcesses((prevProcesses) => [
-                ...event.payload, // Assuming event.payload is the Process object
+                ...event.payload.data, // Assuming event.payload.data is the Process object
             ]);
         });
 
@@ -23,6 +23,12 @@ export interface Process {
   metrics: Metrics;
 }
 
+// every event of a run comes wrapped with the session it belongs to, a replay has none
+export interface SessionEvent<T> {
+  session: number | null;
+  data: T;
+}
+
 interface StoreState {
   selectedAlgo: string;
   setSelectedAlgo: (algo: string) => void;
@@ -35,6 +41,9 @@ interface StoreState {
 
   startingDate: Date | null;
   setStartingDate: (value: Date | null) => void;
+
+  sessionId: number | null;
+  setSessionId: (value: number | null) => void;
 }
 
 const useStore = create<StoreState>((set) => ({
@@ -49,6 +58,13 @@ const useStore = create<StoreState>((set) => ({
 
   startingDate: null,
   setStartingDate: (value) => set({ startingDate: value }),
+
+  sessionId: null,
+  setSessionId: (value) => set({ sessionId: value }),
 }));
 
+// other runs may be going at the same time, this pane only shows the one it started
+export const isCurrentSession = (event: SessionEvent<unknown>) =>
+  event.session === useStore.getState().sessionId;
+
 export default useStore;